@@ -1,9 +1,17 @@
 //! Swap calculations
 
-use crate::curve::{constant_product::ConstantProductCurve, fees::Fees};
+use crate::curve::{
+    constant_product::ConstantProductCurve, fees::Fees, stable::StableCurve, weighted::WeightedCurve,
+};
+use crate::utils::U256;
 use anchor_lang::prelude::*;
+use spl_math::precise_number::PreciseNumber;
 use {crate::error::ErrorCode, std::fmt::Debug};
 
+/// The most search steps `deposit_single_token` takes after bracketing the
+/// swapped amount, each step runs one swap through the curve
+pub const DEPOSIT_SINGLE_TOKEN_MAX_STEPS: usize = 24;
+
 /// Helper function for mapping to ErrorCode::CalculationFailure
 pub fn map_zero_to_none(x: u128) -> Option<u128> {
     if x == 0 {
@@ -15,7 +23,7 @@ pub fn map_zero_to_none(x: u128) -> Option<u128> {
 
 /// The direction of a trade, since curves can be specialized to treat each
 /// token differently (by adding offsets or weights)
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
 pub enum TradeDirection {
     /// Input token 0, output token 1
     ZeroForOne,
@@ -45,6 +53,48 @@ impl TradeDirection {
     }
 }
 
+/// The curve types a pool can be created with
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CurveType {
+    /// x * y = k
+    ConstantProduct,
+    /// StableSwap invariant, curve_params: [amp, 0]
+    Stable,
+    /// x^weight_0 * y^weight_1 = k, curve_params: [weight_0, weight_1]
+    Weighted,
+}
+
+impl CurveType {
+    pub fn from_u8(curve_type: u8) -> Result<Self> {
+        match curve_type {
+            0 => Ok(CurveType::ConstantProduct),
+            1 => Ok(CurveType::Stable),
+            2 => Ok(CurveType::Weighted),
+            _ => err!(ErrorCode::InvalidCurve),
+        }
+    }
+
+    /// Build the calculator of the curve from the params stored in the pool
+    pub fn calculator(self, curve_params: [u64; 2]) -> Result<Box<dyn CurveCalculator>> {
+        match self {
+            CurveType::ConstantProduct => {
+                require!(curve_params == [0, 0], ErrorCode::InvalidCurve);
+                Ok(Box::new(ConstantProductCurve))
+            }
+            CurveType::Stable => {
+                require!(curve_params[1] == 0, ErrorCode::InvalidCurve);
+                Ok(Box::new(
+                    StableCurve::new(curve_params[0]).ok_or(ErrorCode::InvalidCurve)?,
+                ))
+            }
+            CurveType::Weighted => Ok(Box::new(
+                WeightedCurve::new(curve_params[0], curve_params[1])
+                    .ok_or(ErrorCode::InvalidCurve)?,
+            )),
+        }
+    }
+}
+
 /// Encodes results of depositing both sides at once
 #[derive(Debug, PartialEq)]
 pub struct TradingTokenResult {
@@ -67,18 +117,106 @@ pub struct SwapResult {
     pub destination_amount_swapped: u128,
     /// Amount of source tokens going to pool holders
     pub trade_fee: u128,
-    /*/// Amount of source tokens going to protocol
+    /// Amount of source tokens going to protocol
     pub protocol_fee: u128,
     /// Amount of source tokens going to protocol team
-    pub fund_fee: u128, */
+    pub fund_fee: u128,
 }
 
-/// Concrete struct to wrap around the trait object which performs calculation.
-#[derive(Clone, Debug, Default, PartialEq)]
-pub struct CurveCalculator {}
+/// Encodes results of depositing a single token, part of which is swapped
+/// into the other side of the pool
+#[derive(Debug, PartialEq)]
+pub struct SingleTokenDepositResult {
+    /// Amount of lp tokens to mint
+    pub lp_token_amount: u128,
+    /// Result of swapping the portion of the source token
+    pub swap_result: SwapResult,
+    /// Creator's share of the swap trade fee
+    pub creator_fee: u128,
+}
 
-impl CurveCalculator {
-    pub fn validate_supply(token_0_amount: u64, token_1_amount: u64) -> Result<()> {
+/// Encodes results of withdrawing into a single token, the withdrawn source
+/// token being swapped into the destination token
+#[derive(Debug, PartialEq)]
+pub struct SingleTokenWithdrawResult {
+    /// Amount of source token withdrawn and then swapped
+    pub source_amount_withdrawn: u128,
+    /// Amount of destination token withdrawn
+    pub destination_amount_withdrawn: u128,
+    /// Total amount of destination token paid out
+    pub destination_amount: u128,
+    /// Result of swapping the withdrawn source token
+    pub swap_result: SwapResult,
+    /// Creator's share of the swap trade fee
+    pub creator_fee: u128,
+}
+
+/// Trait representing operations required on a swap curve
+pub trait CurveCalculator: Debug {
+    /// Calculate how much destination token will be provided given an amount
+    /// of source token.
+    fn swap_base_input_without_fees(
+        &self,
+        source_amount: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        trade_direction: TradeDirection,
+    ) -> Option<u128>;
+
+    /// Calculate how much source token is needed to get an amount of
+    /// destination token.
+    fn swap_base_output_without_fees(
+        &self,
+        destinsation_amount: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        trade_direction: TradeDirection,
+    ) -> Option<u128>;
+
+    /// Get the amount of trading tokens for the given amount of pool tokens,
+    /// provided the total trading tokens and supply of pool tokens.
+    fn lp_tokens_to_trading_tokens(
+        &self,
+        lp_token_amount: u128,
+        lp_token_supply: u128,
+        swap_token_0_amount: u128,
+        swap_token_1_amount: u128,
+        round_direction: RoundDirection,
+    ) -> Option<TradingTokenResult>;
+
+    /// Calculates the total normalized value of the curve given the liquidity
+    /// parameters.
+    fn normalized_value(
+        &self,
+        swap_token_0_amount: u128,
+        swap_token_1_amount: u128,
+    ) -> Option<PreciseNumber>;
+
+    /// The exact invariant of the curve, which must not decrease from a swap
+    /// once the trade fee is excluded.
+    fn invariant(&self, swap_token_0_amount: u128, swap_token_1_amount: u128) -> Option<u128>;
+
+    /// Get the largest amount of pool tokens that the given amounts of trading
+    /// tokens can pay for, with the amounts rounded up as on deposit.
+    fn trading_tokens_to_lp_tokens(
+        &self,
+        token_0_amount: u128,
+        token_1_amount: u128,
+        lp_token_supply: u128,
+        swap_token_0_amount: u128,
+        swap_token_1_amount: u128,
+    ) -> Option<u128> {
+        let lp_from_token_0 = token_0_amount
+            .checked_mul(lp_token_supply)?
+            .checked_div(swap_token_0_amount)?;
+        let lp_from_token_1 = token_1_amount
+            .checked_mul(lp_token_supply)?
+            .checked_div(swap_token_1_amount)?;
+        map_zero_to_none(lp_from_token_0.min(lp_from_token_1))
+    }
+
+    /// Validate that the given supply on initialization is valid for the curve
+    fn validate_supply(&self, token_0_amount: u64, token_1_amount: u64) -> Result<()> {
         if token_0_amount == 0 {
             return Err(ErrorCode::EmptySupply.into());
         }
@@ -90,26 +228,30 @@ impl CurveCalculator {
 
     /// Subtract fees and calculate how much destination token will be provided
     /// given an amount of source token.
-    pub fn swap_base_input(
+    #[allow(clippy::too_many_arguments)]
+    fn swap_base_input(
+        &self,
         source_amount: u128,
         swap_source_amount: u128,
         swap_destination_amount: u128,
+        trade_direction: TradeDirection,
         trade_fee_rate: u64,
-        // protocol_fee_rate: u64,
-        // fund_fee_rate: u64,
+        protocol_fee_rate: u64,
+        fund_fee_rate: u64,
     ) -> Option<SwapResult> {
         // debit the fee to calculate the amount swapped
         let trade_fee = Fees::trading_fee(source_amount, trade_fee_rate)?;
-        // let protocol_fee = Fees::protocol_fee(trade_fee, protocol_fee_rate)?;
-        // let fund_fee = Fees::fund_fee(trade_fee, fund_fee_rate)?;
+        let protocol_fee = Fees::protocol_fee(trade_fee, protocol_fee_rate)?;
+        let fund_fee = Fees::fund_fee(trade_fee, fund_fee_rate)?;
 
         let source_amount_less_fees = source_amount.checked_sub(trade_fee)?;
 
-        let destination_amount_swapped = ConstantProductCurve::swap_base_input_without_fees(
+        let destination_amount_swapped = self.swap_base_input_without_fees(
             source_amount_less_fees,
             swap_source_amount,
             swap_destination_amount,
-        );
+            trade_direction,
+        )?;
 
         Some(SwapResult {
             new_swap_source_amount: swap_source_amount.checked_add(source_amount)?,
@@ -118,30 +260,34 @@ impl CurveCalculator {
             source_amount_swapped: source_amount,
             destination_amount_swapped,
             trade_fee,
-            // protocol_fee,
-            // fund_fee,
+            protocol_fee,
+            fund_fee,
         })
     }
 
-    pub fn swap_base_output(
+    #[allow(clippy::too_many_arguments)]
+    fn swap_base_output(
+        &self,
         destinsation_amount: u128,
         swap_source_amount: u128,
         swap_destination_amount: u128,
+        trade_direction: TradeDirection,
         trade_fee_rate: u64,
-        // protocol_fee_rate: u64,
-        // fund_fee_rate: u64,
+        protocol_fee_rate: u64,
+        fund_fee_rate: u64,
     ) -> Option<SwapResult> {
-        let source_amount_swapped = ConstantProductCurve::swap_base_output_without_fees(
+        let source_amount_swapped = self.swap_base_output_without_fees(
             destinsation_amount,
             swap_source_amount,
             swap_destination_amount,
-        );
+            trade_direction,
+        )?;
 
         let source_amount =
             Fees::calculate_pre_fee_amount(source_amount_swapped, trade_fee_rate).unwrap();
         let trade_fee = Fees::trading_fee(source_amount, trade_fee_rate)?;
-        // let protocol_fee = Fees::protocol_fee(trade_fee, protocol_fee_rate)?;
-        // let fund_fee = Fees::fund_fee(trade_fee, fund_fee_rate)?;
+        let protocol_fee = Fees::protocol_fee(trade_fee, protocol_fee_rate)?;
+        let fund_fee = Fees::fund_fee(trade_fee, fund_fee_rate)?;
 
         Some(SwapResult {
             new_swap_source_amount: swap_source_amount.checked_add(source_amount)?,
@@ -150,27 +296,228 @@ impl CurveCalculator {
             source_amount_swapped: source_amount,
             destination_amount_swapped: destinsation_amount,
             trade_fee,
-            // protocol_fee,
-            // fund_fee,
+            protocol_fee,
+            fund_fee,
         })
     }
 
-    /// Get the amount of trading tokens for the given amount of pool tokens,
-    /// provided the total trading tokens and supply of pool tokens.
-    pub fn lp_tokens_to_trading_tokens(
+    /// An estimate of the source amount `deposit_single_token` swaps, used to
+    /// narrow its search. Curves without a closed form return `None`.
+    fn deposit_single_token_swap_estimate(
+        &self,
+        _source_amount: u128,
+        _swap_source_amount: u128,
+        _trade_fee_rate: u64,
+        _protocol_fee_rate: u64,
+        _fund_fee_rate: u64,
+        _creator_fee_rate: u64,
+    ) -> Option<u128> {
+        None
+    }
+
+    /// Deposit only the source token: swap part of it through the curve with
+    /// fees and deposit the rest together with the swapped out amount. The
+    /// protocol, fund and creator fees leave the source reserve.
+    ///
+    /// The swapped portion is searched so that the lp tokens backed by both
+    /// sides are as close as possible, and the lp amount is the smaller of the
+    /// two so that the value of existing lp tokens never decreases. The search
+    /// starts around `deposit_single_token_swap_estimate` when the curve has
+    /// one, and runs regula falsi steps safeguarded by bisection for at most
+    /// `DEPOSIT_SINGLE_TOKEN_MAX_STEPS` steps.
+    #[allow(clippy::too_many_arguments)]
+    fn deposit_single_token(
+        &self,
+        source_amount: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        lp_token_supply: u128,
+        trade_direction: TradeDirection,
+        trade_fee_rate: u64,
+        protocol_fee_rate: u64,
+        fund_fee_rate: u64,
+        creator_fee_rate: u64,
+    ) -> Option<SingleTokenDepositResult> {
+        // lp tokens backed by the source side and by the destination side
+        // after swapping `swap_amount` of the source token
+        let lp_amounts = |swap_amount: u128| -> Option<(u128, u128, SwapResult, u128)> {
+            let swap_result = self.swap_base_input(
+                swap_amount,
+                swap_source_amount,
+                swap_destination_amount,
+                trade_direction,
+                trade_fee_rate,
+                protocol_fee_rate,
+                fund_fee_rate,
+            )?;
+            let creator_fee = Fees::creator_fee(swap_result.trade_fee, creator_fee_rate)?;
+            let new_swap_source_amount = swap_result
+                .new_swap_source_amount
+                .checked_sub(swap_result.protocol_fee)?
+                .checked_sub(swap_result.fund_fee)?
+                .checked_sub(creator_fee)?;
+            let lp_from_source = source_amount
+                .checked_sub(swap_amount)?
+                .checked_mul(lp_token_supply)?
+                .checked_div(new_swap_source_amount)?;
+            let lp_from_destination = swap_result
+                .destination_amount_swapped
+                .checked_mul(lp_token_supply)?
+                .checked_div(swap_result.new_swap_destination_amount)?;
+            Some((lp_from_source, lp_from_destination, swap_result, creator_fee))
+        };
+        // lp_from_source decreases and lp_from_destination increases with the
+        // swapped amount, returns whether lp_from_source >= lp_from_destination
+        // and the distance between them
+        let side = |swap_amount: u128| -> Option<(bool, u128)> {
+            let (lp_from_source, lp_from_destination, ..) = lp_amounts(swap_amount)?;
+            Some((
+                lp_from_source >= lp_from_destination,
+                lp_from_source.abs_diff(lp_from_destination),
+            ))
+        };
+
+        // `low` keeps lp_from_source >= lp_from_destination, `high` the
+        // opposite unless it is the whole source amount, the gaps of the
+        // bounds are unknown until they are swapped
+        let (mut low, mut low_gap, mut high, mut high_gap) = match self
+            .deposit_single_token_swap_estimate(
+                source_amount,
+                swap_source_amount,
+                trade_fee_rate,
+                protocol_fee_rate,
+                fund_fee_rate,
+                creator_fee_rate,
+            ) {
+            Some(estimate) => {
+                let estimate = estimate.min(source_amount);
+                let mut width = 1u128;
+                loop {
+                    let low = estimate.saturating_sub(width);
+                    let high = estimate.saturating_add(width).min(source_amount);
+                    let (low_is_source, low_gap) = side(low)?;
+                    let (high_is_source, high_gap) = side(high)?;
+                    if low_is_source && (!high_is_source || high == source_amount) {
+                        break (low, Some(low_gap), high, Some(high_gap));
+                    }
+                    width = width.checked_mul(16)?;
+                }
+            }
+            None => (0, None, source_amount, None),
+        };
+
+        // interpolate between the gaps, halving the gap of a bound kept twice
+        // in a row (Illinois), and bisect when interpolation failed to halve
+        // the range over two steps
+        let mut last_moved = None;
+        let mut last_width = high - low;
+        for step in 0..DEPOSIT_SINGLE_TOKEN_MAX_STEPS {
+            // no swapped amount backs more lp tokens than one backing the same
+            // amount on both sides
+            let width = high - low;
+            if width <= 1 || low_gap == Some(0) {
+                break;
+            }
+            let middle = match (low_gap, high_gap) {
+                (Some(low_gap), Some(high_gap)) if step % 2 == 0 || width * 2 <= last_width => {
+                    let numerator = U256::from(width) * U256::from(low_gap);
+                    let denominator = U256::from(low_gap) + U256::from(high_gap);
+                    let offset = if denominator.is_zero() {
+                        width / 2
+                    } else {
+                        (numerator / denominator).as_u128()
+                    };
+                    low + offset.clamp(1, width - 1)
+                }
+                _ => low + width / 2,
+            };
+            if step % 2 == 0 {
+                last_width = width;
+            }
+            let (is_source, gap) = side(middle)?;
+            if is_source {
+                low = middle;
+                low_gap = Some(gap);
+                if last_moved == Some(true) {
+                    high_gap = high_gap.map(|gap| gap / 2);
+                }
+            } else {
+                high = middle;
+                high_gap = Some(gap);
+                if last_moved == Some(false) {
+                    low_gap = low_gap.map(|gap| gap / 2);
+                }
+            }
+            last_moved = Some(is_source);
+        }
+
+        let (low_source, low_destination, low_result, low_creator_fee) = lp_amounts(low)?;
+        let (high_source, high_destination, high_result, high_creator_fee) = lp_amounts(high)?;
+        let low_lp = low_source.min(low_destination);
+        let high_lp = high_source.min(high_destination);
+        let (lp_token_amount, swap_result, creator_fee) = if high_lp > low_lp {
+            (high_lp, high_result, high_creator_fee)
+        } else {
+            (low_lp, low_result, low_creator_fee)
+        };
+        Some(SingleTokenDepositResult {
+            lp_token_amount: map_zero_to_none(lp_token_amount)?,
+            swap_result,
+            creator_fee,
+        })
+    }
+
+    /// Withdraw lp tokens into the destination token only: the proportional
+    /// source amount is swapped with fees against the reserves left after the
+    /// withdrawal, the protocol, fund and creator fees leaving the source reserve.
+    #[allow(clippy::too_many_arguments)]
+    fn withdraw_single_token(
+        &self,
         lp_token_amount: u128,
         lp_token_supply: u128,
-        swap_token_0_amount: u128,
-        swap_token_1_amount: u128,
-        round_direction: RoundDirection,
-    ) -> Option<TradingTokenResult> {
-        ConstantProductCurve::lp_tokens_to_trading_tokens(
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        trade_direction: TradeDirection,
+        trade_fee_rate: u64,
+        protocol_fee_rate: u64,
+        fund_fee_rate: u64,
+        creator_fee_rate: u64,
+    ) -> Option<SingleTokenWithdrawResult> {
+        let (swap_token_0_amount, swap_token_1_amount) = match trade_direction {
+            TradeDirection::ZeroForOne => (swap_source_amount, swap_destination_amount),
+            TradeDirection::OneForZero => (swap_destination_amount, swap_source_amount),
+        };
+        let result = self.lp_tokens_to_trading_tokens(
             lp_token_amount,
             lp_token_supply,
             swap_token_0_amount,
             swap_token_1_amount,
-            round_direction,
-        )
+            RoundDirection::Floor,
+        )?;
+        let (source_amount_withdrawn, destination_amount_withdrawn) = match trade_direction {
+            TradeDirection::ZeroForOne => (result.token_0_amount, result.token_1_amount),
+            TradeDirection::OneForZero => (result.token_1_amount, result.token_0_amount),
+        };
+
+        let swap_result = self.swap_base_input(
+            source_amount_withdrawn,
+            swap_source_amount.checked_sub(source_amount_withdrawn)?,
+            swap_destination_amount.checked_sub(destination_amount_withdrawn)?,
+            trade_direction,
+            trade_fee_rate,
+            protocol_fee_rate,
+            fund_fee_rate,
+        )?;
+        let creator_fee = Fees::creator_fee(swap_result.trade_fee, creator_fee_rate)?;
+        let destination_amount =
+            destination_amount_withdrawn.checked_add(swap_result.destination_amount_swapped)?;
+        Some(SingleTokenWithdrawResult {
+            source_amount_withdrawn,
+            destination_amount_withdrawn,
+            destination_amount: map_zero_to_none(destination_amount)?,
+            swap_result,
+            creator_fee,
+        })
     }
 }
 
@@ -186,22 +533,6 @@ pub mod test {
     /// comparing a one-sided deposit to a swap + deposit.
     pub const CONVERSION_BASIS_POINTS_GUARANTEE: u128 = 50;
 
-    /// Calculates the total normalized value of the curve given the liquidity
-    /// parameters.
-    ///
-    /// The constant product implementation for this function gives the square root
-    /// of the Uniswap invariant.
-    pub fn normalized_value(
-        swap_token_a_amount: u128,
-        swap_token_b_amount: u128,
-    ) -> Option<PreciseNumber> {
-        let swap_token_a_amount = PreciseNumber::new(swap_token_a_amount)?;
-        let swap_token_b_amount = PreciseNumber::new(swap_token_b_amount)?;
-        swap_token_a_amount
-            .checked_mul(&swap_token_b_amount)?
-            .sqrt()
-    }
-
     /// Test function checking that a swap never reduces the overall value of
     /// the pool.
     ///
@@ -258,7 +589,7 @@ pub mod test {
         swap_token_0_amount: u128,
         swap_token_1_amount: u128,
     ) {
-        let deposit_result = CurveCalculator::lp_tokens_to_trading_tokens(
+        let deposit_result = ConstantProductCurve::lp_tokens_to_trading_tokens(
             lp_token_amount,
             lp_token_supply,
             swap_token_0_amount,
@@ -305,7 +636,7 @@ pub mod test {
         swap_token_0_amount: u128,
         swap_token_1_amount: u128,
     ) {
-        let withdraw_result = CurveCalculator::lp_tokens_to_trading_tokens(
+        let withdraw_result = ConstantProductCurve::lp_tokens_to_trading_tokens(
             lp_token_amount,
             lp_token_supply,
             swap_token_0_amount,
@@ -317,11 +648,15 @@ pub mod test {
         let new_swap_token_1_amount = swap_token_1_amount - withdraw_result.token_1_amount;
         let new_pool_token_supply = lp_token_supply - lp_token_amount;
 
-        let value = normalized_value(swap_token_0_amount, swap_token_1_amount).unwrap();
+        let value = ConstantProductCurve
+            .normalized_value(swap_token_0_amount, swap_token_1_amount)
+            .unwrap();
         // since we can get rounding issues on the pool value which make it seem that
         // the value per token has gone down, we bump it up by an epsilon of 1
         // to cover all cases
-        let new_value = normalized_value(new_swap_token_0_amount, new_swap_token_1_amount).unwrap();
+        let new_value = ConstantProductCurve
+            .normalized_value(new_swap_token_0_amount, new_swap_token_1_amount)
+            .unwrap();
 
         // the following inequality must hold:
         // new_pool_value / new_pool_token_supply >= pool_value / pool_token_supply
@@ -343,4 +678,217 @@ pub mod test {
            (total, intermediate)
        }
     }
+
+    /// Counts the swaps run through the wrapped curve
+    #[derive(Debug)]
+    struct CountingCurve<'a> {
+        curve: &'a dyn CurveCalculator,
+        swaps: std::cell::Cell<usize>,
+    }
+
+    impl CurveCalculator for CountingCurve<'_> {
+        fn swap_base_input_without_fees(
+            &self,
+            source_amount: u128,
+            swap_source_amount: u128,
+            swap_destination_amount: u128,
+            trade_direction: TradeDirection,
+        ) -> Option<u128> {
+            self.swaps.set(self.swaps.get() + 1);
+            self.curve.swap_base_input_without_fees(
+                source_amount,
+                swap_source_amount,
+                swap_destination_amount,
+                trade_direction,
+            )
+        }
+
+        fn swap_base_output_without_fees(
+            &self,
+            destinsation_amount: u128,
+            swap_source_amount: u128,
+            swap_destination_amount: u128,
+            trade_direction: TradeDirection,
+        ) -> Option<u128> {
+            self.curve.swap_base_output_without_fees(
+                destinsation_amount,
+                swap_source_amount,
+                swap_destination_amount,
+                trade_direction,
+            )
+        }
+
+        fn lp_tokens_to_trading_tokens(
+            &self,
+            lp_token_amount: u128,
+            lp_token_supply: u128,
+            swap_token_0_amount: u128,
+            swap_token_1_amount: u128,
+            round_direction: RoundDirection,
+        ) -> Option<TradingTokenResult> {
+            self.curve.lp_tokens_to_trading_tokens(
+                lp_token_amount,
+                lp_token_supply,
+                swap_token_0_amount,
+                swap_token_1_amount,
+                round_direction,
+            )
+        }
+
+        fn normalized_value(
+            &self,
+            swap_token_0_amount: u128,
+            swap_token_1_amount: u128,
+        ) -> Option<PreciseNumber> {
+            self.curve
+                .normalized_value(swap_token_0_amount, swap_token_1_amount)
+        }
+
+        fn invariant(&self, swap_token_0_amount: u128, swap_token_1_amount: u128) -> Option<u128> {
+            self.curve
+                .invariant(swap_token_0_amount, swap_token_1_amount)
+        }
+
+        fn deposit_single_token_swap_estimate(
+            &self,
+            source_amount: u128,
+            swap_source_amount: u128,
+            trade_fee_rate: u64,
+            protocol_fee_rate: u64,
+            fund_fee_rate: u64,
+            creator_fee_rate: u64,
+        ) -> Option<u128> {
+            self.curve.deposit_single_token_swap_estimate(
+                source_amount,
+                swap_source_amount,
+                trade_fee_rate,
+                protocol_fee_rate,
+                fund_fee_rate,
+                creator_fee_rate,
+            )
+        }
+    }
+
+    /// The best lp amount of a single token deposit, bisecting over every
+    /// swapped amount
+    fn deposit_single_token_by_bisection(
+        curve: &dyn CurveCalculator,
+        source_amount: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        lp_token_supply: u128,
+        fee_rates: (u64, u64, u64, u64),
+    ) -> u128 {
+        let lp_amounts = |swap_amount: u128| {
+            let swap_result = curve
+                .swap_base_input(
+                    swap_amount,
+                    swap_source_amount,
+                    swap_destination_amount,
+                    TradeDirection::ZeroForOne,
+                    fee_rates.0,
+                    fee_rates.1,
+                    fee_rates.2,
+                )
+                .unwrap();
+            let creator_fee = Fees::creator_fee(swap_result.trade_fee, fee_rates.3).unwrap();
+            let new_swap_source_amount = swap_result.new_swap_source_amount
+                - swap_result.protocol_fee
+                - swap_result.fund_fee
+                - creator_fee;
+            (
+                (source_amount - swap_amount) * lp_token_supply / new_swap_source_amount,
+                swap_result.destination_amount_swapped * lp_token_supply
+                    / swap_result.new_swap_destination_amount,
+            )
+        };
+        let (mut low, mut high) = (0u128, source_amount);
+        while high - low > 1 {
+            let middle = low + (high - low) / 2;
+            let (lp_from_source, lp_from_destination) = lp_amounts(middle);
+            if lp_from_source >= lp_from_destination {
+                low = middle;
+            } else {
+                high = middle;
+            }
+        }
+        let (low_source, low_destination) = lp_amounts(low);
+        let (high_source, high_destination) = lp_amounts(high);
+        low_source
+            .min(low_destination)
+            .max(high_source.min(high_destination))
+    }
+
+    #[test]
+    fn deposit_single_token_bounds_the_swaps_run() {
+        let stable = StableCurve::new(100).unwrap();
+        let weighted = WeightedCurve::new(800_000, 200_000).unwrap();
+        let curves: [(&dyn CurveCalculator, usize); 3] = [
+            (&ConstantProductCurve, 12),
+            (&stable, DEPOSIT_SINGLE_TOKEN_MAX_STEPS + 2),
+            (&weighted, DEPOSIT_SINGLE_TOKEN_MAX_STEPS + 2),
+        ];
+        let cases = [
+            (2_000, 1_000_000, 1_000_000, 1_000_000, (0, 0, 0, 0)),
+            (
+                1_000_000_000,
+                5_000_000_000,
+                7_000_000_000,
+                6_000_000_000,
+                (2_500, 0, 0, 0),
+            ),
+            (
+                u64::MAX as u128 / 4,
+                u64::MAX as u128 / 2,
+                u64::MAX as u128 / 3,
+                u64::MAX as u128,
+                (10_000, 120_000, 40_000, 640_000),
+            ),
+            (
+                123_456_789,
+                987_654_321_000,
+                555_555_555_555,
+                1_000_000_000,
+                (3_000, 200_000, 0, 0),
+            ),
+            (
+                3_000_000_000,
+                1_000_000_000,
+                2_000_000_000,
+                1_500_000_000,
+                (100_000, 0, 50_000, 100_000),
+            ),
+        ];
+        for (curve, max_swaps) in curves {
+            for (source, swap_source, swap_destination, supply, fee_rates) in cases {
+                let counting = CountingCurve {
+                    curve,
+                    swaps: std::cell::Cell::new(0),
+                };
+                let result = counting
+                    .deposit_single_token(
+                        source,
+                        swap_source,
+                        swap_destination,
+                        supply,
+                        TradeDirection::ZeroForOne,
+                        fee_rates.0,
+                        fee_rates.1,
+                        fee_rates.2,
+                        fee_rates.3,
+                    )
+                    .unwrap();
+                assert!(counting.swaps.get() <= max_swaps);
+                let expected = deposit_single_token_by_bisection(
+                    curve,
+                    source,
+                    swap_source,
+                    swap_destination,
+                    supply,
+                    fee_rates,
+                );
+                assert_eq!(result.lp_token_amount, expected);
+            }
+        }
+    }
 }
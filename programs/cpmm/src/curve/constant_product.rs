@@ -1,9 +1,13 @@
 //! The Uniswap invariantConstantProductCurve::
 
 use crate::{
-    curve::calculator::{RoundDirection, TradingTokenResult},
-    utils::CheckedCeilDiv,
+    curve::{
+        calculator::{CurveCalculator, RoundDirection, TradeDirection, TradingTokenResult},
+        fees::FEE_RATE_DENOMINATOR_VALUE,
+    },
+    utils::{CheckedCeilDiv, U256},
 };
+use spl_math::precise_number::PreciseNumber;
 
 /// ConstantProductCurve struct implementing CurveCalculator
 #[derive(Clone, Debug, Default, PartialEq)]
@@ -25,8 +29,7 @@ impl ConstantProductCurve {
         // delta_y = (delta_x * y) / (x + delta_x)
         let numerator = source_amount.checked_mul(swap_destination_amount).unwrap();
         let denominator = swap_source_amount.checked_add(source_amount).unwrap();
-        let destinsation_amount_swapped = numerator.checked_div(denominator).unwrap();
-        destinsation_amount_swapped
+        numerator.checked_div(denominator).unwrap()
     }
 
     pub fn swap_base_output_without_fees(
@@ -92,6 +95,114 @@ impl ConstantProductCurve {
     }
 }
 
+impl CurveCalculator for ConstantProductCurve {
+    fn swap_base_input_without_fees(
+        &self,
+        source_amount: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        _trade_direction: TradeDirection,
+    ) -> Option<u128> {
+        Some(Self::swap_base_input_without_fees(
+            source_amount,
+            swap_source_amount,
+            swap_destination_amount,
+        ))
+    }
+
+    fn swap_base_output_without_fees(
+        &self,
+        destinsation_amount: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        _trade_direction: TradeDirection,
+    ) -> Option<u128> {
+        Some(Self::swap_base_output_without_fees(
+            destinsation_amount,
+            swap_source_amount,
+            swap_destination_amount,
+        ))
+    }
+
+    fn lp_tokens_to_trading_tokens(
+        &self,
+        lp_token_amount: u128,
+        lp_token_supply: u128,
+        swap_token_0_amount: u128,
+        swap_token_1_amount: u128,
+        round_direction: RoundDirection,
+    ) -> Option<TradingTokenResult> {
+        Self::lp_tokens_to_trading_tokens(
+            lp_token_amount,
+            lp_token_supply,
+            swap_token_0_amount,
+            swap_token_1_amount,
+            round_direction,
+        )
+    }
+
+    /// The constant product implementation for this function gives the square root
+    /// of the Uniswap invariant.
+    fn normalized_value(
+        &self,
+        swap_token_0_amount: u128,
+        swap_token_1_amount: u128,
+    ) -> Option<PreciseNumber> {
+        let swap_token_0_amount = PreciseNumber::new(swap_token_0_amount)?;
+        let swap_token_1_amount = PreciseNumber::new(swap_token_1_amount)?;
+        swap_token_0_amount
+            .checked_mul(&swap_token_1_amount)?
+            .sqrt()
+    }
+
+    /// x * y
+    fn invariant(&self, swap_token_0_amount: u128, swap_token_1_amount: u128) -> Option<u128> {
+        swap_token_0_amount.checked_mul(swap_token_1_amount)
+    }
+
+    /// With r = 1 - trade_fee and
+    /// k = 1 - trade_fee * (protocol_fee + fund_fee + creator_fee)
+    /// the lp tokens of both sides match when swapping s of the deposit a
+    /// into the source reserve x:
+    ///   (a - s) * x = r * s * (x + k * s)
+    /// solved for s with every rate scaled by the fee denominator
+    fn deposit_single_token_swap_estimate(
+        &self,
+        source_amount: u128,
+        swap_source_amount: u128,
+        trade_fee_rate: u64,
+        protocol_fee_rate: u64,
+        fund_fee_rate: u64,
+        creator_fee_rate: u64,
+    ) -> Option<u128> {
+        let denominator = U256::from(FEE_RATE_DENOMINATOR_VALUE);
+        let r = denominator.checked_sub(U256::from(trade_fee_rate))?;
+        let k = (denominator * denominator).checked_sub(
+            U256::from(trade_fee_rate)
+                * (U256::from(protocol_fee_rate)
+                    + U256::from(fund_fee_rate)
+                    + U256::from(creator_fee_rate)),
+        )?;
+        let b = (denominator + r)
+            .checked_mul(denominator * denominator)?
+            .checked_mul(U256::from(swap_source_amount))?;
+        let c = U256::from(4)
+            .checked_mul(r)?
+            .checked_mul(k)?
+            .checked_mul(denominator * denominator * denominator)?
+            .checked_mul(U256::from(source_amount))?
+            .checked_mul(U256::from(swap_source_amount))?;
+        let root = b.checked_mul(b)?.checked_add(c)?.integer_sqrt();
+        let estimate = root
+            .checked_sub(b)?
+            .checked_div(U256::from(2).checked_mul(r)?.checked_mul(k)?)?;
+        if estimate > U256::from(u128::MAX) {
+            return None;
+        }
+        Some(estimate.as_u128())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use {
@@ -101,9 +212,11 @@ mod tests {
                 check_curve_value_from_swap, check_pool_value_from_deposit,
                 check_pool_value_from_withdraw, total_and_intermediate,
             },
-            RoundDirection, TradeDirection,
+            CurveCalculator, RoundDirection,
         },
+        crate::curve::fees::Fees,
         proptest::prelude::*,
+        spl_math::uint::U256,
     };
 
     fn check_pool_token_rate(
@@ -282,4 +395,221 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn deposit_single_token_swaps_about_half() {
+        let result = ConstantProductCurve
+            .deposit_single_token(
+                2_000,
+                1_000_000,
+                1_000_000,
+                1_000_000,
+                TradeDirection::ZeroForOne,
+                0,
+                0,
+                0,
+                0,
+            )
+            .unwrap();
+        assert!((999..=1_001).contains(&result.swap_result.source_amount_swapped));
+        assert_eq!(result.lp_token_amount, 999);
+    }
+
+    #[test]
+    fn deposit_single_token_leaves_the_creator_fee_out() {
+        let deposit = |creator_fee_rate| {
+            ConstantProductCurve
+                .deposit_single_token(
+                    2_000_000,
+                    1_000_000_000,
+                    1_000_000_000,
+                    1_000_000_000,
+                    TradeDirection::ZeroForOne,
+                    100_000,
+                    120_000,
+                    40_000,
+                    creator_fee_rate,
+                )
+                .unwrap()
+        };
+        let without_creator_fee = deposit(0);
+        let with_creator_fee = deposit(400_000);
+        assert_eq!(without_creator_fee.creator_fee, 0);
+        assert_eq!(
+            with_creator_fee.creator_fee,
+            Fees::creator_fee(with_creator_fee.swap_result.trade_fee, 400_000).unwrap()
+        );
+        assert!(with_creator_fee.creator_fee > 0);
+        // the creator fee leaves the source reserve the deposit is measured against
+        assert!(with_creator_fee.lp_token_amount > without_creator_fee.lp_token_amount);
+    }
+
+    #[test]
+    fn withdraw_single_token_returns_the_creator_fee() {
+        let withdraw = |creator_fee_rate| {
+            ConstantProductCurve
+                .withdraw_single_token(
+                    1_000_000,
+                    1_000_000_000,
+                    1_000_000_000,
+                    1_000_000_000,
+                    TradeDirection::ZeroForOne,
+                    100_000,
+                    120_000,
+                    40_000,
+                    creator_fee_rate,
+                )
+                .unwrap()
+        };
+        let without_creator_fee = withdraw(0);
+        let with_creator_fee = withdraw(400_000);
+        assert_eq!(without_creator_fee.creator_fee, 0);
+        assert_eq!(with_creator_fee.swap_result.trade_fee, 100_000);
+        assert_eq!(with_creator_fee.creator_fee, 40_000);
+        // the fees are taken from the swapped source token, not the amount paid out
+        assert_eq!(
+            with_creator_fee.destination_amount,
+            without_creator_fee.destination_amount
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn curve_value_does_not_decrease_from_deposit_single_token(
+            source_amount in 1..u64::MAX,
+            swap_source_amount in 1..u64::MAX,
+            swap_destination_amount in 1..u64::MAX,
+            lp_token_supply in 1..u64::MAX,
+            trade_fee_rate in 0..100_000u64,
+        ) {
+            let source_amount = source_amount as u128;
+            let swap_source_amount = swap_source_amount as u128;
+            let swap_destination_amount = swap_destination_amount as u128;
+            let lp_token_supply = lp_token_supply as u128;
+            let result = ConstantProductCurve.deposit_single_token(
+                source_amount,
+                swap_source_amount,
+                swap_destination_amount,
+                lp_token_supply,
+                TradeDirection::ZeroForOne,
+                trade_fee_rate,
+                120_000,
+                40_000,
+                40_000,
+            );
+            prop_assume!(result.is_some());
+            let result = result.unwrap();
+            let swap_result = result.swap_result;
+
+            // the pool keeps the whole source amount less protocol, fund and creator fees
+            let new_swap_source_amount = U256::from(
+                swap_source_amount + source_amount
+                    - swap_result.protocol_fee
+                    - swap_result.fund_fee
+                    - result.creator_fee,
+            );
+            let new_lp_token_supply = U256::from(lp_token_supply + result.lp_token_amount);
+            let lp_token_supply = U256::from(lp_token_supply);
+            let swap_source_amount = U256::from(swap_source_amount);
+
+            // sqrt(x' * y) / s' >= sqrt(x * y) / s with y unchanged
+            prop_assert!(
+                new_swap_source_amount * lp_token_supply * lp_token_supply
+                    >= swap_source_amount * new_lp_token_supply * new_lp_token_supply
+            );
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn curve_value_does_not_decrease_from_withdraw_single_token(
+            (lp_token_supply, lp_token_amount) in total_and_intermediate(u32::MAX as u64),
+            swap_source_amount in 1..u64::MAX,
+            swap_destination_amount in 1..u64::MAX,
+            trade_fee_rate in 0..100_000u64,
+        ) {
+            let lp_token_supply = lp_token_supply as u128;
+            let lp_token_amount = lp_token_amount as u128;
+            let swap_source_amount = swap_source_amount as u128;
+            let swap_destination_amount = swap_destination_amount as u128;
+            let result = ConstantProductCurve.withdraw_single_token(
+                lp_token_amount,
+                lp_token_supply,
+                swap_source_amount,
+                swap_destination_amount,
+                TradeDirection::ZeroForOne,
+                trade_fee_rate,
+                120_000,
+                40_000,
+                40_000,
+            );
+            prop_assume!(result.is_some());
+            let result = result.unwrap();
+            let swap_result = result.swap_result;
+            prop_assert_eq!(
+                result.destination_amount,
+                result.destination_amount_withdrawn + swap_result.destination_amount_swapped
+            );
+
+            // the withdrawn source token goes back into the pool with the swap
+            let new_swap_source_amount = U256::from(
+                swap_source_amount
+                    - swap_result.protocol_fee
+                    - swap_result.fund_fee
+                    - result.creator_fee,
+            );
+            let new_swap_destination_amount =
+                U256::from(swap_destination_amount - result.destination_amount);
+            let new_lp_token_supply = U256::from(lp_token_supply - lp_token_amount);
+            let lp_token_supply = U256::from(lp_token_supply);
+
+            // sqrt(x' * y') / s' >= sqrt(x * y) / s
+            prop_assert!(
+                new_swap_source_amount * new_swap_destination_amount * lp_token_supply * lp_token_supply
+                    >= U256::from(swap_source_amount)
+                        * U256::from(swap_destination_amount)
+                        * new_lp_token_supply
+                        * new_lp_token_supply
+            );
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn trading_tokens_to_lp_tokens_fits_maximum_amounts(
+            token_0_amount in 1..u64::MAX,
+            token_1_amount in 1..u64::MAX,
+            lp_token_supply in 1..u64::MAX,
+            swap_token_0_amount in 1..u64::MAX,
+            swap_token_1_amount in 1..u64::MAX,
+        ) {
+            let lp_token_amount = ConstantProductCurve.trading_tokens_to_lp_tokens(
+                token_0_amount as u128,
+                token_1_amount as u128,
+                lp_token_supply as u128,
+                swap_token_0_amount as u128,
+                swap_token_1_amount as u128,
+            );
+            prop_assume!(lp_token_amount.is_some());
+            let lp_token_amount = lp_token_amount.unwrap();
+            let results = ConstantProductCurve::lp_tokens_to_trading_tokens(
+                lp_token_amount,
+                lp_token_supply as u128,
+                swap_token_0_amount as u128,
+                swap_token_1_amount as u128,
+                RoundDirection::Ceiling,
+            )
+            .unwrap();
+            prop_assert!(results.token_0_amount <= token_0_amount as u128);
+            prop_assert!(results.token_1_amount <= token_1_amount as u128);
+
+            // one more pool token would exceed one of the amounts
+            prop_assert!(
+                (lp_token_amount + 1) * swap_token_0_amount as u128
+                    > token_0_amount as u128 * lp_token_supply as u128
+                    || (lp_token_amount + 1) * swap_token_1_amount as u128
+                        > token_1_amount as u128 * lp_token_supply as u128
+            );
+        }
+    }
 }
@@ -4,7 +4,11 @@
 pub mod fees;
 pub mod calculator;
 pub mod constant_product;
+pub mod stable;
+pub mod weighted;
 
 pub use fees::*;
 pub use calculator::*;
 pub use constant_product::*;
+pub use stable::*;
+pub use weighted::*;
@@ -0,0 +1,391 @@
+//! The StableSwap invariant calculator.
+
+use crate::{
+    curve::{
+        calculator::{CurveCalculator, RoundDirection, TradeDirection, TradingTokenResult},
+        constant_product::ConstantProductCurve,
+    },
+    utils::{checked_ceil_div_u256, U256},
+};
+use spl_math::precise_number::PreciseNumber;
+
+/// Minimum amplification coefficient
+pub const MIN_AMP: u64 = 1;
+/// Maximum amplification coefficient
+pub const MAX_AMP: u64 = 1_000_000;
+
+/// Number of coins in a pool
+const N_COINS: u8 = 2;
+/// N_COINS ** N_COINS
+const N_COINS_SQUARED: u8 = 4;
+/// Maximum number of iterations of the newton methods
+const ITERATIONS: u16 = 256;
+
+/// StableCurve struct implementing the StableSwap invariant
+/// A * n^n * sum(x_i) + D = A * D * n^n + D^(n+1) / (n^n * prod(x_i))
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct StableCurve {
+    /// Amplification coefficient (A)
+    pub amp: u64,
+}
+
+/// Returns a to the power of b
+fn checked_u8_power(a: &U256, b: u8) -> Option<U256> {
+    let mut result = *a;
+    for _ in 1..b {
+        result = result.checked_mul(*a)?;
+    }
+    Some(result)
+}
+
+/// Returns a multiplied by b
+fn checked_u8_mul(a: &U256, b: u8) -> Option<U256> {
+    a.checked_mul(U256::from(b))
+}
+
+impl StableCurve {
+    pub fn new(amp: u64) -> Option<Self> {
+        if (MIN_AMP..=MAX_AMP).contains(&amp) {
+            Some(Self { amp })
+        } else {
+            None
+        }
+    }
+
+    /// A * n
+    fn leverage(&self) -> Option<u64> {
+        self.amp.checked_mul(N_COINS as u64)
+    }
+
+    /// d = (leverage * sum_x + d_product * n_coins) * initial_d
+    ///     / ((leverage - 1) * initial_d + (n_coins + 1) * d_product)
+    fn calculate_step(
+        initial_d: &U256,
+        leverage: u64,
+        sum_x: u128,
+        d_product: &U256,
+    ) -> Option<U256> {
+        let leverage_mul = U256::from(leverage).checked_mul(sum_x.into())?;
+        let d_p_mul = checked_u8_mul(d_product, N_COINS)?;
+
+        let l_val = leverage_mul.checked_add(d_p_mul)?.checked_mul(*initial_d)?;
+
+        let leverage_sub = initial_d.checked_mul((leverage.checked_sub(1)?).into())?;
+        let n_coins_sum = checked_u8_mul(d_product, N_COINS.checked_add(1)?)?;
+
+        let r_val = leverage_sub.checked_add(n_coins_sum)?;
+
+        l_val.checked_div(r_val)
+    }
+
+    /// Compute the stable swap invariant (D)
+    pub fn compute_d(&self, amount_0: u128, amount_1: u128) -> Option<u128> {
+        let leverage = self.leverage()?;
+        let sum_x = amount_0.checked_add(amount_1)?;
+        if sum_x == 0 {
+            return Some(0);
+        }
+        let amount_0_times_coins = checked_u8_mul(&U256::from(amount_0), N_COINS)?;
+        let amount_1_times_coins = checked_u8_mul(&U256::from(amount_1), N_COINS)?;
+        let mut d: U256 = sum_x.into();
+        for _ in 0..ITERATIONS {
+            let mut d_product = d;
+            d_product = d_product
+                .checked_mul(d)?
+                .checked_div(amount_0_times_coins)?;
+            d_product = d_product
+                .checked_mul(d)?
+                .checked_div(amount_1_times_coins)?;
+            let d_previous = d;
+            d = Self::calculate_step(&d, leverage, sum_x, &d_product)?;
+            if d == d_previous {
+                break;
+            }
+        }
+        u128::try_from(d).ok()
+    }
+
+    /// Compute the amount of the other token (y) that keeps the invariant D
+    /// given the new amount of one token (x), rounded up.
+    /// Solve for y: y**2 + y * (x + D / leverage - D) = D**3 / (n**n * x * leverage)
+    fn compute_y(&self, x: u128, d: u128) -> Option<u128> {
+        let leverage: U256 = self.leverage()?.into();
+        let x: U256 = x.into();
+        let d: U256 = d.into();
+
+        // c = D ** (n + 1) / (n ** n * x * leverage)
+        let c = checked_u8_power(&d, N_COINS.checked_add(1)?)?
+            .checked_div(checked_u8_mul(&x, N_COINS_SQUARED)?.checked_mul(leverage)?)?;
+        // b = x + D / leverage, the "- D" is applied in the iteration
+        let b = x.checked_add(d.checked_div(leverage)?)?;
+
+        // y = (y ** 2 + c) / (2 * y + b - D)
+        let mut y = d;
+        for _ in 0..ITERATIONS {
+            let y_new = checked_ceil_div_u256(
+                checked_u8_power(&y, 2)?.checked_add(c)?,
+                checked_u8_mul(&y, 2)?.checked_add(b)?.checked_sub(d)?,
+            )?;
+            if y_new == y {
+                break;
+            }
+            y = y_new;
+        }
+        u128::try_from(y).ok()
+    }
+}
+
+impl CurveCalculator for StableCurve {
+    /// Keeps the invariant D, rounding the new destination amount up
+    fn swap_base_input_without_fees(
+        &self,
+        source_amount: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        _trade_direction: TradeDirection,
+    ) -> Option<u128> {
+        let d = self.compute_d(swap_source_amount, swap_destination_amount)?;
+        let new_destination_amount =
+            self.compute_y(swap_source_amount.checked_add(source_amount)?, d)?;
+        swap_destination_amount.checked_sub(new_destination_amount)
+    }
+
+    /// Keeps the invariant D, rounding the new source amount up
+    fn swap_base_output_without_fees(
+        &self,
+        destinsation_amount: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        _trade_direction: TradeDirection,
+    ) -> Option<u128> {
+        let d = self.compute_d(swap_source_amount, swap_destination_amount)?;
+        let new_source_amount =
+            self.compute_y(swap_destination_amount.checked_sub(destinsation_amount)?, d)?;
+        new_source_amount.checked_sub(swap_source_amount)
+    }
+
+    /// Deposits and withdrawals of both sides keep the pool ratio, so this is
+    /// the same ratio calculation as the constant product curve.
+    fn lp_tokens_to_trading_tokens(
+        &self,
+        lp_token_amount: u128,
+        lp_token_supply: u128,
+        swap_token_0_amount: u128,
+        swap_token_1_amount: u128,
+        round_direction: RoundDirection,
+    ) -> Option<TradingTokenResult> {
+        ConstantProductCurve::lp_tokens_to_trading_tokens(
+            lp_token_amount,
+            lp_token_supply,
+            swap_token_0_amount,
+            swap_token_1_amount,
+            round_direction,
+        )
+    }
+
+    /// The stable implementation for this function gives D / 2, the value of
+    /// each side of a balanced pool.
+    fn normalized_value(
+        &self,
+        swap_token_0_amount: u128,
+        swap_token_1_amount: u128,
+    ) -> Option<PreciseNumber> {
+        let d = PreciseNumber::new(self.compute_d(swap_token_0_amount, swap_token_1_amount)?)?;
+        d.checked_div(&PreciseNumber::new(N_COINS as u128)?)
+    }
+
+    /// D
+    fn invariant(&self, swap_token_0_amount: u128, swap_token_1_amount: u128) -> Option<u128> {
+        self.compute_d(swap_token_0_amount, swap_token_1_amount)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::curve::calculator::test::total_and_intermediate, proptest::prelude::*};
+
+    /// Checks that a swap never reduces the invariant D of the pool
+    fn check_curve_value_from_swap(
+        curve: &StableCurve,
+        source_token_amount: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        trade_direction: TradeDirection,
+    ) {
+        let destination_amount_swapped = curve
+            .swap_base_input_without_fees(
+                source_token_amount,
+                swap_source_amount,
+                swap_destination_amount,
+                trade_direction,
+            )
+            .unwrap();
+
+        let (swap_token_0_amount, swap_token_1_amount) = match trade_direction {
+            TradeDirection::ZeroForOne => (swap_source_amount, swap_destination_amount),
+            TradeDirection::OneForZero => (swap_destination_amount, swap_source_amount),
+        };
+        let previous_value = curve
+            .compute_d(swap_token_0_amount, swap_token_1_amount)
+            .unwrap();
+
+        let new_swap_source_amount = swap_source_amount.checked_add(source_token_amount).unwrap();
+        let new_swap_destination_amount = swap_destination_amount
+            .checked_sub(destination_amount_swapped)
+            .unwrap();
+        let (swap_token_0_amount, swap_token_1_amount) = match trade_direction {
+            TradeDirection::ZeroForOne => (new_swap_source_amount, new_swap_destination_amount),
+            TradeDirection::OneForZero => (new_swap_destination_amount, new_swap_source_amount),
+        };
+        let new_value = curve
+            .compute_d(swap_token_0_amount, swap_token_1_amount)
+            .unwrap();
+        assert!(new_value >= previous_value);
+    }
+
+    /// Checks that a withdraw never reduces the invariant D per pool token
+    fn check_pool_value_from_withdraw(
+        curve: &StableCurve,
+        lp_token_amount: u128,
+        lp_token_supply: u128,
+        swap_token_0_amount: u128,
+        swap_token_1_amount: u128,
+    ) {
+        let withdraw_result = curve
+            .lp_tokens_to_trading_tokens(
+                lp_token_amount,
+                lp_token_supply,
+                swap_token_0_amount,
+                swap_token_1_amount,
+                RoundDirection::Floor,
+            )
+            .unwrap();
+        let new_swap_token_0_amount = swap_token_0_amount - withdraw_result.token_0_amount;
+        let new_swap_token_1_amount = swap_token_1_amount - withdraw_result.token_1_amount;
+        let new_lp_token_supply = lp_token_supply - lp_token_amount;
+
+        let value = curve
+            .compute_d(swap_token_0_amount, swap_token_1_amount)
+            .unwrap();
+        let new_value = curve
+            .compute_d(new_swap_token_0_amount, new_swap_token_1_amount)
+            .unwrap();
+
+        // new_value / new_lp_token_supply >= value / lp_token_supply, with an
+        // epsilon of 1 for the rounding of D
+        assert!(
+            U256::from(new_value + 1) * U256::from(lp_token_supply)
+                >= U256::from(value) * U256::from(new_lp_token_supply)
+        );
+    }
+
+    #[test]
+    fn new_checks_amp_range() {
+        assert!(StableCurve::new(0).is_none());
+        assert!(StableCurve::new(MAX_AMP + 1).is_none());
+        assert_eq!(StableCurve::new(100), Some(StableCurve { amp: 100 }));
+    }
+
+    #[test]
+    fn compute_d_of_balanced_pool() {
+        let curve = StableCurve { amp: 100 };
+        assert_eq!(curve.compute_d(0, 0), Some(0));
+        // D equals the sum of the reserves when the pool is balanced
+        assert_eq!(curve.compute_d(1_000_000, 1_000_000), Some(2_000_000));
+    }
+
+    #[test]
+    fn stable_swap_has_less_slippage_than_constant_product() {
+        let curve = StableCurve { amp: 100 };
+        let swap_source_amount = 1_000_000_000u128;
+        let swap_destination_amount = 1_000_000_000u128;
+        let source_amount = 10_000_000u128;
+        let stable = curve
+            .swap_base_input_without_fees(
+                source_amount,
+                swap_source_amount,
+                swap_destination_amount,
+                TradeDirection::ZeroForOne,
+            )
+            .unwrap();
+        let constant_product = ConstantProductCurve::swap_base_input_without_fees(
+            source_amount,
+            swap_source_amount,
+            swap_destination_amount,
+        );
+        assert!(stable > constant_product);
+        assert!(stable <= source_amount);
+    }
+
+    #[test]
+    fn swap_base_output_covers_destination_amount() {
+        let curve = StableCurve { amp: 85 };
+        let swap_source_amount = 2_000_000_000u128;
+        let swap_destination_amount = 1_000_000_000u128;
+        let destination_amount = 50_000_000u128;
+        let source_amount = curve
+            .swap_base_output_without_fees(
+                destination_amount,
+                swap_source_amount,
+                swap_destination_amount,
+                TradeDirection::ZeroForOne,
+            )
+            .unwrap();
+        let destination_amount_swapped = curve
+            .swap_base_input_without_fees(
+                source_amount,
+                swap_source_amount,
+                swap_destination_amount,
+                TradeDirection::ZeroForOne,
+            )
+            .unwrap();
+        assert!(destination_amount_swapped >= destination_amount);
+    }
+
+    proptest! {
+        #[test]
+        fn curve_value_does_not_decrease_from_swap(
+            amp in MIN_AMP..=MAX_AMP,
+            source_token_amount in 1..u32::MAX as u128,
+            swap_source_amount in 1_000..u64::MAX as u128,
+            swap_destination_amount in 1_000..u64::MAX as u128,
+        ) {
+            let curve = StableCurve { amp };
+            prop_assume!(source_token_amount <= swap_source_amount);
+            check_curve_value_from_swap(
+                &curve,
+                source_token_amount,
+                swap_source_amount,
+                swap_destination_amount,
+                TradeDirection::ZeroForOne
+            );
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn curve_value_does_not_decrease_from_withdraw(
+            amp in MIN_AMP..=MAX_AMP,
+            (pool_token_supply, pool_token_amount) in total_and_intermediate(u64::MAX),
+            swap_token_a_amount in 1_000..u64::MAX,
+            swap_token_b_amount in 1_000..u64::MAX,
+        ) {
+            let curve = StableCurve { amp };
+            let pool_token_amount = pool_token_amount as u128;
+            let pool_token_supply = pool_token_supply as u128;
+            let swap_token_a_amount = swap_token_a_amount as u128;
+            let swap_token_b_amount = swap_token_b_amount as u128;
+            // Make sure we will get at least one trading token out for each
+            // side, otherwise the calculation fails
+            prop_assume!(pool_token_amount * swap_token_a_amount / pool_token_supply >= 1);
+            prop_assume!(pool_token_amount * swap_token_b_amount / pool_token_supply >= 1);
+            check_pool_value_from_withdraw(
+                &curve,
+                pool_token_amount,
+                pool_token_supply,
+                swap_token_a_amount,
+                swap_token_b_amount,
+            );
+        }
+    }
+}
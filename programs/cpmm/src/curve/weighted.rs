@@ -0,0 +1,503 @@
+//! The weighted product (Balancer-style) invariant calculator.
+
+use crate::{
+    curve::{
+        calculator::{CurveCalculator, RoundDirection, TradeDirection, TradingTokenResult},
+        constant_product::ConstantProductCurve,
+    },
+    utils::{checked_ceil_div_u256, U256},
+};
+use spl_math::precise_number::PreciseNumber;
+
+/// Denominator of the weights, weight_0 + weight_1 must be equal to it
+pub const WEIGHT_DENOMINATOR_VALUE: u64 = 1_000_000;
+/// Minimum weight of each side of a pool, 1%
+pub const MIN_WEIGHT: u64 = 10_000;
+
+/// Fractional bits of the Q64.64 fixed-point numbers
+const Q64_BITS: usize = 64;
+/// ln(2) in Q64.64
+const LN_2_Q64: u128 = 0xB172_17F7_D1CF_79AB;
+/// Every power is rounded up by 2^-POW_ERROR_MARGIN_BITS in favor of the
+/// pool, which covers the error of the fixed-point approximations.
+const POW_ERROR_MARGIN_BITS: usize = 40;
+
+/// 1 in Q64.64
+fn one() -> U256 {
+    U256::one() << Q64_BITS
+}
+
+/// log2 of a positive Q64.64 number, as a signed Q64.64 number
+fn log2(x: U256) -> Option<i128> {
+    if x.is_zero() {
+        return None;
+    }
+    // x = 2^integer * m, with m in [1, 2)
+    let msb = x.bits() - 1;
+    let integer = msb as i128 - Q64_BITS as i128;
+    let mut m = if msb >= Q64_BITS {
+        x >> (msb - Q64_BITS)
+    } else {
+        x << (Q64_BITS - msb)
+    };
+    // each squaring of m gives the next bit of log2(m)
+    let two = one() << 1;
+    let mut fraction = 0i128;
+    for i in 1..=Q64_BITS {
+        m = m.checked_mul(m)? >> Q64_BITS;
+        if m >= two {
+            m >>= 1;
+            fraction |= 1i128 << (Q64_BITS - i);
+        }
+    }
+    Some((integer << Q64_BITS) + fraction)
+}
+
+/// 2^y of a signed Q64.64 number, as a Q64.64 number rounded down
+fn exp2(y: i128) -> Option<U256> {
+    let integer = y >> Q64_BITS;
+    let fraction = (y - (integer << Q64_BITS)) as u128;
+    // 2^fraction = e^(fraction * ln2), by its taylor series
+    let z = U256::from(fraction).checked_mul(U256::from(LN_2_Q64))? >> Q64_BITS;
+    let mut term = one();
+    let mut sum = one();
+    let mut k = 1u64;
+    while !term.is_zero() {
+        term = (term.checked_mul(z)? >> Q64_BITS) / U256::from(k);
+        sum = sum.checked_add(term)?;
+        k += 1;
+    }
+    if integer >= 0 {
+        let shift = usize::try_from(integer).ok()?;
+        if sum.bits() + shift > 256 {
+            return None;
+        }
+        Some(sum << shift)
+    } else {
+        let shift = usize::try_from(-integer).ok()?;
+        if shift >= 256 {
+            return Some(U256::zero());
+        }
+        Some(sum >> shift)
+    }
+}
+
+/// base^(numerator / denominator) of a Q64.64 base, rounded up by the error margin
+fn checked_pow_up(base: U256, numerator: u64, denominator: u64) -> Option<U256> {
+    let exponent = log2(base)?
+        .checked_mul(i128::from(numerator))?
+        .checked_div(i128::from(denominator))?;
+    let power = exp2(exponent)?;
+    power
+        .checked_add(power >> POW_ERROR_MARGIN_BITS)?
+        .checked_add(U256::one())
+}
+
+/// WeightedCurve struct implementing the weighted product invariant
+/// x^weight_0 * y^weight_1 = constant
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct WeightedCurve {
+    /// Weight of token_0, in WEIGHT_DENOMINATOR_VALUE
+    pub weight_0: u64,
+    /// Weight of token_1, in WEIGHT_DENOMINATOR_VALUE
+    pub weight_1: u64,
+}
+
+impl WeightedCurve {
+    pub fn new(weight_0: u64, weight_1: u64) -> Option<Self> {
+        if weight_0 < MIN_WEIGHT
+            || weight_1 < MIN_WEIGHT
+            || weight_0.checked_add(weight_1)? != WEIGHT_DENOMINATOR_VALUE
+        {
+            return None;
+        }
+        Some(Self { weight_0, weight_1 })
+    }
+
+    /// The weights of the source and destination tokens of a trade
+    fn weights(&self, trade_direction: TradeDirection) -> (u64, u64) {
+        match trade_direction {
+            TradeDirection::ZeroForOne => (self.weight_0, self.weight_1),
+            TradeDirection::OneForZero => (self.weight_1, self.weight_0),
+        }
+    }
+}
+
+impl CurveCalculator for WeightedCurve {
+    /// delta_y = y * (1 - (x / (x + delta_x)) ^ (weight_x / weight_y))
+    fn swap_base_input_without_fees(
+        &self,
+        source_amount: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        trade_direction: TradeDirection,
+    ) -> Option<u128> {
+        let (source_weight, destination_weight) = self.weights(trade_direction);
+        let new_swap_source_amount = swap_source_amount.checked_add(source_amount)?;
+        let base = checked_ceil_div_u256(
+            U256::from(swap_source_amount) << Q64_BITS,
+            U256::from(new_swap_source_amount),
+        )?;
+        let power = checked_pow_up(base, source_weight, destination_weight)?;
+        if power >= one() {
+            return Some(0);
+        }
+        let destination_amount_swapped =
+            U256::from(swap_destination_amount).checked_mul(one() - power)? >> Q64_BITS;
+        u128::try_from(destination_amount_swapped).ok()
+    }
+
+    /// delta_x = x * ((y / (y - delta_y)) ^ (weight_y / weight_x) - 1)
+    fn swap_base_output_without_fees(
+        &self,
+        destinsation_amount: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        trade_direction: TradeDirection,
+    ) -> Option<u128> {
+        let (source_weight, destination_weight) = self.weights(trade_direction);
+        let new_swap_destination_amount =
+            swap_destination_amount.checked_sub(destinsation_amount)?;
+        let base = checked_ceil_div_u256(
+            U256::from(swap_destination_amount) << Q64_BITS,
+            U256::from(new_swap_destination_amount),
+        )?;
+        let power = checked_pow_up(base, destination_weight, source_weight)?;
+        let source_amount_swapped = checked_ceil_div_u256(
+            U256::from(swap_source_amount).checked_mul(power.checked_sub(one())?)?,
+            one(),
+        )?;
+        u128::try_from(source_amount_swapped).ok()
+    }
+
+    /// Deposits and withdrawals of both sides keep the pool ratio, and so the
+    /// weights, so this is the same ratio calculation as the constant product curve.
+    fn lp_tokens_to_trading_tokens(
+        &self,
+        lp_token_amount: u128,
+        lp_token_supply: u128,
+        swap_token_0_amount: u128,
+        swap_token_1_amount: u128,
+        round_direction: RoundDirection,
+    ) -> Option<TradingTokenResult> {
+        ConstantProductCurve::lp_tokens_to_trading_tokens(
+            lp_token_amount,
+            lp_token_supply,
+            swap_token_0_amount,
+            swap_token_1_amount,
+            round_direction,
+        )
+    }
+
+    /// The weighted implementation for this function gives the weighted
+    /// geometric mean x^weight_0 * y^weight_1.
+    fn normalized_value(
+        &self,
+        swap_token_0_amount: u128,
+        swap_token_1_amount: u128,
+    ) -> Option<PreciseNumber> {
+        let value =
+            exp2(i128::try_from(self.invariant(swap_token_0_amount, swap_token_1_amount)?).ok()?)?;
+        let integer = PreciseNumber::new(u128::try_from(value >> Q64_BITS).ok()?)?;
+        let fraction = PreciseNumber::new((value - ((value >> Q64_BITS) << Q64_BITS)).as_u128())?
+            .checked_div(&PreciseNumber::new(1u128 << Q64_BITS)?)?;
+        integer.checked_add(&fraction)
+    }
+
+    /// log2(x^weight_0 * y^weight_1) in Q64.64, which is monotonic in the
+    /// invariant and can be computed without overflow.
+    fn invariant(&self, swap_token_0_amount: u128, swap_token_1_amount: u128) -> Option<u128> {
+        if swap_token_0_amount == 0 || swap_token_1_amount == 0 {
+            return None;
+        }
+        let log_0 = log2(U256::from(swap_token_0_amount) << Q64_BITS)?;
+        let log_1 = log2(U256::from(swap_token_1_amount) << Q64_BITS)?;
+        let value = log_0
+            .checked_mul(i128::from(self.weight_0))?
+            .checked_add(log_1.checked_mul(i128::from(self.weight_1))?)?
+            .checked_div(i128::from(WEIGHT_DENOMINATOR_VALUE))?;
+        u128::try_from(value).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::curve::calculator::test::total_and_intermediate, proptest::prelude::*};
+
+    /// Checks that a swap never reduces the invariant of the pool
+    fn check_curve_value_from_swap(
+        curve: &WeightedCurve,
+        source_token_amount: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        trade_direction: TradeDirection,
+    ) {
+        let destination_amount_swapped = curve
+            .swap_base_input_without_fees(
+                source_token_amount,
+                swap_source_amount,
+                swap_destination_amount,
+                trade_direction,
+            )
+            .unwrap();
+
+        let (swap_token_0_amount, swap_token_1_amount) = match trade_direction {
+            TradeDirection::ZeroForOne => (swap_source_amount, swap_destination_amount),
+            TradeDirection::OneForZero => (swap_destination_amount, swap_source_amount),
+        };
+        let previous_value = curve
+            .invariant(swap_token_0_amount, swap_token_1_amount)
+            .unwrap();
+
+        let new_swap_source_amount = swap_source_amount.checked_add(source_token_amount).unwrap();
+        let new_swap_destination_amount = swap_destination_amount
+            .checked_sub(destination_amount_swapped)
+            .unwrap();
+        let (swap_token_0_amount, swap_token_1_amount) = match trade_direction {
+            TradeDirection::ZeroForOne => (new_swap_source_amount, new_swap_destination_amount),
+            TradeDirection::OneForZero => (new_swap_destination_amount, new_swap_source_amount),
+        };
+        let new_value = curve
+            .invariant(swap_token_0_amount, swap_token_1_amount)
+            .unwrap();
+        assert!(new_value >= previous_value);
+    }
+
+    #[test]
+    fn new_checks_weights() {
+        assert!(WeightedCurve::new(0, WEIGHT_DENOMINATOR_VALUE).is_none());
+        assert!(
+            WeightedCurve::new(MIN_WEIGHT - 1, WEIGHT_DENOMINATOR_VALUE - MIN_WEIGHT + 1).is_none()
+        );
+        assert!(WeightedCurve::new(800_000, 300_000).is_none());
+        assert_eq!(
+            WeightedCurve::new(800_000, 200_000),
+            Some(WeightedCurve {
+                weight_0: 800_000,
+                weight_1: 200_000
+            })
+        );
+    }
+
+    #[test]
+    fn fixed_point_log2_and_exp2() {
+        assert_eq!(log2(one()), Some(0));
+        assert_eq!(log2(one() << 10), Some(10i128 << Q64_BITS));
+        assert_eq!(log2(one() >> 3), Some(-3i128 << Q64_BITS));
+        assert_eq!(exp2(5i128 << Q64_BITS), Some(one() << 5));
+        // 2^0.5 = 1.41421356237...
+        let sqrt_2 = exp2(1i128 << (Q64_BITS - 1)).unwrap();
+        assert_eq!(
+            (sqrt_2 * U256::from(100_000_000_000u64)) >> Q64_BITS,
+            U256::from(141_421_356_237u64)
+        );
+        // log2(3) = 1.58496250072...
+        let log2_3 = log2(U256::from(3u8) << Q64_BITS).unwrap();
+        assert_eq!(
+            (U256::from(log2_3 as u128) * U256::from(100_000_000_000u64)) >> Q64_BITS,
+            U256::from(158_496_250_072u64)
+        );
+    }
+
+    #[test]
+    fn equal_weights_match_constant_product() {
+        let curve = WeightedCurve::new(500_000, 500_000).unwrap();
+        let tests: &[(u128, u128, u128)] = &[
+            (10, 4_000_000, 70_000_000_000),
+            (1_000_000, 30_000_000, 10_000_000),
+            (5_000_000_000, 10_000_000_000, 20_000_000_000),
+        ];
+        for (source_amount, swap_source_amount, swap_destination_amount) in tests.iter() {
+            let weighted = curve
+                .swap_base_input_without_fees(
+                    *source_amount,
+                    *swap_source_amount,
+                    *swap_destination_amount,
+                    TradeDirection::ZeroForOne,
+                )
+                .unwrap();
+            let constant_product = ConstantProductCurve::swap_base_input_without_fees(
+                *source_amount,
+                *swap_source_amount,
+                *swap_destination_amount,
+            );
+            assert!(weighted <= constant_product);
+            // only the error margin of the power is lost
+            assert!(
+                constant_product - weighted
+                    <= (swap_destination_amount >> POW_ERROR_MARGIN_BITS) + 1
+            );
+        }
+    }
+
+    #[test]
+    fn weights_favor_the_heavier_side() {
+        // an 80/20 pool where token_1 is priced at 4 token_0
+        let curve = WeightedCurve::new(800_000, 200_000).unwrap();
+        let swap_token_0_amount = 4_000_000_000u128;
+        let swap_token_1_amount = 250_000_000u128;
+        // selling a small amount of token_1 returns about 4 token_0 per token_1
+        let destination_amount_swapped = curve
+            .swap_base_input_without_fees(
+                1_000,
+                swap_token_1_amount,
+                swap_token_0_amount,
+                TradeDirection::OneForZero,
+            )
+            .unwrap();
+        assert_eq!(destination_amount_swapped, 3_999);
+    }
+
+    #[test]
+    fn swap_base_output_covers_destination_amount() {
+        let curve = WeightedCurve::new(800_000, 200_000).unwrap();
+        for trade_direction in [TradeDirection::ZeroForOne, TradeDirection::OneForZero] {
+            let swap_source_amount = 2_000_000_000u128;
+            let swap_destination_amount = 1_000_000_000u128;
+            let destination_amount = 50_000_000u128;
+            let source_amount = curve
+                .swap_base_output_without_fees(
+                    destination_amount,
+                    swap_source_amount,
+                    swap_destination_amount,
+                    trade_direction,
+                )
+                .unwrap();
+            let destination_amount_swapped = curve
+                .swap_base_input_without_fees(
+                    source_amount,
+                    swap_source_amount,
+                    swap_destination_amount,
+                    trade_direction,
+                )
+                .unwrap();
+            assert!(destination_amount_swapped >= destination_amount);
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn curve_value_does_not_decrease_from_swap(
+            weight_0 in MIN_WEIGHT..=WEIGHT_DENOMINATOR_VALUE - MIN_WEIGHT,
+            source_token_amount in 1..u64::MAX as u128,
+            swap_source_amount in 1..u64::MAX as u128,
+            swap_destination_amount in 1..u64::MAX as u128,
+            zero_for_one in proptest::bool::ANY,
+        ) {
+            let curve = WeightedCurve::new(weight_0, WEIGHT_DENOMINATOR_VALUE - weight_0).unwrap();
+            let trade_direction = if zero_for_one {
+                TradeDirection::ZeroForOne
+            } else {
+                TradeDirection::OneForZero
+            };
+            check_curve_value_from_swap(
+                &curve,
+                source_token_amount,
+                swap_source_amount,
+                swap_destination_amount,
+                trade_direction,
+            );
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn curve_value_does_not_decrease_from_swap_base_output(
+            weight_0 in MIN_WEIGHT..=WEIGHT_DENOMINATOR_VALUE - MIN_WEIGHT,
+            (swap_destination_amount, destination_amount) in total_and_intermediate(u64::MAX),
+            swap_source_amount in 1..u32::MAX as u128,
+            zero_for_one in proptest::bool::ANY,
+        ) {
+            let curve = WeightedCurve::new(weight_0, WEIGHT_DENOMINATOR_VALUE - weight_0).unwrap();
+            let (swap_source_weight, swap_destination_weight) = if zero_for_one {
+                (curve.weight_0, curve.weight_1)
+            } else {
+                (curve.weight_1, curve.weight_0)
+            };
+            let swap_destination_amount = swap_destination_amount as u128;
+            let destination_amount = destination_amount as u128;
+            let trade_direction = if zero_for_one {
+                TradeDirection::ZeroForOne
+            } else {
+                TradeDirection::OneForZero
+            };
+            let source_amount = curve.swap_base_output_without_fees(
+                destination_amount,
+                swap_source_amount,
+                swap_destination_amount,
+                trade_direction,
+            );
+            // very large trades overflow instead of returning a wrong amount
+            prop_assume!(source_amount.is_some());
+            let source_amount = source_amount.unwrap();
+            let previous_value = WeightedCurve {
+                weight_0: swap_source_weight,
+                weight_1: swap_destination_weight,
+            }
+            .invariant(swap_source_amount, swap_destination_amount)
+            .unwrap();
+            let new_value = WeightedCurve {
+                weight_0: swap_source_weight,
+                weight_1: swap_destination_weight,
+            }
+            .invariant(
+                swap_source_amount + source_amount,
+                swap_destination_amount - destination_amount,
+            )
+            .unwrap();
+            assert!(new_value >= previous_value);
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn curve_value_does_not_decrease_from_withdraw(
+            weight_0 in MIN_WEIGHT..=WEIGHT_DENOMINATOR_VALUE - MIN_WEIGHT,
+            (pool_token_supply, pool_token_amount) in total_and_intermediate(u64::MAX),
+            swap_token_a_amount in 1..u64::MAX,
+            swap_token_b_amount in 1..u64::MAX,
+        ) {
+            let curve = WeightedCurve::new(weight_0, WEIGHT_DENOMINATOR_VALUE - weight_0).unwrap();
+            let pool_token_amount = pool_token_amount as u128;
+            let pool_token_supply = pool_token_supply as u128;
+            let swap_token_a_amount = swap_token_a_amount as u128;
+            let swap_token_b_amount = swap_token_b_amount as u128;
+            // Make sure we will get at least one trading token out for each
+            // side, otherwise the calculation fails
+            prop_assume!(pool_token_amount * swap_token_a_amount / pool_token_supply >= 1);
+            prop_assume!(pool_token_amount * swap_token_b_amount / pool_token_supply >= 1);
+            let withdraw_result = curve
+                .lp_tokens_to_trading_tokens(
+                    pool_token_amount,
+                    pool_token_supply,
+                    swap_token_a_amount,
+                    swap_token_b_amount,
+                    RoundDirection::Floor,
+                )
+                .unwrap();
+            let value = curve
+                .normalized_value(swap_token_a_amount, swap_token_b_amount)
+                .unwrap();
+            let new_value = curve
+                .normalized_value(
+                    swap_token_a_amount - withdraw_result.token_0_amount,
+                    swap_token_b_amount - withdraw_result.token_1_amount,
+                )
+                .unwrap();
+            // new_value / new_pool_token_supply >= value / pool_token_supply,
+            // with an epsilon of the precision of the fixed-point numbers
+            let lp_token_supply = PreciseNumber::new(pool_token_supply).unwrap();
+            let new_lp_token_supply =
+                PreciseNumber::new(pool_token_supply - pool_token_amount).unwrap();
+            let epsilon = value
+                .checked_div(&PreciseNumber::new(1u128 << 40).unwrap())
+                .unwrap();
+            assert!(new_value
+                .checked_add(&epsilon)
+                .unwrap()
+                .checked_mul(&lp_token_supply)
+                .unwrap()
+                .greater_than_or_equal(&value.checked_mul(&new_lp_token_supply).unwrap()));
+        }
+    }
+}
@@ -27,7 +27,55 @@ pub enum ErrorCode {
     NotSupportMint,
     #[msg("invaild vault")]
     InvalidVault,
-    #[msg("Init lp amount is too less(Because 100 amount lp will be locked)")]
+    #[msg("Init lp amount is too less(Because the lock lp amount of the config will be locked)")]
     InitLpAmountTooLess,
+    /// Fee rate changes must go through `queue_config_update`
+    #[msg("Config param is timelocked, queue the update instead")]
+    ConfigUpdateTimelocked,
+    /// No config update is queued, or its delay has not elapsed yet
+    #[msg("No config update is ready to be applied")]
+    ConfigUpdateNotReady,
+    /// The observations do not cover the requested twap window
+    #[msg("Not enough observations for the twap window")]
+    ObservationNotEnough,
+    /// Unknown curve type, or curve params out of range
+    #[msg("Invalid curve type or params")]
+    InvalidCurve,
+    /// A flash loan against the pool is outstanding
+    #[msg("Pool is locked by a flash loan")]
+    PoolLocked,
+    /// The vaults did not receive the principal plus the fee back
+    #[msg("Flash loan is not repaid")]
+    FlashLoanNotRepaid,
+    /// The transaction landed after its deadline
+    #[msg("Transaction deadline exceeded")]
+    DeadlineExceeded,
+    /// The creator fee rate is above the max creator fee rate of the config
+    #[msg("Creator fee rate is too high")]
+    CreatorFeeRateTooHigh,
+    /// The launch fee rate is not below the fee rate denominator or the decay is unknown
+    #[msg("Invalid launch fee")]
+    InvalidLaunchFee,
+    /// A vault holds less than the amounts the pool owes out of it
+    #[msg("Vault amount is less than the amount owed")]
+    InsufficientVault,
+    /// An amount calculation overflowed
+    #[msg("Math overflow")]
+    MathOverflow,
+    /// A fee rate is above the fee rate denominator, or the shares of the
+    /// trade fee add up to more than the whole trade fee
+    #[msg("Invalid fee rate")]
+    InvalidFeeRate,
+    /// No config update is queued
+    #[msg("No config update is queued")]
+    ConfigUpdateNotQueued,
+    /// No longer returned, referral fees are a rebate the payer can take
+    #[msg("Invalid referrer")]
+    InvalidReferrer,
+    /// The launch fee rate or duration is above the limit of the config
+    #[msg("Launch fee is too high")]
+    LaunchFeeTooHigh,
+    /// The pool already records its observations
+    #[msg("Observation already initialized")]
+    ObservationAlreadyInitialized,
 }
-
@@ -0,0 +1,45 @@
+use crate::emit_event;
+use crate::error::ErrorCode;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(index: u16)]
+pub struct AcceptAdmin<'info> {
+    /// The proposed amm config owner
+    #[account(address = amm_config.pending_owner @ ErrorCode::InvalidOwner)]
+    pub pending_owner: Signer<'info>,
+
+    /// Amm config account to be taken over
+    #[account(
+        mut,
+        seeds = [
+            AMM_CONFIG_SEED.as_bytes(),
+            &index.to_be_bytes()
+        ],
+        bump = amm_config.bump
+    )]
+    pub amm_config: Account<'info, AmmConfig>,
+}
+
+pub fn process_accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+    let amm_config = &mut ctx.accounts.amm_config;
+    let owner_before = amm_config.owner;
+    amm_config.owner = amm_config.pending_owner;
+    amm_config.pending_owner = Pubkey::default();
+    // the create pool fee follows the owner unless it was sent elsewhere
+    if amm_config.create_pool_fee_receiver == owner_before {
+        amm_config.create_pool_fee_receiver = amm_config.owner;
+    }
+
+    emit_event!(
+        ctx,
+        AdminTransferAccepted {
+            account: ctx.accounts.amm_config.key(),
+            admin_before: owner_before,
+            admin: ctx.accounts.amm_config.owner,
+        }
+    );
+    Ok(())
+}
@@ -0,0 +1,39 @@
+use crate::emit_event;
+use crate::error::ErrorCode;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct AcceptProtocolAdmin<'info> {
+    /// The proposed protocol admin
+    #[account(address = protocol_admin.pending_admin @ ErrorCode::InvalidOwner)]
+    pub pending_admin: Signer<'info>,
+
+    /// Protocol admin account to be taken over
+    #[account(
+        mut,
+        seeds = [
+            PROTOCOL_ADMIN_SEED.as_bytes(),
+        ],
+        bump = protocol_admin.bump,
+    )]
+    pub protocol_admin: Account<'info, ProtocolAdmin>,
+}
+
+pub fn process_accept_protocol_admin(ctx: Context<AcceptProtocolAdmin>) -> Result<()> {
+    let protocol_admin = &mut ctx.accounts.protocol_admin;
+    let admin_before = protocol_admin.admin;
+    protocol_admin.admin = protocol_admin.pending_admin;
+    protocol_admin.pending_admin = Pubkey::default();
+
+    emit_event!(
+        ctx,
+        AdminTransferAccepted {
+            account: ctx.accounts.protocol_admin.key(),
+            admin_before,
+            admin: ctx.accounts.protocol_admin.admin,
+        }
+    );
+    Ok(())
+}
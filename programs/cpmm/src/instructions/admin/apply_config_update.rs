@@ -0,0 +1,46 @@
+use crate::emit_event;
+use crate::error::ErrorCode;
+use crate::instructions::admin::update_config::*;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(index: u16)]
+pub struct ApplyConfigUpdate<'info> {
+    /// Amm config account with a queued update
+    #[account(
+        mut,
+        seeds = [
+            AMM_CONFIG_SEED.as_bytes(),
+            &index.to_be_bytes()
+        ],
+        bump = amm_config.bump
+    )]
+    pub amm_config: Account<'info, AmmConfig>,
+}
+
+pub fn process_apply_config_update(ctx: Context<ApplyConfigUpdate>) -> Result<()> {
+    let amm_config = &mut ctx.accounts.amm_config;
+    let block_timestamp = Clock::get()?.unix_timestamp as u64;
+    if amm_config.pending_effective_at == 0 || block_timestamp < amm_config.pending_effective_at {
+        return err!(ErrorCode::ConfigUpdateNotReady);
+    }
+
+    let (param, value) = (amm_config.pending_param, amm_config.pending_value);
+    update_timelocked_param(amm_config, param, value)?;
+
+    amm_config.pending_param = 0;
+    amm_config.pending_value = 0;
+    amm_config.pending_effective_at = 0;
+
+    emit_event!(
+        ctx,
+        ConfigUpdated {
+            amm_config: ctx.accounts.amm_config.key(),
+            param,
+            value,
+        }
+    );
+    Ok(())
+}
@@ -0,0 +1,40 @@
+use crate::emit_event;
+use crate::error::ErrorCode;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(index: u16)]
+pub struct CancelAdminTransfer<'info> {
+    /// The current amm config owner
+    #[account(address = amm_config.owner @ ErrorCode::InvalidOwner)]
+    pub owner: Signer<'info>,
+
+    /// Amm config account with a pending owner
+    #[account(
+        mut,
+        seeds = [
+            AMM_CONFIG_SEED.as_bytes(),
+            &index.to_be_bytes()
+        ],
+        bump = amm_config.bump
+    )]
+    pub amm_config: Account<'info, AmmConfig>,
+}
+
+pub fn process_cancel_admin_transfer(ctx: Context<CancelAdminTransfer>) -> Result<()> {
+    let amm_config = &mut ctx.accounts.amm_config;
+    let pending_owner = amm_config.pending_owner;
+    amm_config.pending_owner = Pubkey::default();
+
+    emit_event!(
+        ctx,
+        AdminTransferCancelled {
+            account: ctx.accounts.amm_config.key(),
+            admin: ctx.accounts.owner.key(),
+            pending_admin: pending_owner,
+        }
+    );
+    Ok(())
+}
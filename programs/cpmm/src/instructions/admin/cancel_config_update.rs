@@ -0,0 +1,47 @@
+use crate::emit_event;
+use crate::error::ErrorCode;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(index: u16)]
+pub struct CancelConfigUpdate<'info> {
+    /// The amm config owner
+    #[account(address = amm_config.owner @ ErrorCode::InvalidOwner)]
+    pub owner: Signer<'info>,
+
+    /// Amm config account with a queued update
+    #[account(
+        mut,
+        seeds = [
+            AMM_CONFIG_SEED.as_bytes(),
+            &index.to_be_bytes()
+        ],
+        bump = amm_config.bump
+    )]
+    pub amm_config: Account<'info, AmmConfig>,
+}
+
+pub fn process_cancel_config_update(ctx: Context<CancelConfigUpdate>) -> Result<()> {
+    let amm_config = &mut ctx.accounts.amm_config;
+    require_neq!(
+        amm_config.pending_effective_at,
+        0,
+        ErrorCode::ConfigUpdateNotQueued
+    );
+    let (param, value) = (amm_config.pending_param, amm_config.pending_value);
+    amm_config.pending_param = 0;
+    amm_config.pending_value = 0;
+    amm_config.pending_effective_at = 0;
+
+    emit_event!(
+        ctx,
+        ConfigUpdateCancelled {
+            amm_config: ctx.accounts.amm_config.key(),
+            param,
+            value,
+        }
+    );
+    Ok(())
+}
@@ -0,0 +1,40 @@
+use crate::emit_event;
+use crate::error::ErrorCode;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct CancelProtocolAdminTransfer<'info> {
+    /// The current protocol admin
+    #[account(address = protocol_admin.admin @ ErrorCode::InvalidOwner)]
+    pub admin: Signer<'info>,
+
+    /// Protocol admin account with a pending admin
+    #[account(
+        mut,
+        seeds = [
+            PROTOCOL_ADMIN_SEED.as_bytes(),
+        ],
+        bump = protocol_admin.bump,
+    )]
+    pub protocol_admin: Account<'info, ProtocolAdmin>,
+}
+
+pub fn process_cancel_protocol_admin_transfer(
+    ctx: Context<CancelProtocolAdminTransfer>,
+) -> Result<()> {
+    let protocol_admin = &mut ctx.accounts.protocol_admin;
+    let pending_admin = protocol_admin.pending_admin;
+    protocol_admin.pending_admin = Pubkey::default();
+
+    emit_event!(
+        ctx,
+        AdminTransferCancelled {
+            account: ctx.accounts.protocol_admin.key(),
+            admin: ctx.accounts.admin.key(),
+            pending_admin,
+        }
+    );
+    Ok(())
+}
@@ -1,17 +1,29 @@
+use crate::emit_event;
+use crate::error::ErrorCode;
 use crate::{constants, state::*};
 use anchor_lang::prelude::*;
 use std::ops::DerefMut;
 
+#[cfg_attr(feature = "event-cpi", event_cpi)]
 #[derive(Accounts)]
 #[instruction(index: u16)]
 pub struct CreateAmmConfig<'info> {
     /// Address to be set as protocol owner.
     #[account(
         mut,
-        // address = crate::admin::id() @ ErrorCode::InvalidOwner
+        address = protocol_admin.admin @ ErrorCode::InvalidOwner
     )]
     pub owner: Signer<'info>,
 
+    /// Protocol admin account, stores the address allowed to sign admin instructions
+    #[account(
+        seeds = [
+            PROTOCOL_ADMIN_SEED.as_bytes(),
+        ],
+        bump = protocol_admin.bump,
+    )]
+    pub protocol_admin: Box<Account<'info, ProtocolAdmin>>,
+
     /// Initialize config state account to store protocol owner address and fee rates.
     #[account(
         init,
@@ -32,11 +44,44 @@ pub fn process_create_amm_config(
     ctx: Context<CreateAmmConfig>,
     index: u16,
     trade_fee_rate: u64,
+    protocol_fee_rate: u64,
+    fund_fee_rate: u64,
+    create_pool_fee: u64,
 ) -> Result<()> {
     let amm_config = ctx.accounts.amm_config.deref_mut();
     amm_config.bump = ctx.bumps.amm_config;
+    amm_config.owner = ctx.accounts.owner.key();
+    amm_config.pending_owner = Pubkey::default();
     amm_config.disable_create_pool = false;
     amm_config.index = index;
     amm_config.trade_fee_rate = trade_fee_rate;
+    amm_config.protocol_fee_rate = protocol_fee_rate;
+    amm_config.fund_fee_rate = fund_fee_rate;
+    amm_config.update_delay = DEFAULT_CONFIG_UPDATE_DELAY;
+    amm_config.pending_param = 0;
+    amm_config.pending_value = 0;
+    amm_config.pending_effective_at = 0;
+    amm_config.flash_loan_fee_rate = 0;
+    amm_config.referral_fee_rate = 0;
+    amm_config.lock_lp_amount = DEFAULT_LOCK_LP_AMOUNT;
+    amm_config.create_pool_fee = create_pool_fee;
+    amm_config.create_pool_fee_receiver = ctx.accounts.owner.key();
+    amm_config.max_creator_fee_rate = 0;
+    amm_config.max_launch_fee_rate = 0;
+    amm_config.max_launch_fee_duration = 0;
+    amm_config.padding = [0u64; 14];
+
+    emit_event!(
+        ctx,
+        AmmConfigCreated {
+            amm_config: ctx.accounts.amm_config.key(),
+            owner: ctx.accounts.owner.key(),
+            index,
+            trade_fee_rate,
+            protocol_fee_rate,
+            fund_fee_rate,
+            create_pool_fee,
+        }
+    );
     Ok(())
 }
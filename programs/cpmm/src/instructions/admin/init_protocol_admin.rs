@@ -0,0 +1,56 @@
+use crate::emit_event;
+use crate::error::ErrorCode;
+use crate::program::Cpmm;
+use crate::{constants, state::*};
+use anchor_lang::prelude::*;
+use std::ops::DerefMut;
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct InitProtocolAdmin<'info> {
+    /// The program upgrade authority, the only key allowed to appoint the protocol admin
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Initialize protocol admin state account to store the admin address
+    #[account(
+        init,
+        seeds = [
+            PROTOCOL_ADMIN_SEED.as_bytes(),
+        ],
+        bump,
+        payer = payer,
+        space = constants::DISCRIMINATOR + ProtocolAdmin::INIT_SPACE
+    )]
+    pub protocol_admin: Account<'info, ProtocolAdmin>,
+
+    /// This program, used to look up its program data account
+    #[account(
+        constraint = cpmm_program.programdata_address()? == Some(program_data.key()) @ ErrorCode::InvalidInput
+    )]
+    pub cpmm_program: Program<'info, Cpmm>,
+
+    /// The program data account holding the upgrade authority
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(payer.key()) @ ErrorCode::InvalidOwner
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn process_init_protocol_admin(ctx: Context<InitProtocolAdmin>, admin: Pubkey) -> Result<()> {
+    let protocol_admin = ctx.accounts.protocol_admin.deref_mut();
+    protocol_admin.bump = ctx.bumps.protocol_admin;
+    protocol_admin.admin = admin;
+    protocol_admin.pending_admin = Pubkey::default();
+
+    emit_event!(
+        ctx,
+        ProtocolAdminInitialized {
+            protocol_admin: ctx.accounts.protocol_admin.key(),
+            admin,
+        }
+    );
+    Ok(())
+}
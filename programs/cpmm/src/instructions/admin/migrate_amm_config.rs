@@ -0,0 +1,87 @@
+use crate::emit_event;
+use crate::error::ErrorCode;
+use crate::{constants, state::*};
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_lang::Discriminator;
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(index: u16)]
+pub struct MigrateAmmConfig<'info> {
+    /// The protocol admin, becomes the owner of the migrated config
+    #[account(
+        mut,
+        address = protocol_admin.admin @ ErrorCode::InvalidOwner
+    )]
+    pub owner: Signer<'info>,
+
+    /// Protocol admin account, stores the address allowed to sign admin instructions
+    #[account(
+        seeds = [
+            PROTOCOL_ADMIN_SEED.as_bytes(),
+        ],
+        bump = protocol_admin.bump,
+    )]
+    pub protocol_admin: Box<Account<'info, ProtocolAdmin>>,
+
+    /// CHECK: amm config account in the layout before the owner fields were
+    /// added, it can not be deserialized until it is resized
+    #[account(
+        mut,
+        seeds = [
+            AMM_CONFIG_SEED.as_bytes(),
+            &index.to_be_bytes()
+        ],
+        bump,
+        owner = crate::ID @ ErrorCode::InvalidInput,
+    )]
+    pub amm_config: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn process_migrate_amm_config(ctx: Context<MigrateAmmConfig>) -> Result<()> {
+    let amm_config_info = ctx.accounts.amm_config.to_account_info();
+    let space = constants::DISCRIMINATOR + AmmConfig::INIT_SPACE;
+    require_gt!(space, amm_config_info.data_len(), ErrorCode::InvalidInput);
+    require!(
+        amm_config_info
+            .try_borrow_data()?
+            .starts_with(&AmmConfig::DISCRIMINATOR),
+        ErrorCode::InvalidInput
+    );
+
+    //1.补足扩容后的租金
+    let rent_due = Rent::get()?
+        .minimum_balance(space)
+        .saturating_sub(amm_config_info.lamports());
+    if rent_due > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.owner.to_account_info(),
+                    to: amm_config_info.clone(),
+                },
+            ),
+            rent_due,
+        )?;
+    }
+
+    //2.扩容,新增字段以0填充后写入owner及默认值
+    amm_config_info.realloc(space, true)?;
+    let mut amm_config = AmmConfig::try_deserialize(&mut &amm_config_info.try_borrow_data()?[..])?;
+    amm_config.migrate(ctx.accounts.owner.key());
+    amm_config.try_serialize(&mut &mut amm_config_info.try_borrow_mut_data()?[..])?;
+
+    emit_event!(
+        ctx,
+        AmmConfigMigrated {
+            amm_config: ctx.accounts.amm_config.key(),
+            owner: ctx.accounts.owner.key(),
+            index: amm_config.index,
+        }
+    );
+    Ok(())
+}
@@ -1,8 +1,44 @@
+pub mod init_protocol_admin;
+pub use init_protocol_admin::*;
+
+pub mod propose_protocol_admin;
+pub use propose_protocol_admin::*;
+
+pub mod accept_protocol_admin;
+pub use accept_protocol_admin::*;
+
+pub mod cancel_protocol_admin_transfer;
+pub use cancel_protocol_admin_transfer::*;
+
 pub mod create_config;
 pub use create_config::*;
 
+pub mod migrate_amm_config;
+pub use migrate_amm_config::*;
+
 pub mod update_config;
 pub use update_config::*;
 
+pub mod set_create_pool_fee_receiver;
+pub use set_create_pool_fee_receiver::*;
+
+pub mod queue_config_update;
+pub use queue_config_update::*;
+
+pub mod apply_config_update;
+pub use apply_config_update::*;
+
+pub mod cancel_config_update;
+pub use cancel_config_update::*;
+
 pub mod update_pool_status;
 pub use update_pool_status::*;
+
+pub mod propose_admin;
+pub use propose_admin::*;
+
+pub mod accept_admin;
+pub use accept_admin::*;
+
+pub mod cancel_admin_transfer;
+pub use cancel_admin_transfer::*;
@@ -0,0 +1,40 @@
+use crate::emit_event;
+use crate::error::ErrorCode;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(index: u16)]
+pub struct ProposeAdmin<'info> {
+    /// The current amm config owner
+    #[account(address = amm_config.owner @ ErrorCode::InvalidOwner)]
+    pub owner: Signer<'info>,
+
+    /// Amm config account to be handed over
+    #[account(
+        mut,
+        seeds = [
+            AMM_CONFIG_SEED.as_bytes(),
+            &index.to_be_bytes()
+        ],
+        bump = amm_config.bump
+    )]
+    pub amm_config: Account<'info, AmmConfig>,
+}
+
+pub fn process_propose_admin(ctx: Context<ProposeAdmin>, new_owner: Pubkey) -> Result<()> {
+    require_keys_neq!(new_owner, Pubkey::default(), ErrorCode::InvalidInput);
+    let amm_config = &mut ctx.accounts.amm_config;
+    amm_config.pending_owner = new_owner;
+
+    emit_event!(
+        ctx,
+        AdminTransferProposed {
+            account: ctx.accounts.amm_config.key(),
+            admin: ctx.accounts.owner.key(),
+            pending_admin: new_owner,
+        }
+    );
+    Ok(())
+}
@@ -0,0 +1,41 @@
+use crate::emit_event;
+use crate::error::ErrorCode;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct ProposeProtocolAdmin<'info> {
+    /// The current protocol admin
+    #[account(address = protocol_admin.admin @ ErrorCode::InvalidOwner)]
+    pub admin: Signer<'info>,
+
+    /// Protocol admin account to be handed over
+    #[account(
+        mut,
+        seeds = [
+            PROTOCOL_ADMIN_SEED.as_bytes(),
+        ],
+        bump = protocol_admin.bump,
+    )]
+    pub protocol_admin: Account<'info, ProtocolAdmin>,
+}
+
+pub fn process_propose_protocol_admin(
+    ctx: Context<ProposeProtocolAdmin>,
+    new_admin: Pubkey,
+) -> Result<()> {
+    require_keys_neq!(new_admin, Pubkey::default(), ErrorCode::InvalidInput);
+    let protocol_admin = &mut ctx.accounts.protocol_admin;
+    protocol_admin.pending_admin = new_admin;
+
+    emit_event!(
+        ctx,
+        AdminTransferProposed {
+            account: ctx.accounts.protocol_admin.key(),
+            admin: ctx.accounts.admin.key(),
+            pending_admin: new_admin,
+        }
+    );
+    Ok(())
+}
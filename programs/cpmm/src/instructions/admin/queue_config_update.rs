@@ -0,0 +1,56 @@
+use crate::emit_event;
+use crate::error::ErrorCode;
+use crate::instructions::admin::update_config::*;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(index: u16)]
+pub struct QueueConfigUpdate<'info> {
+    /// The amm config owner
+    #[account(address = amm_config.owner @ ErrorCode::InvalidOwner)]
+    pub owner: Signer<'info>,
+
+    /// Amm config account to be changed
+    #[account(
+        mut,
+        seeds = [
+            AMM_CONFIG_SEED.as_bytes(),
+            &index.to_be_bytes()
+        ],
+        bump = amm_config.bump
+    )]
+    pub amm_config: Account<'info, AmmConfig>,
+}
+
+pub fn process_queue_config_update(
+    ctx: Context<QueueConfigUpdate>,
+    param: u8,
+    value: u64,
+) -> Result<()> {
+    require!(is_timelocked_param(param), ErrorCode::InvalidInput);
+    let amm_config = &mut ctx.accounts.amm_config;
+
+    // reject values that could never be applied
+    let mut preview = AmmConfig::clone(amm_config);
+    update_timelocked_param(&mut preview, param, value)?;
+
+    let block_timestamp = Clock::get()?.unix_timestamp as u64;
+    amm_config.pending_param = param;
+    amm_config.pending_value = value;
+    amm_config.pending_effective_at = block_timestamp
+        .checked_add(amm_config.update_delay)
+        .unwrap();
+
+    emit_event!(
+        ctx,
+        ConfigUpdateQueued {
+            amm_config: ctx.accounts.amm_config.key(),
+            param,
+            value,
+            effective_at: ctx.accounts.amm_config.pending_effective_at,
+        }
+    );
+    Ok(())
+}
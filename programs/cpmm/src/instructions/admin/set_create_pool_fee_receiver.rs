@@ -0,0 +1,44 @@
+use crate::emit_event;
+use crate::error::ErrorCode;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(index: u16)]
+pub struct SetCreatePoolFeeReceiver<'info> {
+    /// The amm config owner
+    #[account(address = amm_config.owner @ ErrorCode::InvalidOwner)]
+    pub owner: Signer<'info>,
+
+    /// Amm config account to be changed
+    #[account(
+        mut,
+        seeds = [
+            AMM_CONFIG_SEED.as_bytes(),
+            &index.to_be_bytes()
+        ],
+        bump = amm_config.bump
+    )]
+    pub amm_config: Account<'info, AmmConfig>,
+}
+
+pub fn process_set_create_pool_fee_receiver(
+    ctx: Context<SetCreatePoolFeeReceiver>,
+    receiver: Pubkey,
+) -> Result<()> {
+    require_keys_neq!(receiver, Pubkey::default(), ErrorCode::InvalidInput);
+    let amm_config = &mut ctx.accounts.amm_config;
+    let receiver_before = amm_config.create_pool_fee_receiver;
+    amm_config.create_pool_fee_receiver = receiver;
+
+    emit_event!(
+        ctx,
+        CreatePoolFeeReceiverUpdated {
+            amm_config: ctx.accounts.amm_config.key(),
+            receiver_before,
+            receiver,
+        }
+    );
+    Ok(())
+}
@@ -1,13 +1,15 @@
 use crate::curve::fees::FEE_RATE_DENOMINATOR_VALUE;
+use crate::emit_event;
 use crate::error::ErrorCode;
 use crate::state::*;
 use anchor_lang::prelude::*;
 
+#[cfg_attr(feature = "event-cpi", event_cpi)]
 #[derive(Accounts)]
-#[instruction(index:u16)]
+#[instruction(param: u8, value: u64, index: u16)]
 pub struct UpdateAmmConfig<'info> {
-    /// The amm config owner or admin
-    // #[account(address = crate::admin::id() @ ErrorCode::InvalidOwner)]
+    /// The amm config owner
+    #[account(address = amm_config.owner @ ErrorCode::InvalidOwner)]
     pub owner: Signer<'info>,
 
     /// Amm config account to be changed
@@ -31,16 +33,203 @@ pub fn process_update_amm_config(
     let amm_config: &mut Account<'_, AmmConfig> = &mut ctx.accounts.amm_config;
     let match_param: Option<u8> = Some(param);
     match match_param {
-        Some(0) => update_trade_fee_rate(amm_config, value),
-        Some(1) => amm_config.disable_create_pool = if value == 0 { false } else { true },
+        Some(param) if is_timelocked_param(param) => {
+            return err!(ErrorCode::ConfigUpdateTimelocked)
+        }
+        Some(1) => amm_config.disable_create_pool = value != 0,
+        Some(7) => update_lock_lp_amount(amm_config, value)?,
+        Some(10) => update_max_creator_fee_rate(amm_config, value)?,
+        Some(11) => update_max_launch_fee_rate(amm_config, value)?,
+        Some(12) => update_max_launch_fee_duration(amm_config, value)?,
         _ => return err!(ErrorCode::InvalidInput),
     }
 
+    emit_event!(
+        ctx,
+        ConfigUpdated {
+            amm_config: ctx.accounts.amm_config.key(),
+            param,
+            value,
+        }
+    );
     Ok(())
 }
 
-fn update_trade_fee_rate(amm_config: &mut Account<AmmConfig>, trade_fee_rate: u64) {
-    assert!(trade_fee_rate < FEE_RATE_DENOMINATOR_VALUE);
+/// Params that change the fees charged to traders and LPs. They can only be
+/// changed through `queue_config_update` and `apply_config_update`.
+pub fn is_timelocked_param(param: u8) -> bool {
+    matches!(param, 0 | 2 | 3 | 4 | 5 | 6 | 8)
+}
+
+pub fn update_timelocked_param(amm_config: &mut AmmConfig, param: u8, value: u64) -> Result<()> {
+    match param {
+        0 => update_trade_fee_rate(amm_config, value)?,
+        2 => update_protocol_fee_rate(amm_config, value)?,
+        3 => update_fund_fee_rate(amm_config, value)?,
+        4 => update_config_update_delay(amm_config, value)?,
+        5 => update_flash_loan_fee_rate(amm_config, value)?,
+        6 => update_referral_fee_rate(amm_config, value)?,
+        8 => amm_config.create_pool_fee = value,
+        _ => return err!(ErrorCode::InvalidInput),
+    }
+    Ok(())
+}
+
+fn update_config_update_delay(amm_config: &mut AmmConfig, update_delay: u64) -> Result<()> {
+    require_gte!(update_delay, DEFAULT_CONFIG_UPDATE_DELAY, ErrorCode::InvalidInput);
+    amm_config.update_delay = update_delay;
+    Ok(())
+}
+
+fn update_trade_fee_rate(amm_config: &mut AmmConfig, trade_fee_rate: u64) -> Result<()> {
+    require!(trade_fee_rate < FEE_RATE_DENOMINATOR_VALUE, ErrorCode::InvalidFeeRate);
     amm_config.trade_fee_rate = trade_fee_rate;
+    Ok(())
+}
+
+fn update_protocol_fee_rate(amm_config: &mut AmmConfig, protocol_fee_rate: u64) -> Result<()> {
+    require!(protocol_fee_rate <= FEE_RATE_DENOMINATOR_VALUE, ErrorCode::InvalidFeeRate);
+    require!(
+        protocol_fee_rate
+            + amm_config.fund_fee_rate
+            + amm_config.referral_fee_rate
+            + amm_config.max_creator_fee_rate
+            <= FEE_RATE_DENOMINATOR_VALUE,
+        ErrorCode::InvalidFeeRate
+    );
+    amm_config.protocol_fee_rate = protocol_fee_rate;
+    Ok(())
+}
+
+fn update_fund_fee_rate(amm_config: &mut AmmConfig, fund_fee_rate: u64) -> Result<()> {
+    require!(fund_fee_rate <= FEE_RATE_DENOMINATOR_VALUE, ErrorCode::InvalidFeeRate);
+    require!(
+        fund_fee_rate
+            + amm_config.protocol_fee_rate
+            + amm_config.referral_fee_rate
+            + amm_config.max_creator_fee_rate
+            <= FEE_RATE_DENOMINATOR_VALUE,
+        ErrorCode::InvalidFeeRate
+    );
+    amm_config.fund_fee_rate = fund_fee_rate;
+    Ok(())
+}
+
+fn update_flash_loan_fee_rate(amm_config: &mut AmmConfig, flash_loan_fee_rate: u64) -> Result<()> {
+    require!(flash_loan_fee_rate < FEE_RATE_DENOMINATOR_VALUE, ErrorCode::InvalidFeeRate);
+    amm_config.flash_loan_fee_rate = flash_loan_fee_rate;
+    Ok(())
+}
+
+fn update_referral_fee_rate(amm_config: &mut AmmConfig, referral_fee_rate: u64) -> Result<()> {
+    require!(referral_fee_rate <= FEE_RATE_DENOMINATOR_VALUE, ErrorCode::InvalidFeeRate);
+    require!(
+        referral_fee_rate
+            + amm_config.protocol_fee_rate
+            + amm_config.fund_fee_rate
+            + amm_config.max_creator_fee_rate
+            <= FEE_RATE_DENOMINATOR_VALUE,
+        ErrorCode::InvalidFeeRate
+    );
+    amm_config.referral_fee_rate = referral_fee_rate;
+    Ok(())
 }
 
+fn update_max_creator_fee_rate(amm_config: &mut AmmConfig, max_creator_fee_rate: u64) -> Result<()> {
+    require!(max_creator_fee_rate <= FEE_RATE_DENOMINATOR_VALUE, ErrorCode::InvalidFeeRate);
+    require!(
+        max_creator_fee_rate
+            + amm_config.protocol_fee_rate
+            + amm_config.fund_fee_rate
+            + amm_config.referral_fee_rate
+            <= FEE_RATE_DENOMINATOR_VALUE,
+        ErrorCode::InvalidFeeRate
+    );
+    amm_config.max_creator_fee_rate = max_creator_fee_rate;
+    Ok(())
+}
+
+fn update_max_launch_fee_rate(amm_config: &mut AmmConfig, max_launch_fee_rate: u64) -> Result<()> {
+    require!(max_launch_fee_rate < FEE_RATE_DENOMINATOR_VALUE, ErrorCode::InvalidFeeRate);
+    amm_config.max_launch_fee_rate = max_launch_fee_rate;
+    Ok(())
+}
+
+fn update_max_launch_fee_duration(
+    amm_config: &mut AmmConfig,
+    max_launch_fee_duration: u64,
+) -> Result<()> {
+    require_gte!(LAUNCH_FEE_DURATION_LIMIT, max_launch_fee_duration, ErrorCode::InvalidInput);
+    amm_config.max_launch_fee_duration = max_launch_fee_duration;
+    Ok(())
+}
+
+fn update_lock_lp_amount(amm_config: &mut AmmConfig, lock_lp_amount: u64) -> Result<()> {
+    require!(
+        (DEFAULT_LOCK_LP_AMOUNT..=MAX_LOCK_LP_AMOUNT).contains(&lock_lp_amount),
+        ErrorCode::InvalidInput
+    );
+    amm_config.lock_lp_amount = lock_lp_amount;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn update_delay_can_not_go_below_the_default() {
+        let mut amm_config = AmmConfig {
+            update_delay: DEFAULT_CONFIG_UPDATE_DELAY,
+            ..Default::default()
+        };
+        update_timelocked_param(&mut amm_config, 4, 2 * DEFAULT_CONFIG_UPDATE_DELAY).unwrap();
+        assert_eq!(amm_config.update_delay, 2 * DEFAULT_CONFIG_UPDATE_DELAY);
+
+        for update_delay in [0, DEFAULT_CONFIG_UPDATE_DELAY - 1] {
+            assert_eq!(
+                update_timelocked_param(&mut amm_config, 4, update_delay),
+                Err(ErrorCode::InvalidInput.into())
+            );
+        }
+        update_timelocked_param(&mut amm_config, 4, DEFAULT_CONFIG_UPDATE_DELAY).unwrap();
+        assert_eq!(amm_config.update_delay, DEFAULT_CONFIG_UPDATE_DELAY);
+    }
+
+    #[test]
+    fn lock_lp_amount_is_bounded() {
+        let mut amm_config = AmmConfig::default();
+        update_lock_lp_amount(&mut amm_config, DEFAULT_LOCK_LP_AMOUNT).unwrap();
+        assert_eq!(amm_config.lock_lp_amount, DEFAULT_LOCK_LP_AMOUNT);
+        update_lock_lp_amount(&mut amm_config, MAX_LOCK_LP_AMOUNT).unwrap();
+        assert_eq!(amm_config.lock_lp_amount, MAX_LOCK_LP_AMOUNT);
+
+        assert_eq!(
+            update_lock_lp_amount(&mut amm_config, DEFAULT_LOCK_LP_AMOUNT - 1),
+            Err(ErrorCode::InvalidInput.into())
+        );
+        assert_eq!(
+            update_lock_lp_amount(&mut amm_config, MAX_LOCK_LP_AMOUNT + 1),
+            Err(ErrorCode::InvalidInput.into())
+        );
+        assert_eq!(amm_config.lock_lp_amount, MAX_LOCK_LP_AMOUNT);
+    }
+
+    #[test]
+    fn launch_fee_limits_are_bounded() {
+        let mut amm_config = AmmConfig::default();
+        update_max_launch_fee_rate(&mut amm_config, FEE_RATE_DENOMINATOR_VALUE - 1).unwrap();
+        assert_eq!(amm_config.max_launch_fee_rate, FEE_RATE_DENOMINATOR_VALUE - 1);
+        assert_eq!(
+            update_max_launch_fee_rate(&mut amm_config, FEE_RATE_DENOMINATOR_VALUE),
+            Err(ErrorCode::InvalidFeeRate.into())
+        );
+
+        update_max_launch_fee_duration(&mut amm_config, LAUNCH_FEE_DURATION_LIMIT).unwrap();
+        assert_eq!(amm_config.max_launch_fee_duration, LAUNCH_FEE_DURATION_LIMIT);
+        assert_eq!(
+            update_max_launch_fee_duration(&mut amm_config, LAUNCH_FEE_DURATION_LIMIT + 1),
+            Err(ErrorCode::InvalidInput.into())
+        );
+    }
+}
@@ -1,15 +1,18 @@
 use std::ops::DerefMut;
 
+use crate::emit_event;
+use crate::error::ErrorCode;
 use crate::state::*;
 use anchor_lang::prelude::*;
 use anchor_spl::token_interface::Mint;
 
+#[cfg_attr(feature = "event-cpi", event_cpi)]
 #[derive(Accounts)]
 #[instruction(index:u16)]
 pub struct UpdatePoolStatus<'info> {
     #[account(
-        mut
-        // address = crate::admin::id()
+        mut,
+        address = amm_config.owner @ ErrorCode::InvalidOwner
     )]
     pub authority: Signer<'info>,
 
@@ -54,7 +57,17 @@ pub struct UpdatePoolStatus<'info> {
 pub fn process_update_pool_status(ctx: Context<UpdatePoolStatus>, status: u8) -> Result<()> {
     require_gte!(255, status);
     let pool_state =ctx.accounts.pool_state.deref_mut();
+    let status_before = pool_state.status;
     pool_state.set_status(status);
     pool_state.recent_epoch = Clock::get()?.epoch;
+
+    emit_event!(
+        ctx,
+        PoolStatusChanged {
+            pool_id: ctx.accounts.pool_state.key(),
+            status_before,
+            status,
+        }
+    );
     Ok(())
 }
@@ -0,0 +1,134 @@
+use crate::emit_event;
+use crate::error::ErrorCode;
+use crate::state::*;
+use crate::utils::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use std::ops::DerefMut;
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct CollectFee<'info> {
+    /// The amm config owner for protocol and fund fees, the pool creator for creator fees
+    pub owner: Signer<'info>,
+
+    /// CHECK: pool vault and lp mint authority
+    #[account(
+        seeds = [
+            crate::AUTH_SEED.as_bytes(),
+        ],
+        bump = pool_state.auth_bump,
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    /// Pool state stores accumulated fee amounts
+    #[account(
+        mut,
+        has_one = amm_config @ ErrorCode::NotApproved,
+        has_one = token_0_vault @ ErrorCode::InvalidVault,
+        has_one = token_1_vault @ ErrorCode::InvalidVault,
+    )]
+    pub pool_state: Box<Account<'info, PoolState>>,
+
+    /// Amm config account stores owner
+    pub amm_config: Box<Account<'info, AmmConfig>>,
+
+    /// The address that holds pool tokens for token_0
+    #[account(mut)]
+    pub token_0_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The address that holds pool tokens for token_1
+    #[account(mut)]
+    pub token_1_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The mint of token_0 vault
+    #[account(
+        address = token_0_vault.mint
+    )]
+    pub vault_0_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The mint of token_1 vault
+    #[account(
+        address = token_1_vault.mint
+    )]
+    pub vault_1_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The address that receives the collected token_0 fees
+    #[account(
+        mut,
+        token::mint = vault_0_mint,
+        token::token_program = token_0_program,
+    )]
+    pub recipient_token_0_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The address that receives the collected token_1 fees
+    #[account(
+        mut,
+        token::mint = vault_1_mint,
+        token::token_program = token_1_program,
+    )]
+    pub recipient_token_1_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// token_0_program
+    pub token_0_program: Interface<'info, TokenInterface>,
+
+    /// token_1_program
+    pub token_1_program: Interface<'info, TokenInterface>,
+}
+
+pub fn process_collect_fee(
+    ctx: Context<CollectFee>,
+    fee_type: PoolFeeType,
+    amount_0_requested: u64,
+    amount_1_requested: u64,
+) -> Result<()> {
+    //1.校验签名者,协议及基金费用由config owner提取,创建者费用由pool创建者提取
+    let fee_owner = match fee_type {
+        PoolFeeType::Protocol | PoolFeeType::Fund => ctx.accounts.amm_config.owner,
+        PoolFeeType::Creator => ctx.accounts.pool_state.pool_creator,
+    };
+    require_keys_eq!(ctx.accounts.owner.key(), fee_owner, ErrorCode::InvalidOwner);
+
+    //2.计算可提取的费用
+    let pool_state = ctx.accounts.pool_state.deref_mut();
+    let (amount_0, amount_1) =
+        pool_state.take_fees(fee_type, amount_0_requested, amount_1_requested);
+
+    //3.从vault 转账到recipient token account
+    transfer_from_pool_vault_to_user(
+        ctx.accounts.authority.to_account_info(),
+        ctx.accounts.token_0_vault.to_account_info(),
+        ctx.accounts.recipient_token_0_account.to_account_info(),
+        ctx.accounts.vault_0_mint.to_account_info(),
+        ctx.accounts.token_0_program.to_account_info(),
+        amount_0,
+        ctx.accounts.vault_0_mint.decimals,
+        &[&[crate::AUTH_SEED.as_bytes(), &[pool_state.auth_bump]]],
+    )?;
+
+    transfer_from_pool_vault_to_user(
+        ctx.accounts.authority.to_account_info(),
+        ctx.accounts.token_1_vault.to_account_info(),
+        ctx.accounts.recipient_token_1_account.to_account_info(),
+        ctx.accounts.vault_1_mint.to_account_info(),
+        ctx.accounts.token_1_program.to_account_info(),
+        amount_1,
+        ctx.accounts.vault_1_mint.decimals,
+        &[&[crate::AUTH_SEED.as_bytes(), &[pool_state.auth_bump]]],
+    )?;
+
+    pool_state.recent_epoch = Clock::get()?.epoch;
+
+    emit_event!(
+        ctx,
+        FeeCollected {
+            pool_id: ctx.accounts.pool_state.key(),
+            fee_type,
+            recipient_token_0_account: ctx.accounts.recipient_token_0_account.key(),
+            recipient_token_1_account: ctx.accounts.recipient_token_1_account.key(),
+            amount_0,
+            amount_1,
+        }
+    );
+    Ok(())
+}
@@ -3,9 +3,10 @@ use std::ops::DerefMut;
 use anchor_lang::prelude::*;
 use anchor_spl::{associated_token::AssociatedToken, token::Token, token_interface::{Mint, TokenAccount, TokenInterface}};
 
-use crate::{error::ErrorCode, token_mint_to, transfer_from_user_to_pool_vault, AmmConfig, CurveCalculator, PoolState, AMM_CONFIG_SEED, AUTH_SEED, POOL_SEED};
+use crate::{check_deadline, emit_event, error::ErrorCode, get_transfer_inverse_fee, token_mint_to, LpChangeType, LpChanged, transfer_from_user_to_pool_vault, AmmConfig, PoolState, AMM_CONFIG_SEED, AUTH_SEED, POOL_SEED};
 
 
+#[cfg_attr(feature = "event-cpi", event_cpi)]
 #[derive(Accounts)]
 #[instruction(index:u16)]
 pub struct Deposit<'info>{
@@ -41,6 +42,7 @@ pub struct Deposit<'info>{
         // has_one = token_1_mint,
         has_one = token_0_vault @ ErrorCode::NotApproved,
         has_one = token_1_vault @ ErrorCode::NotApproved,
+        constraint = !pool_state.locked @ ErrorCode::PoolLocked,
         seeds = [
             POOL_SEED.as_bytes(),
             amm_config.key().as_ref(),
@@ -130,7 +132,9 @@ pub fn process_deposit(
     lp_token_amount: u64,
     maximum_token_0_amount: u64,
     maximum_token_1_amount: u64,
+    deadline: Option<i64>,
 ) -> Result<()> {
+    check_deadline(deadline)?;
 
     let pool_state = ctx.accounts.pool_state.deref_mut();
 
@@ -140,19 +144,30 @@ pub fn process_deposit(
     }
     
     //2.计算交易tokens
-    let result = CurveCalculator::lp_tokens_to_trading_tokens(
+    pool_state.sync_legacy_reserves(
+        ctx.accounts.token_0_vault.amount,
+        ctx.accounts.token_1_vault.amount,
+    )?;
+    let (total_token_0_amount, total_token_1_amount) = pool_state.reserves();
+    let result = pool_state.curve()?.lp_tokens_to_trading_tokens(
             u128::from(lp_token_amount), 
             u128::from(pool_state.lp_supply), 
-            u128::from(ctx.accounts.token_0_vault.amount), 
-            u128::from(ctx.accounts.token_1_vault.amount), 
+            u128::from(total_token_0_amount), 
+            u128::from(total_token_1_amount), 
             crate::RoundDirection::Ceiling
         ).ok_or(ErrorCode::ZeroTradingTokens)?;
 
     let token_0_amount = u64::try_from(result.token_0_amount).unwrap();
     let token_1_amount = u64::try_from(result.token_1_amount).unwrap();
 
-    if token_0_amount > maximum_token_0_amount 
-        || token_1_amount > maximum_token_1_amount {
+    //vault需收到token_0_amount和token_1_amount,用户转账数量需加上token-2022转账手续费
+    let transfer_fee_0 = get_transfer_inverse_fee(&ctx.accounts.token_0_mint.to_account_info(), token_0_amount)?;
+    let transfer_fee_1 = get_transfer_inverse_fee(&ctx.accounts.token_1_mint.to_account_info(), token_1_amount)?;
+    let transfer_token_0_amount = token_0_amount.checked_add(transfer_fee_0).unwrap();
+    let transfer_token_1_amount = token_1_amount.checked_add(transfer_fee_1).unwrap();
+
+    if transfer_token_0_amount > maximum_token_0_amount 
+        || transfer_token_1_amount > maximum_token_1_amount {
             return Err(ErrorCode::ExceededSlippage.into());
     }
 
@@ -164,7 +179,7 @@ pub fn process_deposit(
         ctx.accounts.token_0_vault.to_account_info(), 
         ctx.accounts.token_0_mint.to_account_info(), 
         ctx.accounts.token_0_program.to_account_info(),
-        token_0_amount, 
+        transfer_token_0_amount, 
         ctx.accounts.token_0_mint.decimals)?;
 
     //3.2 转账token_1
@@ -174,11 +189,16 @@ pub fn process_deposit(
         ctx.accounts.token_1_vault.to_account_info(), 
         ctx.accounts.token_1_mint.to_account_info(), 
         ctx.accounts.token_1_program.to_account_info(),
-        token_1_amount, 
+        transfer_token_1_amount, 
         ctx.accounts.token_1_mint.decimals)?;
     
     //4.更新pool_state
+    let lp_amount_before = pool_state.lp_supply;
     pool_state.lp_supply = pool_state.lp_supply.checked_add(lp_token_amount).unwrap();
+    pool_state.set_reserves(
+        total_token_0_amount.checked_add(token_0_amount).unwrap(),
+        total_token_1_amount.checked_add(token_1_amount).unwrap(),
+    );
 
     //5.mint lp_tokens
     token_mint_to(
@@ -191,5 +211,22 @@ pub fn process_deposit(
 
     pool_state.recent_epoch = Clock::get()?.epoch;
 
+    emit_event!(
+        ctx,
+        LpChanged {
+            pool_id: ctx.accounts.pool_state.key(),
+            lp_amount_before,
+            token_0_vault_before: total_token_0_amount,
+            token_1_vault_before: total_token_1_amount,
+            token_0_vault_after: total_token_0_amount.checked_add(token_0_amount).unwrap(),
+            token_1_vault_after: total_token_1_amount.checked_add(token_1_amount).unwrap(),
+            token_0_amount,
+            token_1_amount,
+            transfer_fee_0,
+            transfer_fee_1,
+            lp_amount: lp_token_amount,
+            change_type: LpChangeType::Deposit,
+        }
+    );
     Ok(())
 }
\ No newline at end of file
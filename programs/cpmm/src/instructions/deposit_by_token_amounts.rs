@@ -0,0 +1,51 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::ErrorCode, get_transfer_fee, process_deposit, Deposit};
+
+pub fn process_deposit_by_token_amounts(
+    ctx: Context<Deposit>,
+    maximum_token_0_amount: u64,
+    maximum_token_1_amount: u64,
+    minimum_lp_out: u64,
+    deadline: Option<i64>,
+) -> Result<()> {
+    let pool_state = &mut ctx.accounts.pool_state;
+
+    //1.计算两个token vault可交易token
+    pool_state.sync_legacy_reserves(
+        ctx.accounts.token_0_vault.amount,
+        ctx.accounts.token_1_vault.amount,
+    )?;
+    let (total_token_0_amount, total_token_1_amount) = pool_state.reserves();
+
+    //2.扣除token-2022转账手续费后,计算vault最多能收到的数量可支持的lp数量
+    let transfer_fee_0 = get_transfer_fee(
+        &ctx.accounts.token_0_mint.to_account_info(),
+        maximum_token_0_amount,
+    )?;
+    let transfer_fee_1 = get_transfer_fee(
+        &ctx.accounts.token_1_mint.to_account_info(),
+        maximum_token_1_amount,
+    )?;
+    let lp_token_amount = pool_state
+        .curve()?
+        .trading_tokens_to_lp_tokens(
+            u128::from(maximum_token_0_amount.checked_sub(transfer_fee_0).unwrap()),
+            u128::from(maximum_token_1_amount.checked_sub(transfer_fee_1).unwrap()),
+            u128::from(pool_state.lp_supply),
+            u128::from(total_token_0_amount),
+            u128::from(total_token_1_amount),
+        )
+        .ok_or(ErrorCode::ZeroTradingTokens)?;
+    let lp_token_amount = u64::try_from(lp_token_amount).unwrap();
+    require_gte!(lp_token_amount, minimum_lp_out, ErrorCode::ExceededSlippage);
+
+    //3.按lp数量存入,只转入所需的数量,并以最大数量作为滑点校验
+    process_deposit(
+        ctx,
+        lp_token_amount,
+        maximum_token_0_amount,
+        maximum_token_1_amount,
+        deadline,
+    )
+}
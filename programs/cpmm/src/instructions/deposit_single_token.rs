@@ -0,0 +1,269 @@
+use std::ops::DerefMut;
+
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::Token,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use crate::{
+    check_deadline, emit_event, error::ErrorCode, get_transfer_fee, pool, sorted_mints, token_mint_to,
+    transfer_from_user_to_pool_vault, AmmConfig, LpChangeType, LpChanged, ObservationState, PoolState,
+    TradeDirection, AMM_CONFIG_SEED, AUTH_SEED, POOL_SEED, POOL_VAULT_SEED,
+};
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(index:u16)]
+pub struct DepositSingleToken<'info> {
+    /// Pays to mint the position
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// CHECK: pool vault and lp mint authority
+    #[account(
+        seeds = [
+            crate::AUTH_SEED.as_bytes(),
+        ],
+        bump = pool_state.auth_bump
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [
+            AMM_CONFIG_SEED.as_bytes(),
+            &index.to_be_bytes()
+        ],
+        bump = amm_config.bump
+    )]
+    pub amm_config: Box<Account<'info, AmmConfig>>,
+
+    /// pool state
+    #[account(
+        mut,
+        has_one = lp_mint @ ErrorCode::NotApproved,
+        constraint = !pool_state.locked @ ErrorCode::PoolLocked,
+        seeds = [
+            POOL_SEED.as_bytes(),
+            amm_config.key().as_ref(),
+            sorted_mints(input_token_mint.key(), output_token_mint.key()).0.as_ref(),
+            sorted_mints(input_token_mint.key(), output_token_mint.key()).1.as_ref(),
+        ],
+        bump = pool_state.bump
+    )]
+    pub pool_state: Box<Account<'info, PoolState>>,
+
+    /// Lp token mint
+    #[account(mut)]
+    pub lp_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// owner Lp token account
+    #[account(
+        init_if_needed,
+        associated_token::mint = lp_mint,
+        associated_token::authority = owner,
+        payer = owner,
+        associated_token::token_program = token_program,
+    )]
+    pub owner_lp_token: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// owner's account of the deposited token
+    #[account(
+        mut,
+        associated_token::mint = input_token_mint,
+        associated_token::authority = owner,
+        associated_token::token_program = input_token_program
+    )]
+    pub input_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The vault receiving the deposited token
+    #[account(
+        mut,
+        constraint = input_vault.key() == pool_state.token_0_vault.key() || input_vault.key() == pool_state.token_1_vault.key(),
+        seeds = [
+            POOL_VAULT_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            input_token_mint.key().as_ref()
+        ],
+        bump,
+    )]
+    pub input_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The vault of the other token, only read for its balance
+    #[account(
+        constraint = output_vault.key() == pool_state.token_0_vault.key() || output_vault.key() == pool_state.token_1_vault.key(),
+        seeds = [
+            POOL_VAULT_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            output_token_mint.key().as_ref()
+        ],
+        bump,
+    )]
+    pub output_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// the mint of the deposited token
+    #[account(mut)]
+    pub input_token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// the mint of the other token
+    pub output_token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The program account for the most recent oracle observation
+    #[account(mut, address = pool_state.observation_key)]
+    pub observation_state: AccountLoader<'info, ObservationState>,
+
+    /// the token program of the deposited token
+    pub input_token_program: Interface<'info, TokenInterface>,
+
+    pub token_program: Program<'info, Token>,
+
+    /// Program to create an ATA for receiving position NFT
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    /// the system program
+    pub system_program: Program<'info, System>,
+}
+
+pub fn process_deposit_single_token(
+    ctx: Context<DepositSingleToken>,
+    amount_in: u64,
+    minimum_lp_out: u64,
+    deadline: Option<i64>,
+) -> Result<()> {
+    check_deadline(deadline)?;
+    let block_timestamp = Clock::get()?.unix_timestamp as u64;
+    let pool_state = ctx.accounts.pool_state.deref_mut();
+
+    //1.检查 pool state,单边存入包含一次兑换,需同时允许存入和交易
+    if !pool_state.get_status_by_bit(pool::PoolStatusBitIndex::Deposit)
+        || !pool_state.get_status_by_bit(pool::PoolStatusBitIndex::Swap)
+        || block_timestamp < pool_state.open_time
+    {
+        return err!(ErrorCode::NotApproved);
+    }
+
+    require_gt!(amount_in, 0);
+
+    //2.根据vault确定存入方向,获取两个token vault可交易token及计算价格
+    let trade_direction = pool_state.trade_direction(
+        ctx.accounts.input_vault.key(),
+        ctx.accounts.output_vault.key(),
+    )?;
+    pool_state.sync_legacy_trade_reserves(
+        trade_direction,
+        ctx.accounts.input_vault.amount,
+        ctx.accounts.output_vault.amount,
+    )?;
+    let (total_token_0_amount, total_token_1_amount) = pool_state.reserves();
+    let (total_input_token_amount, total_output_token_amount) = match trade_direction {
+        TradeDirection::ZeroForOne => (total_token_0_amount, total_token_1_amount),
+        TradeDirection::OneForZero => (total_token_1_amount, total_token_0_amount),
+    };
+    let (token_0_price_x32, token_1_price_x32) =
+        pool_state.token_price_x32(total_token_0_amount, total_token_1_amount);
+
+    //扣除token-2022转账手续费,vault实际收到的数量
+    let transfer_fee =
+        get_transfer_fee(&ctx.accounts.input_token_mint.to_account_info(), amount_in)?;
+    let actual_amount_in = amount_in.checked_sub(transfer_fee).unwrap();
+    require_gt!(actual_amount_in, 0);
+
+    //3.计算需兑换的部分及可mint的lp数量
+    let trade_fee_rate = pool_state.trade_fee_rate(&ctx.accounts.amm_config, block_timestamp)?;
+    let creator_share_rate =
+        pool_state.creator_share_rate(&ctx.accounts.amm_config, trade_fee_rate, 0)?;
+    let result = pool_state
+        .curve()?
+        .deposit_single_token(
+            u128::from(actual_amount_in),
+            u128::from(total_input_token_amount),
+            u128::from(total_output_token_amount),
+            u128::from(pool_state.lp_supply),
+            trade_direction,
+            trade_fee_rate,
+            ctx.accounts.amm_config.protocol_fee_rate,
+            ctx.accounts.amm_config.fund_fee_rate,
+            creator_share_rate,
+        )
+        .ok_or(ErrorCode::ZeroTradingTokens)?;
+
+    let lp_token_amount = u64::try_from(result.lp_token_amount).unwrap();
+    require_gte!(lp_token_amount, minimum_lp_out, ErrorCode::ExceededSlippage);
+
+    let protocol_fee = u64::try_from(result.swap_result.protocol_fee).unwrap();
+    let fund_fee = u64::try_from(result.swap_result.fund_fee).unwrap();
+    let creator_fee = u64::try_from(result.creator_fee).unwrap();
+    pool_state.accrue_fees(trade_direction, protocol_fee, fund_fee);
+    pool_state.add_creator_fee(trade_direction, creator_fee);
+
+    //4.把 user token 转到 vault账户
+    transfer_from_user_to_pool_vault(
+        ctx.accounts.owner.to_account_info(),
+        ctx.accounts.input_token_account.to_account_info(),
+        ctx.accounts.input_vault.to_account_info(),
+        ctx.accounts.input_token_mint.to_account_info(),
+        ctx.accounts.input_token_program.to_account_info(),
+        amount_in,
+        ctx.accounts.input_token_mint.decimals,
+    )?;
+
+    //5.更新pool_state并mint lp_tokens
+    let lp_amount_before = pool_state.lp_supply;
+    pool_state.lp_supply = pool_state.lp_supply.checked_add(lp_token_amount).unwrap();
+
+    token_mint_to(
+        ctx.accounts.authority.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        ctx.accounts.lp_mint.to_account_info(),
+        ctx.accounts.owner_lp_token.to_account_info(),
+        lp_token_amount,
+        &[&[AUTH_SEED.as_bytes(), &[pool_state.auth_bump]]],
+    )?;
+
+    ctx.accounts.observation_state.load_mut()?.update(
+        block_timestamp,
+        token_0_price_x32,
+        token_1_price_x32,
+    );
+    pool_state.recent_epoch = Clock::get()?.epoch;
+
+    let input_vault_after = total_input_token_amount
+        .checked_add(actual_amount_in)
+        .unwrap()
+        .checked_sub(protocol_fee + fund_fee + creator_fee)
+        .unwrap();
+    let (token_0_vault_after, token_1_vault_after, token_0_amount, token_1_amount) =
+        match trade_direction {
+            TradeDirection::ZeroForOne => {
+                (input_vault_after, total_token_1_amount, actual_amount_in, 0)
+            }
+            TradeDirection::OneForZero => {
+                (total_token_0_amount, input_vault_after, 0, actual_amount_in)
+            }
+        };
+    pool_state.set_reserves(token_0_vault_after, token_1_vault_after);
+    let (transfer_fee_0, transfer_fee_1) = match trade_direction {
+        TradeDirection::ZeroForOne => (transfer_fee, 0),
+        TradeDirection::OneForZero => (0, transfer_fee),
+    };
+
+    emit_event!(
+        ctx,
+        LpChanged {
+            pool_id: ctx.accounts.pool_state.key(),
+            lp_amount_before,
+            token_0_vault_before: total_token_0_amount,
+            token_1_vault_before: total_token_1_amount,
+            token_0_vault_after,
+            token_1_vault_after,
+            token_0_amount,
+            token_1_amount,
+            transfer_fee_0,
+            transfer_fee_1,
+            lp_amount: lp_token_amount,
+            change_type: LpChangeType::Deposit,
+        }
+    );
+    Ok(())
+}
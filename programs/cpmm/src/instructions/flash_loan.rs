@@ -0,0 +1,224 @@
+use std::ops::DerefMut;
+
+use anchor_lang::{
+    prelude::*,
+    solana_program::{instruction::Instruction, program::invoke},
+};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::{
+    curve::fees::Fees, emit_event, error::ErrorCode, pool, transfer_from_pool_vault_to_user,
+    AmmConfig, FlashLoanEvent, PoolState, AMM_CONFIG_SEED, POOL_SEED,
+};
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(index:u16)]
+pub struct FlashLoan<'info> {
+    /// The borrower, its signature is passed on to the receiver program
+    pub borrower: Signer<'info>,
+
+    /// CHECK: pool vault and lp mint authority
+    #[account(
+        seeds = [
+            crate::AUTH_SEED.as_bytes(),
+        ],
+        bump = pool_state.auth_bump,
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [
+            AMM_CONFIG_SEED.as_bytes(),
+            &index.to_be_bytes()
+        ],
+        bump = amm_config.bump
+    )]
+    pub amm_config: Box<Account<'info, AmmConfig>>,
+
+    /// Pool state account
+    #[account(
+        mut,
+        has_one = token_0_vault,
+        has_one = token_1_vault,
+        constraint = !pool_state.locked @ ErrorCode::PoolLocked,
+        seeds = [
+            POOL_SEED.as_bytes(),
+            amm_config.key().as_ref(),
+            vault_0_mint.key().as_ref(),
+            vault_1_mint.key().as_ref(),
+        ],
+        bump = pool_state.bump
+    )]
+    pub pool_state: Box<Account<'info, PoolState>>,
+
+    /// The address that holds pool tokens for token_0
+    #[account(mut)]
+    pub token_0_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The address that holds pool tokens for token_1
+    #[account(mut)]
+    pub token_1_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The token account receiving the borrowed token_0
+    #[account(
+        mut,
+        token::mint = vault_0_mint,
+        token::token_program = token_0_program,
+    )]
+    pub receiver_token_0: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The token account receiving the borrowed token_1
+    #[account(
+        mut,
+        token::mint = vault_1_mint,
+        token::token_program = token_1_program,
+    )]
+    pub receiver_token_1: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The mint of token_0 vault
+    #[account(
+        address = token_0_vault.mint
+    )]
+    pub vault_0_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The mint of token_1 vault
+    #[account(
+        address = token_1_vault.mint
+    )]
+    pub vault_1_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// token_0_program
+    pub token_0_program: Interface<'info, TokenInterface>,
+
+    /// token_1_program
+    pub token_1_program: Interface<'info, TokenInterface>,
+
+    /// CHECK: the program invoked with the loan, it must repay the vaults
+    #[account(
+        executable,
+        constraint = receiver_program.key() != crate::ID @ ErrorCode::InvalidInput
+    )]
+    pub receiver_program: UncheckedAccount<'info>,
+}
+
+pub fn process_flash_loan<'info>(
+    ctx: Context<'_, '_, '_, 'info, FlashLoan<'info>>,
+    amount_0: u64,
+    amount_1: u64,
+    data: Vec<u8>,
+) -> Result<()> {
+    let block_timestamp = Clock::get()?.unix_timestamp as u64;
+    let pool_state = ctx.accounts.pool_state.deref_mut();
+    //1.校验交易池状态及开始时间
+    if !pool_state.get_status_by_bit(pool::PoolStatusBitIndex::Swap)
+        || block_timestamp < pool_state.open_time
+    {
+        return err!(ErrorCode::NotApproved);
+    }
+    require!(amount_0 > 0 || amount_1 > 0, ErrorCode::InvalidInput);
+
+    //2.计算需归还的手续费,手续费留在vault中归lp所有
+    let flash_loan_fee_rate = ctx.accounts.amm_config.flash_loan_fee_rate;
+    let fee_0 =
+        u64::try_from(Fees::flash_loan_fee(u128::from(amount_0), flash_loan_fee_rate).unwrap())
+            .unwrap();
+    let fee_1 =
+        u64::try_from(Fees::flash_loan_fee(u128::from(amount_1), flash_loan_fee_rate).unwrap())
+            .unwrap();
+    let vault_0_before = ctx.accounts.token_0_vault.amount;
+    let vault_1_before = ctx.accounts.token_1_vault.amount;
+    pool_state.sync_legacy_reserves(vault_0_before, vault_1_before)?;
+
+    //3.锁定pool,并立即写入账户,避免回调中重入swap/deposit/withdraw
+    pool_state.locked = true;
+    ctx.accounts.pool_state.exit(&crate::ID)?;
+
+    //4.从vault 借出到接收账户
+    let auth_bump = ctx.accounts.pool_state.auth_bump;
+    transfer_from_pool_vault_to_user(
+        ctx.accounts.authority.to_account_info(),
+        ctx.accounts.token_0_vault.to_account_info(),
+        ctx.accounts.receiver_token_0.to_account_info(),
+        ctx.accounts.vault_0_mint.to_account_info(),
+        ctx.accounts.token_0_program.to_account_info(),
+        amount_0,
+        ctx.accounts.vault_0_mint.decimals,
+        &[&[crate::AUTH_SEED.as_bytes(), &[auth_bump]]],
+    )?;
+    transfer_from_pool_vault_to_user(
+        ctx.accounts.authority.to_account_info(),
+        ctx.accounts.token_1_vault.to_account_info(),
+        ctx.accounts.receiver_token_1.to_account_info(),
+        ctx.accounts.vault_1_mint.to_account_info(),
+        ctx.accounts.token_1_program.to_account_info(),
+        amount_1,
+        ctx.accounts.vault_1_mint.decimals,
+        &[&[crate::AUTH_SEED.as_bytes(), &[auth_bump]]],
+    )?;
+
+    //5.调用接收程序,remaining_accounts 原样传给接收程序
+    invoke_receiver(
+        ctx.accounts.receiver_program.to_account_info(),
+        ctx.remaining_accounts,
+        data,
+    )?;
+
+    //6.校验vault收回本金及手续费,手续费归lp所有,计入reserves;多还的部分可通过skim取出
+    ctx.accounts.token_0_vault.reload()?;
+    ctx.accounts.token_1_vault.reload()?;
+    ctx.accounts.pool_state.reload()?;
+    let pool_state = ctx.accounts.pool_state.deref_mut();
+    pool_state.settle_flash_loan(
+        (vault_0_before, vault_1_before),
+        (
+            ctx.accounts.token_0_vault.amount,
+            ctx.accounts.token_1_vault.amount,
+        ),
+        fee_0,
+        fee_1,
+    )?;
+    pool_state.recent_epoch = Clock::get()?.epoch;
+
+    emit_event!(
+        ctx,
+        FlashLoanEvent {
+            pool_id: ctx.accounts.pool_state.key(),
+            receiver_program: ctx.accounts.receiver_program.key(),
+            amount_0,
+            amount_1,
+            fee_0,
+            fee_1,
+        }
+    );
+    Ok(())
+}
+
+/// Invoke the receiver program of a flash loan or flash swap with the given
+/// instruction data, passing on the remaining accounts of the instruction.
+pub fn invoke_receiver<'info>(
+    receiver_program: AccountInfo<'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    data: Vec<u8>,
+) -> Result<()> {
+    let accounts = remaining_accounts
+        .iter()
+        .map(|account| AccountMeta {
+            pubkey: account.key(),
+            is_signer: account.is_signer,
+            is_writable: account.is_writable,
+        })
+        .collect();
+    let program_id = receiver_program.key();
+    let mut account_infos = remaining_accounts.to_vec();
+    account_infos.push(receiver_program);
+    invoke(
+        &Instruction {
+            program_id,
+            accounts,
+            data,
+        },
+        &account_infos,
+    )?;
+    Ok(())
+}
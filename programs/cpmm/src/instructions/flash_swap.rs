@@ -0,0 +1,263 @@
+use std::ops::DerefMut;
+
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::{
+    check_deadline, curve::fees::Fees, emit_event, error::ErrorCode, get_transfer_fee,
+    get_transfer_inverse_fee, invoke_receiver, pool, sorted_mints,
+    transfer_from_pool_vault_to_user, AmmConfig, ObservationState, PoolState, SwapEvent,
+    TradeDirection, AMM_CONFIG_SEED, POOL_SEED, POOL_VAULT_SEED,
+};
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(index:u16)]
+pub struct FlashSwap<'info> {
+    /// The caller, its signature is passed on to the receiver program
+    pub payer: Signer<'info>,
+
+    /// CHECK: pool vault and lp mint authority
+    #[account(
+        seeds = [
+            crate::AUTH_SEED.as_bytes(),
+        ],
+        bump = pool_state.auth_bump,
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [
+            AMM_CONFIG_SEED.as_bytes(),
+            &index.to_be_bytes()
+        ],
+        bump = amm_config.bump
+    )]
+    pub amm_config: Box<Account<'info, AmmConfig>>,
+
+    /// Pool state account, derived from the sorted mint pair
+    #[account(
+        mut,
+        constraint = !pool_state.locked @ ErrorCode::PoolLocked,
+        seeds = [
+            POOL_SEED.as_bytes(),
+            amm_config.key().as_ref(),
+            sorted_mints(input_token_mint.key(), output_token_mint.key()).0.as_ref(),
+            sorted_mints(input_token_mint.key(), output_token_mint.key()).1.as_ref(),
+        ],
+        bump = pool_state.bump
+    )]
+    pub pool_state: Box<Account<'info, PoolState>>,
+
+    /// The token account receiving the output token before it is paid for
+    #[account(
+        mut,
+        token::mint = output_token_mint,
+        token::token_program = output_token_program,
+    )]
+    pub output_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The vault the receiver program must pay the input token into
+    #[account(
+        mut,
+        constraint = input_vault.key() == pool_state.token_0_vault.key() || input_vault.key() == pool_state.token_1_vault.key(),
+        seeds = [
+            POOL_VAULT_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            input_token_mint.key().as_ref()
+        ],
+        bump,
+    )]
+    pub input_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = output_vault.key() == pool_state.token_0_vault.key() || output_vault.key() == pool_state.token_1_vault.key(),
+        seeds = [
+            POOL_VAULT_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            output_token_mint.key().as_ref()
+        ],
+        bump,
+    )]
+    pub output_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub output_token_program: Interface<'info, TokenInterface>,
+
+    pub input_token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    pub output_token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The program account for the most recent oracle observation
+    #[account(mut, address = pool_state.observation_key)]
+    pub observation_state: AccountLoader<'info, ObservationState>,
+
+    /// CHECK: the program invoked with the output token, it must pay the input vault
+    #[account(
+        executable,
+        constraint = receiver_program.key() != crate::ID @ ErrorCode::InvalidInput
+    )]
+    pub receiver_program: UncheckedAccount<'info>,
+}
+
+pub fn process_flash_swap<'info>(
+    ctx: Context<'_, '_, '_, 'info, FlashSwap<'info>>,
+    amount_out: u64,
+    max_amount_in: u64,
+    deadline: Option<i64>,
+    data: Vec<u8>,
+) -> Result<()> {
+    check_deadline(deadline)?;
+    let block_timestamp = Clock::get()?.unix_timestamp as u64;
+    let pool_state = ctx.accounts.pool_state.deref_mut();
+    //校验交易池状态及开始时间
+    if !pool_state.get_status_by_bit(pool::PoolStatusBitIndex::Swap)
+        || block_timestamp < pool_state.open_time
+    {
+        return err!(ErrorCode::NotApproved);
+    }
+
+    require_gt!(amount_out, 0);
+
+    //1.根据vault确定交易方向,获取两个token vault可交易token及计算价格
+    let trade_direction = pool_state.trade_direction(
+        ctx.accounts.input_vault.key(),
+        ctx.accounts.output_vault.key(),
+    )?;
+    pool_state.sync_legacy_trade_reserves(
+        trade_direction,
+        ctx.accounts.input_vault.amount,
+        ctx.accounts.output_vault.amount,
+    )?;
+    let (total_token_0_amount, total_token_1_amount) = pool_state.reserves();
+    let (total_input_token_amount, total_output_token_amount) = match trade_direction {
+        TradeDirection::ZeroForOne => (total_token_0_amount, total_token_1_amount),
+        TradeDirection::OneForZero => (total_token_1_amount, total_token_0_amount),
+    };
+    require_gt!(total_output_token_amount, amount_out);
+    let (token_0_price_x32, token_1_price_x32) =
+        pool_state.token_price_x32(total_token_0_amount, total_token_1_amount);
+
+    let curve = pool_state.curve()?;
+    let constant_before = curve
+        .invariant(
+            u128::from(total_token_0_amount),
+            u128::from(total_token_1_amount),
+        )
+        .unwrap();
+
+    let input_vault_before = ctx.accounts.input_vault.amount;
+
+    //2.锁定pool,并立即写入账户,避免回调中重入swap/deposit/withdraw
+    pool_state.locked = true;
+    ctx.accounts.pool_state.exit(&crate::ID)?;
+
+    //3.先把output token转给接收账户
+    let auth_bump = ctx.accounts.pool_state.auth_bump;
+    transfer_from_pool_vault_to_user(
+        ctx.accounts.authority.to_account_info(),
+        ctx.accounts.output_vault.to_account_info(),
+        ctx.accounts.output_token_account.to_account_info(),
+        ctx.accounts.output_token_mint.to_account_info(),
+        ctx.accounts.output_token_program.to_account_info(),
+        amount_out,
+        ctx.accounts.output_token_mint.decimals,
+        &[&[crate::AUTH_SEED.as_bytes(), &[auth_bump]]],
+    )?;
+
+    //4.调用接收程序,由接收程序把input token转入input_vault
+    invoke_receiver(
+        ctx.accounts.receiver_program.to_account_info(),
+        ctx.remaining_accounts,
+        data,
+    )?;
+
+    //5.重新读取vault,按收到的input token收取手续费并校验不变量
+    ctx.accounts.input_vault.reload()?;
+    ctx.accounts.pool_state.reload()?;
+    let pool_state = ctx.accounts.pool_state.deref_mut();
+    let amount_in = ctx
+        .accounts
+        .input_vault
+        .amount
+        .checked_sub(input_vault_before)
+        .ok_or(ErrorCode::ZeroTradingTokens)?;
+    require_gt!(amount_in, 0);
+    require_gte!(max_amount_in, amount_in, ErrorCode::ExceededSlippage);
+    let new_input_token_amount = total_input_token_amount.checked_add(amount_in).unwrap();
+    let new_output_token_amount = total_output_token_amount.checked_sub(amount_out).unwrap();
+
+    let trade_fee_rate = pool_state.trade_fee_rate(&ctx.accounts.amm_config, block_timestamp)?;
+    let trade_fee = Fees::trading_fee(u128::from(amount_in), trade_fee_rate).unwrap();
+    let protocol_fee =
+        Fees::protocol_fee(trade_fee, ctx.accounts.amm_config.protocol_fee_rate).unwrap();
+    let fund_fee = Fees::fund_fee(trade_fee, ctx.accounts.amm_config.fund_fee_rate).unwrap();
+
+    let new_swap_source_amount = u128::from(new_input_token_amount)
+        .checked_sub(trade_fee)
+        .unwrap();
+    let (new_token_0_amount, new_token_1_amount) = match trade_direction {
+        TradeDirection::ZeroForOne => (new_swap_source_amount, u128::from(new_output_token_amount)),
+        TradeDirection::OneForZero => (u128::from(new_output_token_amount), new_swap_source_amount),
+    };
+    let constant_after = curve
+        .invariant(new_token_0_amount, new_token_1_amount)
+        .unwrap();
+
+    require_gte!(constant_after, constant_before);
+
+    let trade_fee = u64::try_from(trade_fee).unwrap();
+    let protocol_fee = u64::try_from(protocol_fee).unwrap();
+    let fund_fee = u64::try_from(fund_fee).unwrap();
+    pool_state.accrue_fees(trade_direction, protocol_fee, fund_fee);
+    let creator_share_rate =
+        pool_state.creator_share_rate(&ctx.accounts.amm_config, trade_fee_rate, 0)?;
+    let creator_fee = pool_state.accrue_creator_fee(
+        trade_direction,
+        u128::from(trade_fee),
+        creator_share_rate,
+    )?;
+    let input_vault_after = new_input_token_amount
+        .checked_sub(protocol_fee + fund_fee + creator_fee)
+        .unwrap();
+    pool_state.set_trade_reserves(trade_direction, input_vault_after, new_output_token_amount);
+    pool_state.locked = false;
+
+    ctx.accounts.observation_state.load_mut()?.update(
+        block_timestamp,
+        token_0_price_x32,
+        token_1_price_x32,
+    );
+    pool_state.recent_epoch = Clock::get()?.epoch;
+
+    //vault收到的是扣除transfer fee后的数量,按其反推付款方支付的transfer fee
+    let input_transfer_fee =
+        get_transfer_inverse_fee(&ctx.accounts.input_token_mint.to_account_info(), amount_in)?;
+    let output_transfer_fee = get_transfer_fee(
+        &ctx.accounts.output_token_mint.to_account_info(),
+        amount_out,
+    )?;
+
+    emit_event!(
+        ctx,
+        SwapEvent {
+            pool_id: ctx.accounts.pool_state.key(),
+            trade_direction,
+            input_vault_before: total_input_token_amount,
+            output_vault_before: total_output_token_amount,
+            input_vault_after,
+            output_vault_after: new_output_token_amount,
+            input_amount: amount_in,
+            output_amount: amount_out,
+            input_transfer_fee,
+            output_transfer_fee,
+            trade_fee,
+            protocol_fee,
+            fund_fee,
+            referral_fee: 0,
+            creator_fee,
+            base_input: false,
+        }
+    );
+    Ok(())
+}
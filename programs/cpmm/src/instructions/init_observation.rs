@@ -0,0 +1,49 @@
+use anchor_lang::prelude::*;
+
+use crate::{emit_event, ObservationInitialized, ObservationState, PoolState, OBSERVATION_SEED};
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct InitObservation<'info> {
+    /// Pays to create the observation account
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Pool state account created before observations were recorded
+    #[account(mut)]
+    pub pool_state: Box<Account<'info, PoolState>>,
+
+    /// an account to store oracle observations
+    #[account(
+        init,
+        seeds = [
+            OBSERVATION_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+        ],
+        bump,
+        payer = payer,
+        space = ObservationState::LEN
+    )]
+    pub observation_state: AccountLoader<'info, ObservationState>,
+
+    /// To create a new program account
+    pub system_program: Program<'info, System>,
+}
+
+pub fn process_init_observation(ctx: Context<InitObservation>) -> Result<()> {
+    let pool_id = ctx.accounts.pool_state.key();
+    let mut observation_state = ctx.accounts.observation_state.load_init()?;
+    observation_state.pool_id = pool_id;
+    ctx.accounts
+        .pool_state
+        .set_observation_key(ctx.accounts.observation_state.key())?;
+
+    emit_event!(
+        ctx,
+        ObservationInitialized {
+            pool_id,
+            observation_state: ctx.accounts.observation_state.key(),
+        }
+    );
+    Ok(())
+}
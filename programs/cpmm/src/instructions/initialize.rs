@@ -1,5 +1,6 @@
 use crate::constants;
-use crate::curve::CurveCalculator;
+use crate::emit_event;
+use crate::curve::CurveType;
 use crate::error::ErrorCode;
 use crate::state::*;
 use crate::utils::*;
@@ -7,6 +8,7 @@ use anchor_lang::{
     accounts::interface_account::InterfaceAccount,
     prelude::*,
     solana_program::clock,
+    system_program,
 };
 use anchor_spl::token::Token;
 use anchor_spl::{
@@ -15,6 +17,7 @@ use anchor_spl::{
 };
 use std::ops::DerefMut;
 
+#[cfg_attr(feature = "event-cpi", event_cpi)]
 #[derive(Accounts)]
 #[instruction(index:u16)]
 pub struct Initialize<'info> {
@@ -33,6 +36,13 @@ pub struct Initialize<'info> {
     )]
     pub amm_config: Box<Account<'info, AmmConfig>>,
 
+    /// CHECK: receives the pool creation fee of the config
+    #[account(
+        mut,
+        address = amm_config.create_pool_fee_receiver @ ErrorCode::InvalidInput,
+    )]
+    pub create_pool_fee_receiver: UncheckedAccount<'info>,
+
     /// CHECK: pool vault and lp mint authority
     #[account(
         seeds = [
@@ -113,6 +123,21 @@ pub struct Initialize<'info> {
     )]
     pub creator_lp_token: Box<InterfaceAccount<'info, TokenAccount>>,
 
+    /// Holds the lp locked at creation, no instruction moves tokens out of it
+    #[account(
+        init,
+        payer = creator,
+        token::mint = lp_mint,
+        token::authority = authority,
+        token::token_program = token_program,
+        seeds = [
+            POOL_LP_LOCK_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub lock_lp_token: Box<InterfaceAccount<'info, TokenAccount>>,
+
     /// CHECK: Token_0 vault for the pool, created by contract
     #[account(
         init,
@@ -145,6 +170,19 @@ pub struct Initialize<'info> {
     )]
     pub token_1_vault: Box<InterfaceAccount<'info, TokenAccount>>,
 
+    /// an account to store oracle observations
+    #[account(
+        init,
+        seeds = [
+            OBSERVATION_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+        ],
+        bump,
+        payer = creator,
+        space = ObservationState::LEN
+    )]
+    pub observation_state: AccountLoader<'info, ObservationState>,
+
     /// Program to create mint account and mint tokens
     pub token_program: Program<'info, Token>,
     pub token_0_program: Interface<'info, TokenInterface>,
@@ -157,11 +195,16 @@ pub struct Initialize<'info> {
     pub rent: Sysvar<'info, Rent>, */
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn process_initialize(
     ctx: Context<Initialize>,
     init_amount_0: u64,
     init_amount_1: u64,
     mut open_time: u64,
+    curve_type: u8,
+    curve_params: [u64; 2],
+    creator_fee_rate: u64,
+    launch_fee: Option<LaunchFeeParams>,
 ) -> Result<()> {
     //1.判断mint是否被支持
     if !(is_supported_mint(&ctx.accounts.token_0_mint).unwrap()
@@ -173,11 +216,35 @@ pub fn process_initialize(
     if ctx.accounts.amm_config.disable_create_pool {
         return err!(ErrorCode::NotApproved);
     }
+    require_gte!(
+        ctx.accounts.amm_config.max_creator_fee_rate,
+        creator_fee_rate,
+        ErrorCode::CreatorFeeRateTooHigh
+    );
+    let launch_fee = launch_fee.unwrap_or_default();
+    launch_fee.validate(&ctx.accounts.amm_config)?;
+
+    //收取创建pool的费用
+    let create_pool_fee = ctx.accounts.amm_config.create_pool_fee;
+    if create_pool_fee > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.creator.to_account_info(),
+                    to: ctx.accounts.create_pool_fee_receiver.to_account_info(),
+                },
+            ),
+            create_pool_fee,
+        )?;
+    }
+    let curve = CurveType::from_u8(curve_type)?.calculator(curve_params)?;
     let block_timestamp = clock::Clock::get()?.unix_timestamp as u64;
     if open_time <= block_timestamp {
         open_time = block_timestamp + 1;
     }
 
+    let pool_id = ctx.accounts.pool_state.key();
     let pool_state = ctx.accounts.pool_state.deref_mut();
 
     //2.user account transfer to vault account
@@ -204,7 +271,7 @@ pub fn process_initialize(
     ctx.accounts.token_0_vault.reload()?;
     ctx.accounts.token_1_vault.reload()?;
 
-    CurveCalculator::validate_supply(ctx.accounts.token_0_vault.amount, ctx.accounts.token_1_vault.amount)?;
+    curve.validate_supply(ctx.accounts.token_0_vault.amount, ctx.accounts.token_1_vault.amount)?;
 
     //3.计算liquidity,并mint_to user_lp_token_amount
     let liquidity = U128::from(ctx.accounts.token_0_vault.amount)
@@ -212,7 +279,10 @@ pub fn process_initialize(
         .unwrap()
         .integer_sqrt()
         .as_u64();
-    let lock_lp_amount = 100;
+    let lock_lp_amount = ctx.accounts.amm_config.lock_lp_amount.max(min_lock_lp_amount(
+        ctx.accounts.token_0_mint.decimals,
+        ctx.accounts.token_1_mint.decimals,
+    ));
     msg!(
         "liquidity:{}, lock_lp_amount:{}, vault_0_amount:{},vault_1_amount:{}",
         liquidity,
@@ -230,8 +300,19 @@ pub fn process_initialize(
             .ok_or(ErrorCode::InitLpAmountTooLess)?,
         &[&[crate::AUTH_SEED.as_bytes(), &[ctx.bumps.authority]]],
     )?;
+    token::token_mint_to(
+        ctx.accounts.authority.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        ctx.accounts.lp_mint.to_account_info(),
+        ctx.accounts.lock_lp_token.to_account_info(),
+        lock_lp_amount,
+        &[&[crate::AUTH_SEED.as_bytes(), &[ctx.bumps.authority]]],
+    )?;
+
+    //4.初始化pool_state及observation_state账户
+    let mut observation_state = ctx.accounts.observation_state.load_init()?;
+    observation_state.pool_id = pool_id;
 
-    //4.初始化pool_state账户
     pool_state.initialize(
         ctx.bumps.authority,
         liquidity,
@@ -243,7 +324,67 @@ pub fn process_initialize(
         &ctx.accounts.token_0_mint,
         &ctx.accounts.token_1_mint,
         &ctx.accounts.lp_mint,
+        ctx.accounts.observation_state.key(),
+        curve_type,
+        curve_params,
+        creator_fee_rate,
+        launch_fee,
         ctx.bumps.pool_state,
-    ); 
+    );
+    pool_state.set_reserves(
+        ctx.accounts.token_0_vault.amount,
+        ctx.accounts.token_1_vault.amount,
+    );
+
+    emit_event!(
+        ctx,
+        PoolCreated {
+            pool_id,
+            amm_config: ctx.accounts.amm_config.key(),
+            pool_creator: ctx.accounts.creator.key(),
+            token_0_mint: ctx.accounts.token_0_mint.key(),
+            token_1_mint: ctx.accounts.token_1_mint.key(),
+            lp_mint: ctx.accounts.lp_mint.key(),
+            token_0_amount: ctx.accounts.token_0_vault.amount,
+            token_1_amount: ctx.accounts.token_1_vault.amount,
+            lp_supply: liquidity,
+            open_time,
+        }
+    );
     Ok(())
 }
+
+/// The least lp amount to lock. The lp minted for the same value grows with the
+/// decimals of both tokens, so a fixed amount barely protects high decimal pools.
+/// Capped at `MAX_LOCK_LP_AMOUNT`, the most a config can lock.
+pub fn min_lock_lp_amount(mint_0_decimals: u8, mint_1_decimals: u8) -> u64 {
+    10u64
+        .checked_pow((u32::from(mint_0_decimals) + u32::from(mint_1_decimals)) / 4)
+        .map_or(MAX_LOCK_LP_AMOUNT, |amount| amount.min(MAX_LOCK_LP_AMOUNT))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn min_lock_lp_amount_grows_with_the_decimals() {
+        assert_eq!(min_lock_lp_amount(0, 0), 1);
+        assert_eq!(min_lock_lp_amount(6, 6), 1_000);
+        assert_eq!(min_lock_lp_amount(9, 6), 1_000);
+        assert_eq!(min_lock_lp_amount(9, 9), 10_000);
+        assert_eq!(min_lock_lp_amount(18, 18), MAX_LOCK_LP_AMOUNT);
+    }
+
+    #[test]
+    fn min_lock_lp_amount_is_capped_by_the_config_limit() {
+        assert_eq!(min_lock_lp_amount(18, 21), MAX_LOCK_LP_AMOUNT);
+        assert_eq!(min_lock_lp_amount(18, 22), MAX_LOCK_LP_AMOUNT);
+        assert_eq!(min_lock_lp_amount(u8::MAX, u8::MAX), MAX_LOCK_LP_AMOUNT);
+        // a config locking the most is never overridden
+        assert_eq!(
+            MAX_LOCK_LP_AMOUNT.max(min_lock_lp_amount(18, 22)),
+            MAX_LOCK_LP_AMOUNT
+        );
+    }
+}
@@ -7,11 +7,44 @@ pub use initialize::*;
 pub mod deposit;
 pub use deposit::*;
 
+pub mod deposit_single_token;
+pub use deposit_single_token::*;
+
+pub mod deposit_by_token_amounts;
+pub use deposit_by_token_amounts::*;
+
 pub mod withdraw;
 pub use withdraw::*;
 
+pub mod withdraw_single_token;
+pub use withdraw_single_token::*;
+
 pub mod swap_base_input;
 pub use swap_base_input::*;
 
 pub mod swap_base_output;
 pub use swap_base_output::*;
+
+pub mod swap_to_recipient;
+pub use swap_to_recipient::*;
+
+pub mod swap_route;
+pub use swap_route::*;
+
+pub mod flash_loan;
+pub use flash_loan::*;
+
+pub mod flash_swap;
+pub use flash_swap::*;
+
+pub mod sync;
+pub use sync::*;
+
+pub mod skim;
+pub use skim::*;
+
+pub mod init_observation;
+pub use init_observation::*;
+
+pub mod collect_fee;
+pub use collect_fee::*;
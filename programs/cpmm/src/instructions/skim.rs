@@ -0,0 +1,116 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::{emit_event, error::ErrorCode, transfer_from_pool_vault_to_user, PoolState, Skimmed};
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct Skim<'info> {
+    /// CHECK: pool vault and lp mint authority
+    #[account(
+        seeds = [
+            crate::AUTH_SEED.as_bytes(),
+        ],
+        bump = pool_state.auth_bump,
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    /// Pool state account
+    #[account(
+        mut,
+        has_one = token_0_vault @ ErrorCode::InvalidVault,
+        has_one = token_1_vault @ ErrorCode::InvalidVault,
+        constraint = !pool_state.locked @ ErrorCode::PoolLocked,
+    )]
+    pub pool_state: Box<Account<'info, PoolState>>,
+
+    /// The address that holds pool tokens for token_0
+    #[account(mut)]
+    pub token_0_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The address that holds pool tokens for token_1
+    #[account(mut)]
+    pub token_1_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The mint of token_0 vault
+    #[account(
+        address = token_0_vault.mint
+    )]
+    pub vault_0_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The mint of token_1 vault
+    #[account(
+        address = token_1_vault.mint
+    )]
+    pub vault_1_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The address that receives the excess token_0
+    #[account(
+        mut,
+        token::mint = vault_0_mint,
+        token::token_program = token_0_program,
+    )]
+    pub recipient_token_0_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The address that receives the excess token_1
+    #[account(
+        mut,
+        token::mint = vault_1_mint,
+        token::token_program = token_1_program,
+    )]
+    pub recipient_token_1_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// token_0_program
+    pub token_0_program: Interface<'info, TokenInterface>,
+
+    /// token_1_program
+    pub token_1_program: Interface<'info, TokenInterface>,
+}
+
+pub fn process_skim(ctx: Context<Skim>) -> Result<()> {
+    let pool_state = &mut ctx.accounts.pool_state;
+
+    //1.vault中超出reserves及protocol和fund手续费的部分,旧pool先同步reserves
+    pool_state.sync_legacy_reserves(
+        ctx.accounts.token_0_vault.amount,
+        ctx.accounts.token_1_vault.amount,
+    )?;
+    let (amount_0, amount_1) = pool_state.skim_amounts(
+        ctx.accounts.token_0_vault.amount,
+        ctx.accounts.token_1_vault.amount,
+    )?;
+
+    //2.从vault 转账到recipient token account
+    let auth_bump = pool_state.auth_bump;
+    transfer_from_pool_vault_to_user(
+        ctx.accounts.authority.to_account_info(),
+        ctx.accounts.token_0_vault.to_account_info(),
+        ctx.accounts.recipient_token_0_account.to_account_info(),
+        ctx.accounts.vault_0_mint.to_account_info(),
+        ctx.accounts.token_0_program.to_account_info(),
+        amount_0,
+        ctx.accounts.vault_0_mint.decimals,
+        &[&[crate::AUTH_SEED.as_bytes(), &[auth_bump]]],
+    )?;
+
+    transfer_from_pool_vault_to_user(
+        ctx.accounts.authority.to_account_info(),
+        ctx.accounts.token_1_vault.to_account_info(),
+        ctx.accounts.recipient_token_1_account.to_account_info(),
+        ctx.accounts.vault_1_mint.to_account_info(),
+        ctx.accounts.token_1_program.to_account_info(),
+        amount_1,
+        ctx.accounts.vault_1_mint.decimals,
+        &[&[crate::AUTH_SEED.as_bytes(), &[auth_bump]]],
+    )?;
+
+    emit_event!(
+        ctx,
+        Skimmed {
+            pool_id: ctx.accounts.pool_state.key(),
+            amount_0,
+            amount_1,
+        }
+    );
+    Ok(())
+}
@@ -1,5 +1,3 @@
-use std::ops::DerefMut;
-
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
@@ -7,11 +5,13 @@ use anchor_spl::{
 };
 
 use crate::{
-    error::ErrorCode, pool, transfer_from_pool_vault_to_user, transfer_from_user_to_pool_vault,
-    AmmConfig, CurveCalculator, PoolState, TradeDirection, AMM_CONFIG_SEED, POOL_SEED,
+    check_deadline, curve::fees::Fees, emit_event, error::ErrorCode, get_transfer_fee, pool,
+    sorted_mints, transfer_from_pool_vault_to_user, transfer_from_user_to_pool_vault, AmmConfig,
+    ObservationState, PoolState, SwapEvent, TradeDirection, AMM_CONFIG_SEED, POOL_SEED,
     POOL_VAULT_SEED,
 };
 
+#[cfg_attr(feature = "event-cpi", event_cpi)]
 #[derive(Accounts)]
 #[instruction(index:u16)]
 pub struct Swap<'info> {
@@ -37,19 +37,21 @@ pub struct Swap<'info> {
     )]
     pub amm_config: Box<Account<'info, AmmConfig>>,
 
-    /// Pool state account
+    /// Pool state account, derived from the sorted mint pair so that both
+    /// trade directions resolve the pool created by `initialize`
     #[account(
         mut,
         // has_one = lp_mint ,
         // has_one = token_0_vault,
         // has_one = token_1_vault,
+        constraint = !pool_state.locked @ ErrorCode::PoolLocked,
         seeds = [
             POOL_SEED.as_bytes(),
             amm_config.key().as_ref(),
-            input_token_mint.key().as_ref(),
-            output_token_mint.key().as_ref(),
+            sorted_mints(input_token_mint.key(), output_token_mint.key()).0.as_ref(),
+            sorted_mints(input_token_mint.key(), output_token_mint.key()).1.as_ref(),
         ],
-        bump
+        bump = pool_state.bump
     )]
     pub pool_state: Account<'info, PoolState>,
 
@@ -110,18 +112,127 @@ pub struct Swap<'info> {
     )]
     pub output_token_mint: Box<InterfaceAccount<'info, Mint>>,
 
+    /// The program account for the most recent oracle observation
+    #[account(mut, address = pool_state.observation_key)]
+    pub observation_state: AccountLoader<'info, ObservationState>,
+
+    /// Optional token account of the input mint receiving the referral fee.
+    /// Any account is accepted, so the referral fee is a rebate a payer can
+    /// take by referring their own swaps.
+    #[account(
+        mut,
+        token::mint = input_token_mint,
+        token::token_program = input_token_program,
+    )]
+    pub referrer_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
     pub associated_token_program: Program<'info, AssociatedToken>,
 
     pub system_program: Program<'info, System>,
 }
 
+/// The accounts a swap reads and writes, shared by `Swap` and `SwapToRecipient`
+pub struct SwapAccounts<'a, 'info> {
+    pub payer: &'a Signer<'info>,
+    pub authority: &'a UncheckedAccount<'info>,
+    pub amm_config: &'a AmmConfig,
+    pub pool_id: Pubkey,
+    pub pool_state: &'a mut PoolState,
+    pub input_token_account: &'a InterfaceAccount<'info, TokenAccount>,
+    pub output_token_account: &'a InterfaceAccount<'info, TokenAccount>,
+    pub input_vault: &'a InterfaceAccount<'info, TokenAccount>,
+    pub output_vault: &'a InterfaceAccount<'info, TokenAccount>,
+    pub input_token_program: &'a Interface<'info, TokenInterface>,
+    pub output_token_program: &'a Interface<'info, TokenInterface>,
+    pub input_token_mint: &'a InterfaceAccount<'info, Mint>,
+    pub output_token_mint: &'a InterfaceAccount<'info, Mint>,
+    pub observation_state: &'a AccountLoader<'info, ObservationState>,
+    pub referrer_token_account: Option<&'a InterfaceAccount<'info, TokenAccount>>,
+}
+
+impl SwapAccounts<'_, '_> {
+    /// The referral fee rate the swap pays, zero without a referrer
+    pub fn referral_fee_rate(&self) -> u64 {
+        match self.referrer_token_account {
+            Some(_) => self.amm_config.referral_fee_rate,
+            None => 0,
+        }
+    }
+}
+
+/// The referrer's share of `trade_fee` at the rate the swap pays
+pub fn referral_fee(trade_fee: u128, referral_fee_rate: u64) -> Result<u64> {
+    let referral_fee =
+        Fees::referral_fee(trade_fee, referral_fee_rate).ok_or(ErrorCode::MathOverflow)?;
+    Ok(u64::try_from(referral_fee).map_err(|_| ErrorCode::MathOverflow)?)
+}
+
+/// Pay the referrer's share of `trade_fee` out of the input vault, returns the share paid
+pub fn pay_referral_fee(accounts: &SwapAccounts, trade_fee: u128, auth_bump: u8) -> Result<u64> {
+    let referrer_token_account = match accounts.referrer_token_account {
+        Some(referrer_token_account) => referrer_token_account,
+        None => return Ok(0),
+    };
+    let referral_fee = referral_fee(trade_fee, accounts.referral_fee_rate())?;
+    if referral_fee > 0 {
+        transfer_from_pool_vault_to_user(
+            accounts.authority.to_account_info(),
+            accounts.input_vault.to_account_info(),
+            referrer_token_account.to_account_info(),
+            accounts.input_token_mint.to_account_info(),
+            accounts.input_token_program.to_account_info(),
+            referral_fee,
+            accounts.input_token_mint.decimals,
+            &[&[crate::AUTH_SEED.as_bytes(), &[auth_bump]]],
+        )?;
+    }
+    Ok(referral_fee)
+}
+
+impl<'info> Swap<'info> {
+    pub fn swap_accounts(&mut self) -> SwapAccounts<'_, 'info> {
+        SwapAccounts {
+            payer: &self.payer,
+            authority: &self.authority,
+            amm_config: &self.amm_config,
+            pool_id: self.pool_state.key(),
+            pool_state: &mut self.pool_state,
+            input_token_account: &self.input_token_account,
+            output_token_account: &self.output_token_account,
+            input_vault: &self.input_vault,
+            output_vault: &self.output_vault,
+            input_token_program: &self.input_token_program,
+            output_token_program: &self.output_token_program,
+            input_token_mint: &self.input_token_mint,
+            output_token_mint: &self.output_token_mint,
+            observation_state: &self.observation_state,
+            referrer_token_account: self.referrer_token_account.as_deref(),
+        }
+    }
+}
+
 pub fn process_swap_base_input(
     ctx: Context<Swap>,
     amount_in: u64,
     minimum_amount_out: u64,
+    deadline: Option<i64>,
 ) -> Result<()> {
+    check_deadline(deadline)?;
+    let event =
+        execute_swap_base_input(ctx.accounts.swap_accounts(), amount_in, minimum_amount_out)?;
+    emit_event!(ctx, event);
+    Ok(())
+}
+
+/// Swap an exact input amount, returning the event to emit
+pub fn execute_swap_base_input(
+    accounts: SwapAccounts,
+    amount_in: u64,
+    minimum_amount_out: u64,
+) -> Result<SwapEvent> {
     let block_timestamp = Clock::get()?.unix_timestamp as u64;
-    let pool_state = ctx.accounts.pool_state.deref_mut();
+    let referral_fee_rate = accounts.referral_fee_rate();
+    let pool_state = &mut *accounts.pool_state;
     //校验交易池状态及开始时间
     if !pool_state.get_status_by_bit(pool::PoolStatusBitIndex::Swap)
         || block_timestamp < pool_state.open_time
@@ -131,100 +242,172 @@ pub fn process_swap_base_input(
 
     require_gt!(amount_in, 0);
 
-    //1.获取两个token vault可交易token及计算价格
-    let (
-        _trade_direction,
-        total_input_token_amount,
-        total_output_token_amount,
-        _token_0_price_x64,
-        _token_1_price_x64,
-    ) = if ctx.accounts.input_vault.key() == pool_state.token_0_vault
-        && ctx.accounts.output_vault.key() == pool_state.token_1_vault
-    {
-        let (token_0_price_x64, token_1_price_x64) = pool_state.token_price_x32(
-            ctx.accounts.input_vault.amount,
-            ctx.accounts.output_vault.amount,
-        );
-
-        (
-            TradeDirection::ZeroForOne,
-            ctx.accounts.input_vault.amount,
-            ctx.accounts.output_vault.amount,
-            token_0_price_x64,
-            token_1_price_x64,
-        )
-    } else if ctx.accounts.input_vault.key() == pool_state.token_1_vault
-        && ctx.accounts.output_vault.key() == pool_state.token_0_vault
-    {
-        let (token_0_price_x64, token_1_price_x64) = pool_state.token_price_x32(
-            ctx.accounts.output_vault.amount,
-            ctx.accounts.input_vault.amount,
-        );
-
-        (
-            TradeDirection::OneForZero,
-            ctx.accounts.input_vault.amount,
-            ctx.accounts.output_vault.amount,
-            token_0_price_x64,
-            token_1_price_x64,
-        )
-    } else {
-        return err!(ErrorCode::InvalidVault);
+    //1.根据vault确定交易方向,获取两个token vault可交易token及计算价格
+    let trade_direction =
+        pool_state.trade_direction(accounts.input_vault.key(), accounts.output_vault.key())?;
+    pool_state.sync_legacy_trade_reserves(
+        trade_direction,
+        accounts.input_vault.amount,
+        accounts.output_vault.amount,
+    )?;
+    let (total_token_0_amount, total_token_1_amount) = pool_state.reserves();
+    let (total_input_token_amount, total_output_token_amount) = match trade_direction {
+        TradeDirection::ZeroForOne => (total_token_0_amount, total_token_1_amount),
+        TradeDirection::OneForZero => (total_token_1_amount, total_token_0_amount),
     };
+    let (token_0_price_x32, token_1_price_x32) =
+        pool_state.token_price_x32(total_token_0_amount, total_token_1_amount);
 
-    let constant_before = u128::from(total_input_token_amount)
-        .checked_mul(u128::from(total_output_token_amount))
+    //扣除token-2022转账手续费,vault实际收到的数量
+    let input_transfer_fee =
+        get_transfer_fee(&accounts.input_token_mint.to_account_info(), amount_in)?;
+    let actual_amount_in = amount_in.checked_sub(input_transfer_fee).unwrap();
+    require_gt!(actual_amount_in, 0);
+
+    let curve = pool_state.curve()?;
+    let constant_before = curve
+        .invariant(
+            u128::from(total_token_0_amount),
+            u128::from(total_token_1_amount),
+        )
         .unwrap();
 
     //2.计算可兑换出多少token
-    let swap_result = CurveCalculator::swap_base_input(
-        u128::from(amount_in),
-        u128::from(total_input_token_amount),
-        u128::from(total_output_token_amount),
-        ctx.accounts.amm_config.trade_fee_rate,
-    )
-    .ok_or(ErrorCode::ZeroTradingTokens)?;
-
-    let constant_after = swap_result
+    let trade_fee_rate = pool_state.trade_fee_rate(accounts.amm_config, block_timestamp)?;
+    let swap_result = curve
+        .swap_base_input(
+            u128::from(actual_amount_in),
+            u128::from(total_input_token_amount),
+            u128::from(total_output_token_amount),
+            trade_direction,
+            trade_fee_rate,
+            accounts.amm_config.protocol_fee_rate,
+            accounts.amm_config.fund_fee_rate,
+        )
+        .ok_or(ErrorCode::ZeroTradingTokens)?;
+
+    let new_swap_source_amount = swap_result
         .new_swap_source_amount
         .checked_sub(swap_result.trade_fee)
-        .unwrap()
-        .checked_mul(swap_result.new_swap_destination_amount)
+        .unwrap();
+    let (new_token_0_amount, new_token_1_amount) = match trade_direction {
+        TradeDirection::ZeroForOne => (
+            new_swap_source_amount,
+            swap_result.new_swap_destination_amount,
+        ),
+        TradeDirection::OneForZero => (
+            swap_result.new_swap_destination_amount,
+            new_swap_source_amount,
+        ),
+    };
+    let constant_after = curve
+        .invariant(new_token_0_amount, new_token_1_amount)
         .unwrap();
 
     require_eq!(
         u64::try_from(swap_result.source_amount_swapped).unwrap(),
-        amount_in
+        actual_amount_in
     );
 
     require_gte!(constant_after, constant_before);
 
+    //用户实际收到的数量需扣除输出token的转账手续费
     let amount_out: u64 = u64::try_from(swap_result.destination_amount_swapped).unwrap();
-    require_gte!(amount_out, minimum_amount_out, ErrorCode::ExceededSlippage);
+    let output_transfer_fee =
+        get_transfer_fee(&accounts.output_token_mint.to_account_info(), amount_out)?;
+    let amount_received = amount_out.checked_sub(output_transfer_fee).unwrap();
+    require_gt!(amount_received, 0);
+    require_gte!(
+        amount_received,
+        minimum_amount_out,
+        ErrorCode::ExceededSlippage
+    );
+
+    let protocol_fee = u64::try_from(swap_result.protocol_fee).unwrap();
+    let fund_fee = u64::try_from(swap_result.fund_fee).unwrap();
+    pool_state.accrue_fees(trade_direction, protocol_fee, fund_fee);
+    let creator_share_rate =
+        pool_state.creator_share_rate(accounts.amm_config, trade_fee_rate, referral_fee_rate)?;
+    let creator_fee = pool_state.accrue_creator_fee(
+        trade_direction,
+        swap_result.trade_fee,
+        creator_share_rate,
+    )?;
+
     //3.transfer token
     //3.1 转移用户amount_in_token到vault
     transfer_from_user_to_pool_vault(
-        ctx.accounts.payer.to_account_info(),
-        ctx.accounts.input_token_account.to_account_info(),
-        ctx.accounts.input_vault.to_account_info(),
-        ctx.accounts.input_token_mint.to_account_info(),
-        ctx.accounts.input_token_program.to_account_info(),
+        accounts.payer.to_account_info(),
+        accounts.input_token_account.to_account_info(),
+        accounts.input_vault.to_account_info(),
+        accounts.input_token_mint.to_account_info(),
+        accounts.input_token_program.to_account_info(),
         amount_in,
-        ctx.accounts.input_token_mint.decimals,
+        accounts.input_token_mint.decimals,
     )?;
 
     //3.2 转移vault token 到 用户destination token account
     transfer_from_pool_vault_to_user(
-        ctx.accounts.authority.to_account_info(),
-        ctx.accounts.output_vault.to_account_info(),
-        ctx.accounts.output_token_account.to_account_info(),
-        ctx.accounts.output_token_mint.to_account_info(),
-        ctx.accounts.output_token_program.to_account_info(),
+        accounts.authority.to_account_info(),
+        accounts.output_vault.to_account_info(),
+        accounts.output_token_account.to_account_info(),
+        accounts.output_token_mint.to_account_info(),
+        accounts.output_token_program.to_account_info(),
         amount_out,
-        ctx.accounts.output_token_mint.decimals,
+        accounts.output_token_mint.decimals,
         &[&[crate::AUTH_SEED.as_bytes(), &[pool_state.auth_bump]]],
     )?;
 
+    accounts.observation_state.load_mut()?.update(
+        block_timestamp,
+        token_0_price_x32,
+        token_1_price_x32,
+    );
     pool_state.recent_epoch = Clock::get()?.epoch;
-    Ok(())
+
+    //4.有推荐人时,从vault 把推荐人分成的手续费转给推荐人
+    let auth_bump = pool_state.auth_bump;
+    let referral_fee = pay_referral_fee(&accounts, swap_result.trade_fee, auth_bump)?;
+
+    let input_vault_after = total_input_token_amount
+        .checked_add(actual_amount_in)
+        .unwrap()
+        .checked_sub(protocol_fee + fund_fee + referral_fee + creator_fee)
+        .unwrap();
+    let output_vault_after = total_output_token_amount.checked_sub(amount_out).unwrap();
+    accounts
+        .pool_state
+        .set_trade_reserves(trade_direction, input_vault_after, output_vault_after);
+
+    Ok(SwapEvent {
+        pool_id: accounts.pool_id,
+        trade_direction,
+        input_vault_before: total_input_token_amount,
+        output_vault_before: total_output_token_amount,
+        input_vault_after,
+        output_vault_after,
+        input_amount: actual_amount_in,
+        output_amount: amount_out,
+        input_transfer_fee,
+        output_transfer_fee,
+        trade_fee: u64::try_from(swap_result.trade_fee).unwrap(),
+        protocol_fee,
+        fund_fee,
+        referral_fee,
+        creator_fee,
+        base_input: true,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn referral_fee_is_a_share_of_the_trade_fee() {
+        assert_eq!(referral_fee(1_000, 200_000).unwrap(), 200);
+        assert_eq!(referral_fee(9, 200_000).unwrap(), 1);
+        // a swap without a referrer pays a zero rate
+        assert_eq!(referral_fee(1_000, 0).unwrap(), 0);
+    }
 }
@@ -1,21 +1,34 @@
-use std::ops::DerefMut;
-
 use anchor_lang::prelude::*;
 
 use crate::{
-    error::ErrorCode, transfer_from_pool_vault_to_user,
-    transfer_from_user_to_pool_vault, CurveCalculator, PoolStatusBitIndex, TradeDirection,
+    check_deadline, emit_event, error::ErrorCode, get_transfer_inverse_fee,
+    transfer_from_pool_vault_to_user, transfer_from_user_to_pool_vault, PoolStatusBitIndex,
+    SwapEvent, TradeDirection,
 };
 
-use super::Swap;
+use super::{pay_referral_fee, Swap, SwapAccounts};
 
 pub fn process_swap_base_output(
     ctx: Context<Swap>,
     amount_out: u64,
     max_amount_in: u64,
+    deadline: Option<i64>,
 ) -> Result<()> {
+    check_deadline(deadline)?;
+    let event = execute_swap_base_output(ctx.accounts.swap_accounts(), amount_out, max_amount_in)?;
+    emit_event!(ctx, event);
+    Ok(())
+}
+
+/// Swap for an exact output amount, returning the event to emit
+pub fn execute_swap_base_output(
+    accounts: SwapAccounts,
+    amount_out: u64,
+    max_amount_in: u64,
+) -> Result<SwapEvent> {
     let block_timestamp = Clock::get()?.unix_timestamp as u64;
-    let pool_state = ctx.accounts.pool_state.deref_mut();
+    let referral_fee_rate = accounts.referral_fee_rate();
+    let pool_state = &mut *accounts.pool_state;
 
     //校验交易池状态及开始时间
     if !pool_state.get_status_by_bit(PoolStatusBitIndex::Swap)
@@ -26,101 +39,156 @@ pub fn process_swap_base_output(
 
     require_gt!(amount_out, 0);
 
-    //1.获取两个token vault可交易token及计算价格
-    let (
-        _trade_direction,
-        total_input_token_amount,
-        total_output_token_amount,
-        _token_0_price_x64,
-        _token_1_price_x64,
-    ) = if ctx.accounts.input_vault.key() == pool_state.token_0_vault
-        && ctx.accounts.output_vault.key() == pool_state.token_1_vault
-    {
-        let (token_0_price_x64, token_1_price_x64) = pool_state.token_price_x32(
-            ctx.accounts.input_vault.amount,
-            ctx.accounts.output_vault.amount,
-        );
-
-        (
-            TradeDirection::ZeroForOne,
-            ctx.accounts.input_vault.amount,
-            ctx.accounts.output_vault.amount,
-            token_0_price_x64,
-            token_1_price_x64,
-        )
-    } else if ctx.accounts.input_vault.key() == pool_state.token_1_vault
-        && ctx.accounts.output_vault.key() == pool_state.token_0_vault
-    {
-        let (token_0_price_x64, token_1_price_x64) = pool_state.token_price_x32(
-            ctx.accounts.output_vault.amount,
-            ctx.accounts.input_vault.amount,
-        );
-
-        (
-            TradeDirection::OneForZero,
-            ctx.accounts.input_vault.amount,
-            ctx.accounts.output_vault.amount,
-            token_0_price_x64,
-            token_1_price_x64,
-        )
-    } else {
-        return err!(ErrorCode::InvalidVault);
+    //1.根据vault确定交易方向,获取两个token vault可交易token及计算价格
+    let trade_direction =
+        pool_state.trade_direction(accounts.input_vault.key(), accounts.output_vault.key())?;
+    pool_state.sync_legacy_trade_reserves(
+        trade_direction,
+        accounts.input_vault.amount,
+        accounts.output_vault.amount,
+    )?;
+    let (total_token_0_amount, total_token_1_amount) = pool_state.reserves();
+    let (total_input_token_amount, total_output_token_amount) = match trade_direction {
+        TradeDirection::ZeroForOne => (total_token_0_amount, total_token_1_amount),
+        TradeDirection::OneForZero => (total_token_1_amount, total_token_0_amount),
     };
-
-    let constant_before = u128::from(total_input_token_amount)
-        .checked_mul(u128::from(total_output_token_amount))
+    let (token_0_price_x32, token_1_price_x32) =
+        pool_state.token_price_x32(total_token_0_amount, total_token_1_amount);
+
+    //vault需转出的数量需包含输出token的转账手续费,用户才能收到amount_out
+    let output_transfer_fee =
+        get_transfer_inverse_fee(&accounts.output_token_mint.to_account_info(), amount_out)?;
+    let actual_amount_out = amount_out.checked_add(output_transfer_fee).unwrap();
+
+    let curve = pool_state.curve()?;
+    let constant_before = curve
+        .invariant(
+            u128::from(total_token_0_amount),
+            u128::from(total_token_1_amount),
+        )
         .unwrap();
 
     //2.计算需要投入多少token
-    let swap_result = CurveCalculator::swap_base_output(
-        u128::from(amount_out),
-        u128::from(total_input_token_amount),
-        u128::from(total_output_token_amount),
-        ctx.accounts.amm_config.trade_fee_rate,
-    )
-    .ok_or(ErrorCode::ZeroTradingTokens)?;
-
-    let constant_after = swap_result
+    let trade_fee_rate = pool_state.trade_fee_rate(accounts.amm_config, block_timestamp)?;
+    let swap_result = curve
+        .swap_base_output(
+            u128::from(actual_amount_out),
+            u128::from(total_input_token_amount),
+            u128::from(total_output_token_amount),
+            trade_direction,
+            trade_fee_rate,
+            accounts.amm_config.protocol_fee_rate,
+            accounts.amm_config.fund_fee_rate,
+        )
+        .ok_or(ErrorCode::ZeroTradingTokens)?;
+
+    let new_swap_source_amount = swap_result
         .new_swap_source_amount
         .checked_sub(swap_result.trade_fee)
-        .unwrap()
-        .checked_mul(swap_result.new_swap_destination_amount)
+        .unwrap();
+    let (new_token_0_amount, new_token_1_amount) = match trade_direction {
+        TradeDirection::ZeroForOne => (
+            new_swap_source_amount,
+            swap_result.new_swap_destination_amount,
+        ),
+        TradeDirection::OneForZero => (
+            swap_result.new_swap_destination_amount,
+            new_swap_source_amount,
+        ),
+    };
+    let constant_after = curve
+        .invariant(new_token_0_amount, new_token_1_amount)
         .unwrap();
 
     require_eq!(
         u64::try_from(swap_result.destination_amount_swapped).unwrap(),
-        amount_out
+        actual_amount_out
     );
 
     require_gte!(constant_after, constant_before);
 
-    let amount_in: u64 = u64::try_from(swap_result.source_amount_swapped).unwrap();
+    //用户需转入的数量需包含输入token的转账手续费,vault才能收到source_amount_swapped
+    let actual_amount_in: u64 = u64::try_from(swap_result.source_amount_swapped).unwrap();
+    let input_transfer_fee = get_transfer_inverse_fee(
+        &accounts.input_token_mint.to_account_info(),
+        actual_amount_in,
+    )?;
+    let amount_in = actual_amount_in.checked_add(input_transfer_fee).unwrap();
     require_gte!(max_amount_in, amount_in, ErrorCode::ExceededSlippage);
 
+    let protocol_fee = u64::try_from(swap_result.protocol_fee).unwrap();
+    let fund_fee = u64::try_from(swap_result.fund_fee).unwrap();
+    pool_state.accrue_fees(trade_direction, protocol_fee, fund_fee);
+    let creator_share_rate =
+        pool_state.creator_share_rate(accounts.amm_config, trade_fee_rate, referral_fee_rate)?;
+    let creator_fee = pool_state.accrue_creator_fee(
+        trade_direction,
+        swap_result.trade_fee,
+        creator_share_rate,
+    )?;
+
     //3.transfer token
     //3.1 转移用户amount_in_token到vault
     transfer_from_user_to_pool_vault(
-        ctx.accounts.payer.to_account_info(),
-        ctx.accounts.input_token_account.to_account_info(),
-        ctx.accounts.input_vault.to_account_info(),
-        ctx.accounts.input_token_mint.to_account_info(),
-        ctx.accounts.input_token_program.to_account_info(),
+        accounts.payer.to_account_info(),
+        accounts.input_token_account.to_account_info(),
+        accounts.input_vault.to_account_info(),
+        accounts.input_token_mint.to_account_info(),
+        accounts.input_token_program.to_account_info(),
         amount_in,
-        ctx.accounts.input_token_mint.decimals,
+        accounts.input_token_mint.decimals,
     )?;
 
     //3.2 转移vault token 到 用户destination token account
     transfer_from_pool_vault_to_user(
-        ctx.accounts.authority.to_account_info(),
-        ctx.accounts.output_vault.to_account_info(),
-        ctx.accounts.output_token_account.to_account_info(),
-        ctx.accounts.output_token_mint.to_account_info(),
-        ctx.accounts.output_token_program.to_account_info(),
-        amount_out,
-        ctx.accounts.output_token_mint.decimals,
+        accounts.authority.to_account_info(),
+        accounts.output_vault.to_account_info(),
+        accounts.output_token_account.to_account_info(),
+        accounts.output_token_mint.to_account_info(),
+        accounts.output_token_program.to_account_info(),
+        actual_amount_out,
+        accounts.output_token_mint.decimals,
         &[&[crate::AUTH_SEED.as_bytes(), &[pool_state.auth_bump]]],
     )?;
+    accounts.observation_state.load_mut()?.update(
+        block_timestamp,
+        token_0_price_x32,
+        token_1_price_x32,
+    );
     pool_state.recent_epoch = Clock::get()?.epoch;
 
-    Ok(())
+    //4.有推荐人时,从vault 把推荐人分成的手续费转给推荐人
+    let auth_bump = pool_state.auth_bump;
+    let referral_fee = pay_referral_fee(&accounts, swap_result.trade_fee, auth_bump)?;
+
+    let input_vault_after = total_input_token_amount
+        .checked_add(actual_amount_in)
+        .unwrap()
+        .checked_sub(protocol_fee + fund_fee + referral_fee + creator_fee)
+        .unwrap();
+    let output_vault_after = total_output_token_amount
+        .checked_sub(actual_amount_out)
+        .unwrap();
+    accounts
+        .pool_state
+        .set_trade_reserves(trade_direction, input_vault_after, output_vault_after);
+
+    Ok(SwapEvent {
+        pool_id: accounts.pool_id,
+        trade_direction,
+        input_vault_before: total_input_token_amount,
+        output_vault_before: total_output_token_amount,
+        input_vault_after,
+        output_vault_after,
+        input_amount: actual_amount_in,
+        output_amount: actual_amount_out,
+        input_transfer_fee,
+        output_transfer_fee,
+        trade_fee: u64::try_from(swap_result.trade_fee).unwrap(),
+        protocol_fee,
+        fund_fee,
+        referral_fee,
+        creator_fee,
+        base_input: false,
+    })
 }
@@ -0,0 +1,300 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use crate::{
+    check_deadline, emit_event, error::ErrorCode, get_transfer_fee, pool,
+    transfer_from_pool_vault_to_user, transfer_from_user_to_pool_vault, AmmConfig,
+    ObservationState, PoolState, SwapEvent, TradeDirection,
+};
+
+/// Number of `remaining_accounts` of each hop of `swap_route`:
+/// amm_config, pool_state, input_vault, output_vault, output_token_mint,
+/// output_token_program, observation_state
+pub const ROUTE_HOP_ACCOUNTS: usize = 7;
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct SwapRoute<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: pool vault and lp mint authority, shared by all pools
+    #[account(
+        seeds = [
+            crate::AUTH_SEED.as_bytes(),
+        ],
+        bump,
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        associated_token::mint = input_token_mint,
+        associated_token::authority = payer,
+        associated_token::token_program = input_token_program
+    )]
+    pub input_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = output_token_mint,
+        associated_token::authority = payer,
+        associated_token::token_program = output_token_program
+    )]
+    pub output_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub input_token_program: Interface<'info, TokenInterface>,
+
+    pub output_token_program: Interface<'info, TokenInterface>,
+
+    /// The mint of the token paid into the first pool
+    pub input_token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The mint of the token paid out of the last pool
+    pub output_token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn process_swap_route<'info>(
+    ctx: Context<'_, '_, 'info, 'info, SwapRoute<'info>>,
+    amount_in: u64,
+    minimum_amount_out: u64,
+    deadline: Option<i64>,
+) -> Result<()> {
+    check_deadline(deadline)?;
+    let block_timestamp = Clock::get()?.unix_timestamp as u64;
+    require_gt!(amount_in, 0);
+    let hops = ctx.remaining_accounts.chunks_exact(ROUTE_HOP_ACCOUNTS);
+    require!(
+        hops.len() > 0 && hops.remainder().is_empty(),
+        ErrorCode::InvalidInput
+    );
+    let signer_seeds: &[&[&[u8]]] = &[&[crate::AUTH_SEED.as_bytes(), &[ctx.bumps.authority]]];
+
+    //上一跳的付款账户,第一跳由用户付款,之后由上一个pool的output_vault直接转入下一个pool
+    let mut source_account = ctx.accounts.input_token_account.to_account_info();
+    let mut source_mint = ctx.accounts.input_token_mint.to_account_info();
+    let mut source_mint_decimals = ctx.accounts.input_token_mint.decimals;
+    let mut source_token_program = ctx.accounts.input_token_program.to_account_info();
+    let mut from_user = true;
+    let mut amount_sent = amount_in;
+    let mut pool_keys: Vec<Pubkey> = Vec::new();
+
+    for accounts in hops {
+        //1.加载并校验每一跳的账户
+        let amm_config = Account::<AmmConfig>::try_from(&accounts[0])?;
+        let mut pool_state = Account::<PoolState>::try_from(&accounts[1])?;
+        let input_vault = InterfaceAccount::<TokenAccount>::try_from(&accounts[2])?;
+        let output_vault = InterfaceAccount::<TokenAccount>::try_from(&accounts[3])?;
+        let output_mint = InterfaceAccount::<Mint>::try_from(&accounts[4])?;
+        let output_token_program = accounts[5].clone();
+        let observation_state = AccountLoader::<ObservationState>::try_from(&accounts[6])?;
+
+        require_keys_eq!(
+            pool_state.amm_config,
+            amm_config.key(),
+            ErrorCode::InvalidInput
+        );
+        require!(
+            !pool_keys.contains(&pool_state.key()),
+            ErrorCode::InvalidInput
+        );
+        pool_keys.push(pool_state.key());
+        require!(!pool_state.locked, ErrorCode::PoolLocked);
+        require_keys_eq!(
+            observation_state.key(),
+            pool_state.observation_key,
+            ErrorCode::InvalidInput
+        );
+        require_keys_eq!(input_vault.mint, source_mint.key(), ErrorCode::InvalidVault);
+        require_keys_eq!(
+            output_vault.mint,
+            output_mint.key(),
+            ErrorCode::InvalidVault
+        );
+        require_keys_eq!(
+            *output_mint.to_account_info().owner,
+            output_token_program.key(),
+            ErrorCode::InvalidInput
+        );
+
+        //校验交易池状态及开始时间
+        if !pool_state.get_status_by_bit(pool::PoolStatusBitIndex::Swap)
+            || block_timestamp < pool_state.open_time
+        {
+            return err!(ErrorCode::NotApproved);
+        }
+
+        //2.根据vault确定交易方向,获取两个token vault可交易token及计算价格
+        let trade_direction = pool_state.trade_direction(input_vault.key(), output_vault.key())?;
+        pool_state.sync_legacy_trade_reserves(
+            trade_direction,
+            input_vault.amount,
+            output_vault.amount,
+        )?;
+        let (total_token_0_amount, total_token_1_amount) = pool_state.reserves();
+        let (total_input_token_amount, total_output_token_amount) = match trade_direction {
+            TradeDirection::ZeroForOne => (total_token_0_amount, total_token_1_amount),
+            TradeDirection::OneForZero => (total_token_1_amount, total_token_0_amount),
+        };
+        let (token_0_price_x32, token_1_price_x32) =
+            pool_state.token_price_x32(total_token_0_amount, total_token_1_amount);
+
+        //扣除token-2022转账手续费,vault实际收到的数量
+        let input_transfer_fee = get_transfer_fee(&source_mint, amount_sent)?;
+        let actual_amount_in = amount_sent.checked_sub(input_transfer_fee).unwrap();
+        require_gt!(actual_amount_in, 0);
+
+        let curve = pool_state.curve()?;
+        let constant_before = curve
+            .invariant(
+                u128::from(total_token_0_amount),
+                u128::from(total_token_1_amount),
+            )
+            .unwrap();
+
+        //3.计算可兑换出多少token
+        let trade_fee_rate = pool_state.trade_fee_rate(&amm_config, block_timestamp)?;
+        let swap_result = curve
+            .swap_base_input(
+                u128::from(actual_amount_in),
+                u128::from(total_input_token_amount),
+                u128::from(total_output_token_amount),
+                trade_direction,
+                trade_fee_rate,
+                amm_config.protocol_fee_rate,
+                amm_config.fund_fee_rate,
+            )
+            .ok_or(ErrorCode::ZeroTradingTokens)?;
+
+        let new_swap_source_amount = swap_result
+            .new_swap_source_amount
+            .checked_sub(swap_result.trade_fee)
+            .unwrap();
+        let (new_token_0_amount, new_token_1_amount) = match trade_direction {
+            TradeDirection::ZeroForOne => (
+                new_swap_source_amount,
+                swap_result.new_swap_destination_amount,
+            ),
+            TradeDirection::OneForZero => (
+                swap_result.new_swap_destination_amount,
+                new_swap_source_amount,
+            ),
+        };
+        let constant_after = curve
+            .invariant(new_token_0_amount, new_token_1_amount)
+            .unwrap();
+        require_gte!(constant_after, constant_before);
+
+        let amount_out = u64::try_from(swap_result.destination_amount_swapped).unwrap();
+        require_gt!(amount_out, 0);
+        let protocol_fee = u64::try_from(swap_result.protocol_fee).unwrap();
+        let fund_fee = u64::try_from(swap_result.fund_fee).unwrap();
+        pool_state.accrue_fees(trade_direction, protocol_fee, fund_fee);
+        let creator_share_rate = pool_state.creator_share_rate(&amm_config, trade_fee_rate, 0)?;
+        let creator_fee = pool_state.accrue_creator_fee(
+            trade_direction,
+            swap_result.trade_fee,
+            creator_share_rate,
+        )?;
+        let input_vault_after = total_input_token_amount
+            .checked_add(actual_amount_in)
+            .unwrap()
+            .checked_sub(protocol_fee + fund_fee + creator_fee)
+            .unwrap();
+        let output_vault_after = total_output_token_amount.checked_sub(amount_out).unwrap();
+        pool_state.set_trade_reserves(trade_direction, input_vault_after, output_vault_after);
+        pool_state.recent_epoch = Clock::get()?.epoch;
+        pool_state.exit(&crate::ID)?;
+        observation_state
+            .load_mut()?
+            .update(block_timestamp, token_0_price_x32, token_1_price_x32);
+
+        //4.把上一跳的token转入本跳的input_vault
+        if from_user {
+            transfer_from_user_to_pool_vault(
+                ctx.accounts.payer.to_account_info(),
+                source_account,
+                input_vault.to_account_info(),
+                source_mint,
+                source_token_program,
+                amount_sent,
+                source_mint_decimals,
+            )?;
+        } else {
+            transfer_from_pool_vault_to_user(
+                ctx.accounts.authority.to_account_info(),
+                source_account,
+                input_vault.to_account_info(),
+                source_mint,
+                source_token_program,
+                amount_sent,
+                source_mint_decimals,
+                signer_seeds,
+            )?;
+        }
+
+        emit_event!(
+            ctx,
+            SwapEvent {
+                pool_id: pool_state.key(),
+                trade_direction,
+                input_vault_before: total_input_token_amount,
+                output_vault_before: total_output_token_amount,
+                input_vault_after,
+                output_vault_after,
+                input_amount: actual_amount_in,
+                output_amount: amount_out,
+                input_transfer_fee,
+                output_transfer_fee: get_transfer_fee(&output_mint.to_account_info(), amount_out)?,
+                trade_fee: u64::try_from(swap_result.trade_fee).unwrap(),
+                protocol_fee,
+                fund_fee,
+                referral_fee: 0,
+                creator_fee,
+                base_input: true,
+            }
+        );
+
+        source_account = output_vault.to_account_info();
+        source_mint = output_mint.to_account_info();
+        source_mint_decimals = output_mint.decimals;
+        source_token_program = output_token_program;
+        from_user = false;
+        amount_sent = amount_out;
+    }
+
+    //5.最后一跳的output_vault转给用户,并校验用户实际收到的数量
+    require_keys_eq!(
+        source_mint.key(),
+        ctx.accounts.output_token_mint.key(),
+        ErrorCode::InvalidInput
+    );
+    let output_transfer_fee = get_transfer_fee(&source_mint, amount_sent)?;
+    let amount_received = amount_sent.checked_sub(output_transfer_fee).unwrap();
+    require_gt!(amount_received, 0);
+    require_gte!(
+        amount_received,
+        minimum_amount_out,
+        ErrorCode::ExceededSlippage
+    );
+
+    transfer_from_pool_vault_to_user(
+        ctx.accounts.authority.to_account_info(),
+        source_account,
+        ctx.accounts.output_token_account.to_account_info(),
+        source_mint,
+        source_token_program,
+        amount_sent,
+        source_mint_decimals,
+        signer_seeds,
+    )?;
+    Ok(())
+}
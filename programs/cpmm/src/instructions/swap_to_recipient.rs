@@ -0,0 +1,159 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::{
+    check_deadline, emit_event, error::ErrorCode, execute_swap_base_input,
+    execute_swap_base_output, sorted_mints, AmmConfig, ObservationState, PoolState, SwapAccounts,
+    AMM_CONFIG_SEED, POOL_SEED, POOL_VAULT_SEED,
+};
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(index:u16)]
+pub struct SwapToRecipient<'info> {
+    pub payer: Signer<'info>,
+
+    /// CHECK: the owner of `output_token_account`, can be any account including a PDA
+    pub recipient: UncheckedAccount<'info>,
+
+    /// CHECK: pool vault and lp mint authority
+    #[account(
+        seeds = [
+            crate::AUTH_SEED.as_bytes(),
+        ],
+        bump = pool_state.auth_bump,
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [
+            AMM_CONFIG_SEED.as_bytes(),
+            &index.to_be_bytes()
+        ],
+        bump = amm_config.bump
+    )]
+    pub amm_config: Box<Account<'info, AmmConfig>>,
+
+    /// Pool state account, derived from the sorted mint pair
+    #[account(
+        mut,
+        constraint = !pool_state.locked @ ErrorCode::PoolLocked,
+        seeds = [
+            POOL_SEED.as_bytes(),
+            amm_config.key().as_ref(),
+            sorted_mints(input_token_mint.key(), output_token_mint.key()).0.as_ref(),
+            sorted_mints(input_token_mint.key(), output_token_mint.key()).1.as_ref(),
+        ],
+        bump = pool_state.bump
+    )]
+    pub pool_state: Account<'info, PoolState>,
+
+    #[account(
+        mut,
+        token::mint = input_token_mint,
+        token::authority = payer,
+        token::token_program = input_token_program,
+    )]
+    pub input_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Any token account of the output mint owned by the recipient
+    #[account(
+        mut,
+        token::mint = output_token_mint,
+        token::authority = recipient,
+        token::token_program = output_token_program,
+    )]
+    pub output_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = input_vault.key() == pool_state.token_0_vault.key() || input_vault.key() == pool_state.token_1_vault.key(),
+        seeds = [
+            POOL_VAULT_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            input_token_mint.key().as_ref()
+        ],
+        bump,
+    )]
+    pub input_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = output_vault.key() == pool_state.token_0_vault.key() || output_vault.key() == pool_state.token_1_vault.key(),
+        seeds = [
+            POOL_VAULT_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            output_token_mint.key().as_ref()
+        ],
+        bump,
+    )]
+    pub output_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub input_token_program: Interface<'info, TokenInterface>,
+
+    pub output_token_program: Interface<'info, TokenInterface>,
+
+    pub input_token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    pub output_token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The program account for the most recent oracle observation
+    #[account(mut, address = pool_state.observation_key)]
+    pub observation_state: AccountLoader<'info, ObservationState>,
+
+    /// Optional token account of the input mint receiving the referral fee,
+    /// it must not be owned by the payer
+    #[account(
+        mut,
+        token::mint = input_token_mint,
+        token::token_program = input_token_program,
+    )]
+    pub referrer_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+}
+
+impl<'info> SwapToRecipient<'info> {
+    pub fn swap_accounts(&mut self) -> SwapAccounts<'_, 'info> {
+        SwapAccounts {
+            payer: &self.payer,
+            authority: &self.authority,
+            amm_config: &self.amm_config,
+            pool_id: self.pool_state.key(),
+            pool_state: &mut self.pool_state,
+            input_token_account: &self.input_token_account,
+            output_token_account: &self.output_token_account,
+            input_vault: &self.input_vault,
+            output_vault: &self.output_vault,
+            input_token_program: &self.input_token_program,
+            output_token_program: &self.output_token_program,
+            input_token_mint: &self.input_token_mint,
+            output_token_mint: &self.output_token_mint,
+            observation_state: &self.observation_state,
+            referrer_token_account: self.referrer_token_account.as_deref(),
+        }
+    }
+}
+
+pub fn process_swap_base_input_to_recipient(
+    ctx: Context<SwapToRecipient>,
+    amount_in: u64,
+    minimum_amount_out: u64,
+    deadline: Option<i64>,
+) -> Result<()> {
+    check_deadline(deadline)?;
+    let event =
+        execute_swap_base_input(ctx.accounts.swap_accounts(), amount_in, minimum_amount_out)?;
+    emit_event!(ctx, event);
+    Ok(())
+}
+
+pub fn process_swap_base_output_to_recipient(
+    ctx: Context<SwapToRecipient>,
+    amount_out: u64,
+    max_amount_in: u64,
+    deadline: Option<i64>,
+) -> Result<()> {
+    check_deadline(deadline)?;
+    let event = execute_swap_base_output(ctx.accounts.swap_accounts(), amount_out, max_amount_in)?;
+    emit_event!(ctx, event);
+    Ok(())
+}
@@ -0,0 +1,48 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::TokenAccount;
+
+use crate::{emit_event, error::ErrorCode, PoolState, ReservesSynced};
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+pub struct SyncReserves<'info> {
+    /// Pool state account
+    #[account(
+        mut,
+        has_one = token_0_vault @ ErrorCode::InvalidVault,
+        has_one = token_1_vault @ ErrorCode::InvalidVault,
+        constraint = !pool_state.locked @ ErrorCode::PoolLocked,
+    )]
+    pub pool_state: Box<Account<'info, PoolState>>,
+
+    /// The address that holds pool tokens for token_0
+    pub token_0_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The address that holds pool tokens for token_1
+    pub token_1_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+}
+
+pub fn process_sync(ctx: Context<SyncReserves>) -> Result<()> {
+    let pool_state = &mut ctx.accounts.pool_state;
+    let (reserve_0_before, reserve_1_before) = pool_state.reserves();
+
+    //vault中除protocol和fund手续费外的token全部计入reserves
+    let (reserve_0, reserve_1) = pool_state.vault_amount_without_fee(
+        ctx.accounts.token_0_vault.amount,
+        ctx.accounts.token_1_vault.amount,
+    )?;
+    pool_state.set_reserves(reserve_0, reserve_1);
+    pool_state.recent_epoch = Clock::get()?.epoch;
+
+    emit_event!(
+        ctx,
+        ReservesSynced {
+            pool_id: ctx.accounts.pool_state.key(),
+            reserve_0_before,
+            reserve_1_before,
+            reserve_0,
+            reserve_1,
+        }
+    );
+    Ok(())
+}
@@ -2,8 +2,9 @@ use std::ops::DerefMut;
 
 use anchor_lang::prelude::*;
 use anchor_spl::{associated_token::AssociatedToken, token::Token, token_interface::{Mint, TokenAccount, TokenInterface}};
-use crate::{error::ErrorCode, token_burn, transfer_from_pool_vault_to_user, AmmConfig, CurveCalculator, PoolState, AMM_CONFIG_SEED, POOL_SEED};
+use crate::{check_deadline, emit_event, error::ErrorCode, get_transfer_fee, token_burn, LpChangeType, LpChanged, transfer_from_pool_vault_to_user, AmmConfig, PoolState, AMM_CONFIG_SEED, POOL_SEED};
 
+#[cfg_attr(feature = "event-cpi", event_cpi)]
 #[derive(Accounts)]
 #[instruction(index:u16)]
 pub struct Withdraw<'info> {
@@ -36,6 +37,7 @@ pub struct Withdraw<'info> {
         has_one = lp_mint ,
         has_one = token_0_vault,
         has_one = token_1_vault,
+        constraint = !pool_state.locked @ ErrorCode::PoolLocked,
         seeds = [
             POOL_SEED.as_bytes(),
             amm_config.key().as_ref(),
@@ -127,18 +129,23 @@ pub fn process_withdraw(
     ctx:Context<Withdraw>,
     lp_token_amount:u64,
     minimum_token_0_amount:u64,
-    minimum_token_1_amount:u64
+    minimum_token_1_amount:u64,
+    deadline: Option<i64>,
     ) -> Result<()>{
+        check_deadline(deadline)?;
         require_gt!(lp_token_amount,0);
         let pool_state = ctx.accounts.pool_state.deref_mut();
         if !pool_state.get_status_by_bit(crate::PoolStatusBitIndex::Withdraw) {
             return err!(ErrorCode::NotApproved);
         }
-        let total_token_0_amount = ctx.accounts.token_0_vault.amount;
-        let total_token_1_amount = ctx.accounts.token_1_vault.amount;
+        pool_state.sync_legacy_reserves(
+            ctx.accounts.token_0_vault.amount,
+            ctx.accounts.token_1_vault.amount,
+        )?;
+        let (total_token_0_amount, total_token_1_amount) = pool_state.reserves();
 
         //1.计算需要提取的token_1_amount, 和token_2_amount
-        let result = CurveCalculator::lp_tokens_to_trading_tokens(
+        let result = pool_state.curve()?.lp_tokens_to_trading_tokens(
                     u128::from(lp_token_amount), 
                     u128::from(pool_state.lp_supply), 
                     u128::from(total_token_0_amount), 
@@ -148,13 +155,20 @@ pub fn process_withdraw(
 
         let token_0_amount = std::cmp::min(total_token_0_amount, u64::try_from(result.token_0_amount).unwrap());
         let token_1_amount = std::cmp::min(total_token_1_amount,u64::try_from(result.token_1_amount).unwrap());
-        //2.校验是否超过最大滑点
-        if token_0_amount < minimum_token_0_amount 
-                || token_1_amount < minimum_token_1_amount {
+        //2.扣除token-2022转账手续费后,校验用户实际收到的数量是否超过最大滑点
+        let transfer_fee_0 = get_transfer_fee(&ctx.accounts.vault_0_mint.to_account_info(), token_0_amount)?;
+        let transfer_fee_1 = get_transfer_fee(&ctx.accounts.vault_1_mint.to_account_info(), token_1_amount)?;
+        if token_0_amount.checked_sub(transfer_fee_0).unwrap() < minimum_token_0_amount 
+                || token_1_amount.checked_sub(transfer_fee_1).unwrap() < minimum_token_1_amount {
             return err!(ErrorCode::ExceededSlippage);
         }
 
+        let lp_amount_before = pool_state.lp_supply;
         pool_state.lp_supply = pool_state.lp_supply.checked_sub(lp_token_amount).unwrap();
+        pool_state.set_reserves(
+            total_token_0_amount.checked_sub(token_0_amount).unwrap(),
+            total_token_1_amount.checked_sub(token_1_amount).unwrap(),
+        );
 
         //3.burn lp_tokens
         token_burn(
@@ -190,5 +204,23 @@ pub fn process_withdraw(
             &[&[crate::AUTH_SEED.as_bytes(),&[pool_state.auth_bump]]]
         )?;
         pool_state.recent_epoch = Clock::get()?.epoch;
+
+        emit_event!(
+            ctx,
+            LpChanged {
+                pool_id: ctx.accounts.pool_state.key(),
+                lp_amount_before,
+                token_0_vault_before: total_token_0_amount,
+                token_1_vault_before: total_token_1_amount,
+                token_0_vault_after: total_token_0_amount.checked_sub(token_0_amount).unwrap(),
+                token_1_vault_after: total_token_1_amount.checked_sub(token_1_amount).unwrap(),
+                token_0_amount,
+                token_1_amount,
+                transfer_fee_0,
+                transfer_fee_1,
+                lp_amount: lp_token_amount,
+                change_type: LpChangeType::Withdraw,
+            }
+        );
         Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,268 @@
+use std::ops::DerefMut;
+
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::Token,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use crate::{
+    check_deadline, emit_event, error::ErrorCode, get_transfer_fee, pool, sorted_mints, token_burn,
+    transfer_from_pool_vault_to_user, AmmConfig, LpChangeType, LpChanged, ObservationState, PoolState,
+    TradeDirection, AMM_CONFIG_SEED, AUTH_SEED, POOL_SEED, POOL_VAULT_SEED,
+};
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(index:u16)]
+pub struct WithdrawSingleToken<'info> {
+    /// Owner of the lp tokens
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// CHECK: pool vault and lp mint authority
+    #[account(
+        seeds = [
+            crate::AUTH_SEED.as_bytes(),
+        ],
+        bump = pool_state.auth_bump
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [
+            AMM_CONFIG_SEED.as_bytes(),
+            &index.to_be_bytes()
+        ],
+        bump = amm_config.bump
+    )]
+    pub amm_config: Box<Account<'info, AmmConfig>>,
+
+    /// Pool state account
+    #[account(
+        mut,
+        has_one = lp_mint @ ErrorCode::NotApproved,
+        constraint = !pool_state.locked @ ErrorCode::PoolLocked,
+        seeds = [
+            POOL_SEED.as_bytes(),
+            amm_config.key().as_ref(),
+            sorted_mints(input_token_mint.key(), output_token_mint.key()).0.as_ref(),
+            sorted_mints(input_token_mint.key(), output_token_mint.key()).1.as_ref(),
+        ],
+        bump = pool_state.bump
+    )]
+    pub pool_state: Box<Account<'info, PoolState>>,
+
+    /// Owner lp token account
+    #[account(
+        mut,
+        associated_token::mint = lp_mint,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program,
+    )]
+    pub owner_lp_token: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The token account receiving the withdrawn token
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = output_token_mint,
+        associated_token::authority = owner,
+        associated_token::token_program = output_token_program
+    )]
+    pub output_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The vault of the token swapped into the withdrawn token
+    #[account(
+        constraint = input_vault.key() == pool_state.token_0_vault.key() || input_vault.key() == pool_state.token_1_vault.key(),
+        seeds = [
+            POOL_VAULT_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            input_token_mint.key().as_ref()
+        ],
+        bump,
+    )]
+    pub input_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The vault paying out the withdrawn token
+    #[account(
+        mut,
+        constraint = output_vault.key() == pool_state.token_0_vault.key() || output_vault.key() == pool_state.token_1_vault.key(),
+        seeds = [
+            POOL_VAULT_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            output_token_mint.key().as_ref()
+        ],
+        bump,
+    )]
+    pub output_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The mint of the token swapped into the withdrawn token
+    pub input_token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The mint of the withdrawn token
+    #[account(mut)]
+    pub output_token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Pool lp token mint
+    #[account(mut)]
+    pub lp_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The program account for the most recent oracle observation
+    #[account(mut, address = pool_state.observation_key)]
+    pub observation_state: AccountLoader<'info, ObservationState>,
+
+    /// the token program of the withdrawn token
+    pub output_token_program: Interface<'info, TokenInterface>,
+
+    pub token_program: Program<'info, Token>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn process_withdraw_single_token(
+    ctx: Context<WithdrawSingleToken>,
+    lp_token_amount: u64,
+    minimum_amount_out: u64,
+    deadline: Option<i64>,
+) -> Result<()> {
+    check_deadline(deadline)?;
+    require_gt!(lp_token_amount, 0);
+    let block_timestamp = Clock::get()?.unix_timestamp as u64;
+    let pool_state = ctx.accounts.pool_state.deref_mut();
+
+    //1.检查 pool state,单边提取包含一次兑换,需同时允许提取和交易
+    if !pool_state.get_status_by_bit(pool::PoolStatusBitIndex::Withdraw)
+        || !pool_state.get_status_by_bit(pool::PoolStatusBitIndex::Swap)
+        || block_timestamp < pool_state.open_time
+    {
+        return err!(ErrorCode::NotApproved);
+    }
+
+    //2.根据vault确定兑换方向,获取两个token vault可交易token及计算价格
+    let trade_direction = pool_state.trade_direction(
+        ctx.accounts.input_vault.key(),
+        ctx.accounts.output_vault.key(),
+    )?;
+    pool_state.sync_legacy_trade_reserves(
+        trade_direction,
+        ctx.accounts.input_vault.amount,
+        ctx.accounts.output_vault.amount,
+    )?;
+    let (total_token_0_amount, total_token_1_amount) = pool_state.reserves();
+    let (total_input_token_amount, total_output_token_amount) = match trade_direction {
+        TradeDirection::ZeroForOne => (total_token_0_amount, total_token_1_amount),
+        TradeDirection::OneForZero => (total_token_1_amount, total_token_0_amount),
+    };
+    let (token_0_price_x32, token_1_price_x32) =
+        pool_state.token_price_x32(total_token_0_amount, total_token_1_amount);
+
+    //3.按比例计算提取的两种token,并将不需要的一侧兑换成需要的token
+    let trade_fee_rate = pool_state.trade_fee_rate(&ctx.accounts.amm_config, block_timestamp)?;
+    let creator_share_rate =
+        pool_state.creator_share_rate(&ctx.accounts.amm_config, trade_fee_rate, 0)?;
+    let result = pool_state
+        .curve()?
+        .withdraw_single_token(
+            u128::from(lp_token_amount),
+            u128::from(pool_state.lp_supply),
+            u128::from(total_input_token_amount),
+            u128::from(total_output_token_amount),
+            trade_direction,
+            trade_fee_rate,
+            ctx.accounts.amm_config.protocol_fee_rate,
+            ctx.accounts.amm_config.fund_fee_rate,
+            creator_share_rate,
+        )
+        .ok_or(ErrorCode::ZeroTradingTokens)?;
+
+    //用户实际收到的数量需扣除输出token的转账手续费
+    let amount_out = u64::try_from(result.destination_amount).unwrap();
+    let transfer_fee = get_transfer_fee(
+        &ctx.accounts.output_token_mint.to_account_info(),
+        amount_out,
+    )?;
+    let amount_received = amount_out.checked_sub(transfer_fee).unwrap();
+    require_gt!(amount_received, 0);
+    require_gte!(
+        amount_received,
+        minimum_amount_out,
+        ErrorCode::ExceededSlippage
+    );
+
+    let protocol_fee = u64::try_from(result.swap_result.protocol_fee).unwrap();
+    let fund_fee = u64::try_from(result.swap_result.fund_fee).unwrap();
+    let creator_fee = u64::try_from(result.creator_fee).unwrap();
+    pool_state.accrue_fees(trade_direction, protocol_fee, fund_fee);
+    pool_state.add_creator_fee(trade_direction, creator_fee);
+
+    let lp_amount_before = pool_state.lp_supply;
+    pool_state.lp_supply = pool_state.lp_supply.checked_sub(lp_token_amount).unwrap();
+
+    //4.burn lp_tokens
+    token_burn(
+        ctx.accounts.owner.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        ctx.accounts.lp_mint.to_account_info(),
+        ctx.accounts.owner_lp_token.to_account_info(),
+        lp_token_amount,
+        &[&[AUTH_SEED.as_bytes(), &[pool_state.auth_bump]]],
+    )?;
+
+    //5.从output_vault 转账到用户
+    transfer_from_pool_vault_to_user(
+        ctx.accounts.authority.to_account_info(),
+        ctx.accounts.output_vault.to_account_info(),
+        ctx.accounts.output_token_account.to_account_info(),
+        ctx.accounts.output_token_mint.to_account_info(),
+        ctx.accounts.output_token_program.to_account_info(),
+        amount_out,
+        ctx.accounts.output_token_mint.decimals,
+        &[&[AUTH_SEED.as_bytes(), &[pool_state.auth_bump]]],
+    )?;
+
+    ctx.accounts.observation_state.load_mut()?.update(
+        block_timestamp,
+        token_0_price_x32,
+        token_1_price_x32,
+    );
+    pool_state.recent_epoch = Clock::get()?.epoch;
+
+    //兑换的token留在pool中,扣除protocol、fund和creator手续费
+    let input_vault_after = total_input_token_amount
+        .checked_sub(protocol_fee + fund_fee + creator_fee)
+        .unwrap();
+    let output_vault_after = total_output_token_amount.checked_sub(amount_out).unwrap();
+    let (token_0_vault_after, token_1_vault_after, token_0_amount, token_1_amount) =
+        match trade_direction {
+            TradeDirection::ZeroForOne => (input_vault_after, output_vault_after, 0, amount_out),
+            TradeDirection::OneForZero => (output_vault_after, input_vault_after, amount_out, 0),
+        };
+    pool_state.set_reserves(token_0_vault_after, token_1_vault_after);
+    let (transfer_fee_0, transfer_fee_1) = match trade_direction {
+        TradeDirection::ZeroForOne => (0, transfer_fee),
+        TradeDirection::OneForZero => (transfer_fee, 0),
+    };
+
+    emit_event!(
+        ctx,
+        LpChanged {
+            pool_id: ctx.accounts.pool_state.key(),
+            lp_amount_before,
+            token_0_vault_before: total_token_0_amount,
+            token_1_vault_before: total_token_1_amount,
+            token_0_vault_after,
+            token_1_vault_after,
+            token_0_amount,
+            token_1_amount,
+            transfer_fee_0,
+            transfer_fee_1,
+            lp_amount: lp_token_amount,
+            change_type: LpChangeType::Withdraw,
+        }
+    );
+    Ok(())
+}
@@ -27,15 +27,62 @@ solana_security_txt::security_txt! {
 
 declare_id!("HmjcsDRAWNMJtAfKzRuGLEUoj9rXidLJDfnJ5WMMYKz1");
 
-/* pub mod admin {
-    use anchor_lang::prelude::declare_id;
-    declare_id!("GThUX1Atko4tqhN2NaiTazWSeFWMuiUvfFnyJyUghFMJ");
-} */
-
 #[program]
 pub mod cpmm {
 
     use super::*;
+    use crate::error::ErrorCode;
+
+    /// Appoint the protocol admin, who creates the amm configs.
+    /// Can only be called once, by the program upgrade authority
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The accounts needed by instruction.
+    /// * `admin` - The address of the protocol admin.
+    ///
+    pub fn init_protocol_admin(ctx: Context<InitProtocolAdmin>, admin: Pubkey) -> Result<()> {
+        instructions::process_init_protocol_admin(ctx, admin)
+    }
+
+    /// Propose a new protocol admin, who must accept it to take over.
+    /// Must be called by the current protocol admin
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `new_admin`- The proposed protocol admin
+    ///
+    pub fn propose_protocol_admin(
+        ctx: Context<ProposeProtocolAdmin>,
+        new_admin: Pubkey,
+    ) -> Result<()> {
+        instructions::process_propose_protocol_admin(ctx, new_admin)
+    }
+
+    /// Accept the protocol admin role.
+    /// Must be called by the proposed protocol admin
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    ///
+    pub fn accept_protocol_admin(ctx: Context<AcceptProtocolAdmin>) -> Result<()> {
+        instructions::process_accept_protocol_admin(ctx)
+    }
+
+    /// Cancel a pending protocol admin transfer.
+    /// Must be called by the current protocol admin
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    ///
+    pub fn cancel_protocol_admin_transfer(
+        ctx: Context<CancelProtocolAdminTransfer>,
+    ) -> Result<()> {
+        instructions::process_cancel_protocol_admin_transfer(ctx)
+    }
 
     // The configuation of AMM protocol, include trade fee and protocol fee
     /// # Arguments
@@ -45,24 +92,68 @@ pub mod cpmm {
     /// * `trade_fee_rate` - Trade fee rate, can be changed.
     /// * `protocol_fee_rate` - The rate of protocol fee within tarde fee.
     /// * `fund_fee_rate` - The rate of fund fee within tarde fee.
+    /// * `create_pool_fee` - The lamports charged to pool creators, paid to the config owner.
     ///
     pub fn create_amm_config(
         ctx: Context<CreateAmmConfig>,
         index: u16,
         trade_fee_rate: u64,
+        protocol_fee_rate: u64,
+        fund_fee_rate: u64,
+        create_pool_fee: u64,
     ) -> Result<()> {
-        assert!(trade_fee_rate < FEE_RATE_DENOMINATOR_VALUE);
-        instructions::process_create_amm_config(ctx, index, trade_fee_rate)
+        require!(trade_fee_rate < FEE_RATE_DENOMINATOR_VALUE, ErrorCode::InvalidFeeRate);
+        require!(protocol_fee_rate <= FEE_RATE_DENOMINATOR_VALUE, ErrorCode::InvalidFeeRate);
+        require!(fund_fee_rate <= FEE_RATE_DENOMINATOR_VALUE, ErrorCode::InvalidFeeRate);
+        require!(
+            fund_fee_rate + protocol_fee_rate <= FEE_RATE_DENOMINATOR_VALUE,
+            ErrorCode::InvalidFeeRate
+        );
+        instructions::process_create_amm_config(
+            ctx,
+            index,
+            trade_fee_rate,
+            protocol_fee_rate,
+            fund_fee_rate,
+            create_pool_fee,
+        )
+    }
+
+    /// Resize an amm config created before the owner fields were added and
+    /// make the protocol admin its owner and create pool fee receiver.
+    /// Must be called by the protocol admin
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `_index`- The amm config index
+    ///
+    pub fn migrate_amm_config(ctx: Context<MigrateAmmConfig>, _index: u16) -> Result<()> {
+        instructions::process_migrate_amm_config(ctx)
     }
 
-    /// Updates the owner of the amm config
-    /// Must be called by the current owner or admin
+    /// Updates the amm config
+    /// Must be called by the current owner
     ///
     /// # Arguments
     ///
     /// * `ctx`- The context of accounts
-    /// * `trade_fee_rate`- The new trade fee rate of amm config, be set when `param` is 0
-    /// * `param`- The vaule can be 0 | 1 , otherwise will report a error
+    /// * `value`- The new value of the field selected by `param`
+    /// * `param`- The vaule can be 1 | 7 | 10 | 11 | 12, fee params 0 | 2 | 3 | 4 | 5 | 6 | 8 must be queued by
+    ///   `queue_config_update`, otherwise will report a error
+    ///     0: trade_fee_rate
+    ///     1: disable_create_pool
+    ///     2: protocol_fee_rate
+    ///     3: fund_fee_rate
+    ///     4: update_delay
+    ///     5: flash_loan_fee_rate
+    ///     6: referral_fee_rate
+    ///     7: lock_lp_amount, between DEFAULT_LOCK_LP_AMOUNT and MAX_LOCK_LP_AMOUNT
+    ///     8: create_pool_fee
+    ///     9: unused, the create pool fee receiver is set by `set_create_pool_fee_receiver`
+    ///     10: max_creator_fee_rate
+    ///     11: max_launch_fee_rate
+    ///     12: max_launch_fee_duration, at most LAUNCH_FEE_DURATION_LIMIT
     /// * `index`- The amm config index
     ///
     pub fn update_amm_config(
@@ -74,6 +165,106 @@ pub mod cpmm {
         instructions::process_update_amm_config(ctx, param, value, index)
     }
 
+    /// Queue a timelocked change of the amm config, which can be applied by
+    /// anyone once `update_delay` seconds have passed. Queueing again replaces
+    /// the pending change and restarts the delay.
+    /// Must be called by the current owner
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `_index`- The amm config index
+    /// * `param`- The timelocked param to change, see `update_amm_config`
+    /// * `value`- The new value of the param
+    ///
+    pub fn queue_config_update(
+        ctx: Context<QueueConfigUpdate>,
+        _index: u16,
+        param: u8,
+        value: u64,
+    ) -> Result<()> {
+        instructions::process_queue_config_update(ctx, param, value)
+    }
+
+    /// Apply the queued change of the amm config once its delay has passed.
+    /// Can be called by anyone
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `_index`- The amm config index
+    ///
+    pub fn apply_config_update(ctx: Context<ApplyConfigUpdate>, _index: u16) -> Result<()> {
+        instructions::process_apply_config_update(ctx)
+    }
+
+    /// Cancel the queued config update.
+    /// Must be called by the amm config owner
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `_index`- The amm config index
+    ///
+    pub fn cancel_config_update(ctx: Context<CancelConfigUpdate>, _index: u16) -> Result<()> {
+        instructions::process_cancel_config_update(ctx)
+    }
+
+    /// Propose a new owner of the amm config, who must accept it to take over.
+    /// Must be called by the current owner
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `_index`- The amm config index
+    /// * `new_owner`- The proposed owner
+    ///
+    pub fn propose_admin(ctx: Context<ProposeAdmin>, _index: u16, new_owner: Pubkey) -> Result<()> {
+        instructions::process_propose_admin(ctx, new_owner)
+    }
+
+    /// Accept the ownership of the amm config.
+    /// Must be called by the proposed owner
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `_index`- The amm config index
+    ///
+    pub fn accept_admin(ctx: Context<AcceptAdmin>, _index: u16) -> Result<()> {
+        instructions::process_accept_admin(ctx)
+    }
+
+    /// Cancel a pending ownership transfer of the amm config.
+    /// Must be called by the current owner
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `_index`- The amm config index
+    ///
+    pub fn cancel_admin_transfer(ctx: Context<CancelAdminTransfer>, _index: u16) -> Result<()> {
+        instructions::process_cancel_admin_transfer(ctx)
+    }
+
+    /// Set the account receiving the create pool fee, it follows the owner
+    /// through `accept_admin` unless set elsewhere.
+    /// Must be called by the amm config owner
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `_index`- The amm config index
+    /// * `receiver`- The new receiver of the create pool fee
+    ///
+    pub fn set_create_pool_fee_receiver(
+        ctx: Context<SetCreatePoolFeeReceiver>,
+        _index: u16,
+        receiver: Pubkey,
+    ) -> Result<()> {
+        instructions::process_set_create_pool_fee_receiver(ctx, receiver)
+    }
+
     /// Update pool status for given vaule
     ///
     /// # Arguments
@@ -89,6 +280,69 @@ pub mod cpmm {
         instructions::process_update_pool_status(ctx, status)
     }
 
+    /// Collect the protocol fee accrued to the pool, signed by the amm config owner
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The context of accounts
+    /// * `amount_0_requested` - The maximum amount of token_0 to send, can be 0 to collect fees in only token_1
+    /// * `amount_1_requested` - The maximum amount of token_1 to send, can be 0 to collect fees in only token_0
+    ///
+    pub fn collect_protocol_fee(
+        ctx: Context<CollectFee>,
+        amount_0_requested: u64,
+        amount_1_requested: u64,
+    ) -> Result<()> {
+        instructions::process_collect_fee(
+            ctx,
+            PoolFeeType::Protocol,
+            amount_0_requested,
+            amount_1_requested,
+        )
+    }
+
+    /// Collect the fund fee accrued to the pool, signed by the amm config owner
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The context of accounts
+    /// * `amount_0_requested` - The maximum amount of token_0 to send, can be 0 to collect fees in only token_1
+    /// * `amount_1_requested` - The maximum amount of token_1 to send, can be 0 to collect fees in only token_0
+    ///
+    pub fn collect_fund_fee(
+        ctx: Context<CollectFee>,
+        amount_0_requested: u64,
+        amount_1_requested: u64,
+    ) -> Result<()> {
+        instructions::process_collect_fee(
+            ctx,
+            PoolFeeType::Fund,
+            amount_0_requested,
+            amount_1_requested,
+        )
+    }
+
+    /// Collect the creator fee accrued to the pool, signed by the pool creator
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The context of accounts
+    /// * `amount_0_requested` - The maximum amount of token_0 to send, can be 0 to collect fees in only token_1
+    /// * `amount_1_requested` - The maximum amount of token_1 to send, can be 0 to collect fees in only token_0
+    ///
+    pub fn collect_creator_fee(
+        ctx: Context<CollectFee>,
+        amount_0_requested: u64,
+        amount_1_requested: u64,
+    ) -> Result<()> {
+        instructions::process_collect_fee(
+            ctx,
+            PoolFeeType::Creator,
+            amount_0_requested,
+            amount_1_requested,
+        )
+    }
+
     /// Creates a pool for the given token pair and the initial price
     ///
     /// # Arguments
@@ -98,15 +352,33 @@ pub mod cpmm {
     /// * `init_amount_0` - the initial amount_0 to deposit
     /// * `init_amount_1` - the initial amount_1 to deposit
     /// * `open_time` - the timestamp allowed for swap
+    /// * `curve_type` - the curve of the pool, 0: constant product, 1: stable, 2: weighted
+    /// * `curve_params` - the params of the curve, see `CurveType`
+    /// * `creator_fee_rate` - the share of the trade fee paid to the creator, at most the max creator fee rate of the config
+    /// * `launch_fee` - an optional trade fee decaying to the config trade fee after open time, at most the max launch fee rate and duration of the config
     ///
+    #[allow(clippy::too_many_arguments)]
     pub fn initialize(
         ctx: Context<Initialize>,
         _index: u16,
         init_amount_0: u64,
         init_amount_1: u64,
         open_time: u64,
+        curve_type: u8,
+        curve_params: [u64; 2],
+        creator_fee_rate: u64,
+        launch_fee: Option<LaunchFeeParams>,
     ) -> Result<()> {
-        instructions::process_initialize(ctx, init_amount_0, init_amount_1, open_time)
+        instructions::process_initialize(
+            ctx,
+            init_amount_0,
+            init_amount_1,
+            open_time,
+            curve_type,
+            curve_params,
+            creator_fee_rate,
+            launch_fee,
+        )
     }
 
     /// deposit liquidity token into the pool
@@ -130,9 +402,83 @@ pub mod cpmm {
             lp_token_amount,
             maximum_token_0_amount,
             maximum_token_1_amount,
+            None,
+        )
+    }
+
+    /// deposit liquidity token into the pool, failing after a deadline
+    ///
+    /// # Arguments
+    /// * `ctx`- The context of accounts
+    /// * `_index` - amm config index
+    /// * `lp_token_amount` - the lp_token amount_0 to deposit
+    /// * `maximum_token_0_amount` - Maximum token 0 amount to deposit, prevents excessive slippage
+    /// * `maximum_token_1_amount` - Maximum token 1 amount to deposit, prevents excessive slippage
+    /// * `deadline` - Unix timestamp after which the deposit fails
+    ///
+    pub fn deposit_with_deadline(
+        ctx: Context<Deposit>,
+        _index: u16,
+        lp_token_amount: u64,
+        maximum_token_0_amount: u64,
+        maximum_token_1_amount: u64,
+        deadline: i64,
+    ) -> Result<()> {
+        instructions::process_deposit(
+            ctx,
+            lp_token_amount,
+            maximum_token_0_amount,
+            maximum_token_1_amount,
+            Some(deadline),
+        )
+    }
+
+    /// deposit the largest lp amount that the given token amounts can pay for
+    ///
+    /// # Arguments
+    /// * `ctx`- The context of accounts
+    /// * `_index` - amm config index
+    /// * `maximum_token_0_amount` - Maximum token 0 amount to deposit
+    /// * `maximum_token_1_amount` - Maximum token 1 amount to deposit
+    /// * `minimum_lp_out` - Minimum lp token amount to mint, prevents excessive slippage
+    /// * `deadline` - Optional unix timestamp after which the deposit fails
+    ///
+    pub fn deposit_by_token_amounts(
+        ctx: Context<Deposit>,
+        _index: u16,
+        maximum_token_0_amount: u64,
+        maximum_token_1_amount: u64,
+        minimum_lp_out: u64,
+        deadline: Option<i64>,
+    ) -> Result<()> {
+        instructions::process_deposit_by_token_amounts(
+            ctx,
+            maximum_token_0_amount,
+            maximum_token_1_amount,
+            minimum_lp_out,
+            deadline,
         )
     }
 
+    /// deposit only one of the pool tokens, part of it is swapped into the other side
+    ///
+    /// # Arguments
+    /// * `ctx`- The context of accounts
+    /// * `_index` - amm config index
+    /// * `amount_in` - the amount of the input token to deposit
+    /// * `minimum_lp_out` - Minimum lp token amount to mint, prevents excessive slippage
+    /// * `deadline` - Optional unix timestamp after which the deposit fails
+    ///
+    pub fn deposit_single_token(
+        ctx: Context<DepositSingleToken>,
+        _index: u16,
+        amount_in: u64,
+        minimum_lp_out: u64,
+        deadline: Option<i64>,
+    ) -> Result<()> {
+        instructions::process_deposit_single_token(ctx, amount_in, minimum_lp_out, deadline)
+    }
+
     /// Withdraw lp for token0 ande token1
     ///
     /// # Arguments
@@ -155,6 +501,60 @@ pub mod cpmm {
             lp_token_amount,
             minimum_token_0_amount,
             minimum_token_1_amount,
+            None,
+        )
+    }
+
+    /// Withdraw lp for token0 ande token1, failing after a deadline
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `_index` - amm config index
+    /// * `lp_token_amount` - Amount of pool tokens to burn
+    /// * `minimum_token_0_amount` -  Minimum amount of token 0 to receive, prevents excessive slippage
+    /// * `minimum_token_1_amount` -  Minimum amount of token 1 to receive, prevents excessive slippage
+    /// * `deadline` - Unix timestamp after which the withdraw fails
+    ///
+    pub fn withdraw_with_deadline(
+        ctx: Context<Withdraw>,
+        _index: u16,
+        lp_token_amount: u64,
+        minimum_token_0_amount: u64,
+        minimum_token_1_amount: u64,
+        deadline: i64,
+    ) -> Result<()> {
+        instructions::process_withdraw(
+            ctx,
+            lp_token_amount,
+            minimum_token_0_amount,
+            minimum_token_1_amount,
+            Some(deadline),
+        )
+    }
+
+    /// Withdraw lp into one of the pool tokens, the other side is swapped into it
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `_index` - amm config index
+    /// * `lp_token_amount` - Amount of pool tokens to burn
+    /// * `minimum_amount_out` - Minimum amount of the output token to receive, prevents excessive slippage
+    /// * `deadline` - Optional unix timestamp after which the withdraw fails
+    ///
+    pub fn withdraw_single_token(
+        ctx: Context<WithdrawSingleToken>,
+        _index: u16,
+        lp_token_amount: u64,
+        minimum_amount_out: u64,
+        deadline: Option<i64>,
+    ) -> Result<()> {
+        instructions::process_withdraw_single_token(
+            ctx,
+            lp_token_amount,
+            minimum_amount_out,
+            deadline,
         )
     }
 
@@ -173,7 +573,27 @@ pub mod cpmm {
         amount_in: u64,
         minimum_amount_out: u64,
     ) -> Result<()> {
-        instructions::process_swap_base_input(ctx, amount_in, minimum_amount_out)
+        instructions::process_swap_base_input(ctx, amount_in, minimum_amount_out, None)
+    }
+
+    /// Swap the tokens in the pool base input amount, failing after a deadline
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `_index` - amm config index
+    /// * `amount_in` -  input amount to transfer, output to DESTINATION is based on the exchange rate
+    /// * `minimum_amount_out` -  Minimum amount of output token, prevents excessive slippage
+    /// * `deadline` - Unix timestamp after which the swap fails
+    ///
+    pub fn swap_base_input_with_deadline(
+        ctx: Context<Swap>,
+        _index: u16,
+        amount_in: u64,
+        minimum_amount_out: u64,
+        deadline: i64,
+    ) -> Result<()> {
+        instructions::process_swap_base_input(ctx, amount_in, minimum_amount_out, Some(deadline))
     }
 
     /// Swap the tokens in the pool base output amount
@@ -191,6 +611,176 @@ pub mod cpmm {
         amount_out: u64,
         max_amount_in: u64,
     ) -> Result<()> {
-        instructions::process_swap_base_output(ctx, amount_out, max_amount_in)
+        instructions::process_swap_base_output(ctx, amount_out, max_amount_in, None)
+    }
+
+    /// Swap the tokens in the pool base output amount, failing after a deadline
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `_index` - amm config index
+    /// * `amount_out` -  amount of output token
+    /// * `max_amount_in` -  input amount prevents excessive slippage
+    /// * `deadline` - Unix timestamp after which the swap fails
+    ///
+    pub fn swap_base_output_with_deadline(
+        ctx: Context<Swap>,
+        _index: u16,
+        amount_out: u64,
+        max_amount_in: u64,
+        deadline: i64,
+    ) -> Result<()> {
+        instructions::process_swap_base_output(ctx, amount_out, max_amount_in, Some(deadline))
+    }
+
+    /// Swap the tokens in the pool base input amount, paying the output to a
+    /// token account owned by the recipient instead of the payer's ATA
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `_index` - amm config index
+    /// * `amount_in` -  input amount to transfer, output to the recipient is based on the exchange rate
+    /// * `minimum_amount_out` -  Minimum amount of output token, prevents excessive slippage
+    /// * `deadline` - Optional unix timestamp after which the swap fails
+    ///
+    pub fn swap_base_input_to_recipient(
+        ctx: Context<SwapToRecipient>,
+        _index: u16,
+        amount_in: u64,
+        minimum_amount_out: u64,
+        deadline: Option<i64>,
+    ) -> Result<()> {
+        instructions::process_swap_base_input_to_recipient(
+            ctx,
+            amount_in,
+            minimum_amount_out,
+            deadline,
+        )
+    }
+
+    /// Swap the tokens in the pool base output amount, paying the output to a
+    /// token account owned by the recipient instead of the payer's ATA
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `_index` - amm config index
+    /// * `amount_out` -  amount of output token the recipient receives
+    /// * `max_amount_in` -  Maximum amount of input token to pay, prevents excessive slippage
+    /// * `deadline` - Optional unix timestamp after which the swap fails
+    ///
+    pub fn swap_base_output_to_recipient(
+        ctx: Context<SwapToRecipient>,
+        _index: u16,
+        amount_out: u64,
+        max_amount_in: u64,
+        deadline: Option<i64>,
+    ) -> Result<()> {
+        instructions::process_swap_base_output_to_recipient(
+            ctx,
+            amount_out,
+            max_amount_in,
+            deadline,
+        )
+    }
+
+    /// Swap the input amount through an ordered list of pools, the output of
+    /// each pool is paid straight into the input vault of the next one
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts, `remaining_accounts` hold `ROUTE_HOP_ACCOUNTS` accounts per pool
+    /// * `amount_in` -  input amount to transfer into the first pool
+    /// * `minimum_amount_out` -  Minimum amount of the output token to receive, prevents excessive slippage
+    /// * `deadline` - Optional unix timestamp after which the swap fails
+    ///
+    pub fn swap_route<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SwapRoute<'info>>,
+        amount_in: u64,
+        minimum_amount_out: u64,
+        deadline: Option<i64>,
+    ) -> Result<()> {
+        instructions::process_swap_route(ctx, amount_in, minimum_amount_out, deadline)
+    }
+
+    /// Lend tokens from the pool vaults to the receiver program, which must pay
+    /// them back with the flash loan fee before the instruction ends. The pool
+    /// is locked against swaps, deposits and withdrawals meanwhile.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts, `remaining_accounts` are passed on to the receiver program
+    /// * `_index` - amm config index
+    /// * `amount_0` - the amount of token_0 to borrow
+    /// * `amount_1` - the amount of token_1 to borrow
+    /// * `data` - the instruction data for the receiver program
+    ///
+    pub fn flash_loan<'info>(
+        ctx: Context<'_, '_, '_, 'info, FlashLoan<'info>>,
+        _index: u16,
+        amount_0: u64,
+        amount_1: u64,
+        data: Vec<u8>,
+    ) -> Result<()> {
+        instructions::process_flash_loan(ctx, amount_0, amount_1, data)
+    }
+
+    /// Send `amount_out` of the output token first, then invoke the receiver
+    /// program, which must pay enough input token into the input vault for the
+    /// curve invariant to hold once the trade fee is charged.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts, `remaining_accounts` are passed on to the receiver program
+    /// * `_index` - amm config index
+    /// * `amount_out` - the amount of the output token to send
+    /// * `max_amount_in` - Maximum amount of input token the input vault may receive, prevents excessive slippage
+    /// * `deadline` - Optional unix timestamp after which the flash swap fails
+    /// * `data` - the instruction data for the receiver program
+    ///
+    pub fn flash_swap<'info>(
+        ctx: Context<'_, '_, '_, 'info, FlashSwap<'info>>,
+        _index: u16,
+        amount_out: u64,
+        max_amount_in: u64,
+        deadline: Option<i64>,
+        data: Vec<u8>,
+    ) -> Result<()> {
+        instructions::process_flash_swap(ctx, amount_out, max_amount_in, deadline, data)
+    }
+
+    /// Set the pool reserves to the vault balances, excluding the protocol and
+    /// fund fees, adopting any tokens sent to the vaults directly
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    ///
+    pub fn sync(ctx: Context<SyncReserves>) -> Result<()> {
+        instructions::process_sync(ctx)
+    }
+
+    /// Send the vault balances above the pool reserves and the protocol and
+    /// fund fees to the recipient token accounts
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    ///
+    pub fn skim(ctx: Context<Skim>) -> Result<()> {
+        instructions::process_skim(ctx)
+    }
+
+    /// Create the observation account of a pool created before observations
+    /// were recorded, swaps on such a pool need it. Anyone can pay for it
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    ///
+    pub fn init_observation(ctx: Context<InitObservation>) -> Result<()> {
+        instructions::process_init_observation(ctx)
     }
 }
@@ -1,10 +1,20 @@
 use anchor_lang::prelude::*;
 
 pub const AMM_CONFIG_SEED: &str = "amm_config";
+/// Default delay before a queued config update can be applied, one day, and
+/// the shortest delay a config can be updated to
+pub const DEFAULT_CONFIG_UPDATE_DELAY: u64 = 24 * 60 * 60;
+/// Default lp amount locked when a pool is created
+pub const DEFAULT_LOCK_LP_AMOUNT: u64 = 100;
+/// Most lp amount that can be locked when a pool is created, the floor of a
+/// pool of two 18 decimal tokens
+pub const MAX_LOCK_LP_AMOUNT: u64 = 1_000_000_000;
+/// Longest launch fee duration a config can allow, one day
+pub const LAUNCH_FEE_DURATION_LIMIT: u64 = 24 * 60 * 60;
 
 /// Holds the current owner of the factory
 #[account]
-#[derive(InitSpace)]
+#[derive(Default, InitSpace)]
 pub struct AmmConfig {
     /// Bump to identify PDA
     pub bump: u8,
@@ -14,4 +24,84 @@ pub struct AmmConfig {
     pub disable_create_pool: bool,
     /// The trade fee, denominated in hundredths of a bip (10^-6)
     pub trade_fee_rate: u64,
+    /// The protocol fee, as a share of the trade fee
+    pub protocol_fee_rate: u64,
+    /// The fund fee, as a share of the trade fee
+    pub fund_fee_rate: u64,
+    /// Address of the config owner
+    pub owner: Pubkey,
+    /// Address proposed as the next owner, must accept to take over the config
+    pub pending_owner: Pubkey,
+    /// Seconds a queued config update must wait before it can be applied
+    pub update_delay: u64,
+    /// The param of the queued config update, see `update_amm_config`
+    pub pending_param: u8,
+    /// The value of the queued config update
+    pub pending_value: u64,
+    /// The timestamp from which the queued update can be applied, 0 if nothing is queued
+    pub pending_effective_at: u64,
+    /// The flash loan fee, denominated in hundredths of a bip (10^-6)
+    pub flash_loan_fee_rate: u64,
+    /// The referral fee paid to the referrer of a swap, as a share of the trade fee
+    pub referral_fee_rate: u64,
+    /// The lp amount locked forever when a pool is created, raised to a floor
+    /// derived from the decimals of the pool tokens
+    pub lock_lp_amount: u64,
+    /// The lamports charged to the creator of a pool
+    pub create_pool_fee: u64,
+    /// The address receiving the pool creation fee
+    pub create_pool_fee_receiver: Pubkey,
+    /// The highest creator fee rate a pool creator can choose, as a share of the trade fee
+    pub max_creator_fee_rate: u64,
+    /// The highest launch fee rate a pool creator can choose, denominated in hundredths of a bip (10^-6)
+    pub max_launch_fee_rate: u64,
+    /// The longest launch fee duration a pool creator can choose, in seconds
+    pub max_launch_fee_duration: u64,
+    /// padding for future updates
+    pub padding: [u64; 14],
+}
+
+impl AmmConfig {
+    /// Fill the fields added after the first layout, which read as zero once
+    /// the account is resized, with the values `create_amm_config` sets
+    pub fn migrate(&mut self, owner: Pubkey) {
+        self.owner = owner;
+        self.pending_owner = Pubkey::default();
+        self.update_delay = DEFAULT_CONFIG_UPDATE_DELAY;
+        self.lock_lp_amount = DEFAULT_LOCK_LP_AMOUNT;
+        self.create_pool_fee_receiver = owner;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants;
+    use anchor_lang::Discriminator;
+
+    #[test]
+    fn legacy_config_migrates_to_the_current_layout() {
+        // discriminator, bump, index, disable_create_pool, trade_fee_rate
+        let mut data = AmmConfig::DISCRIMINATOR.to_vec();
+        data.push(254);
+        data.extend_from_slice(&3u16.to_le_bytes());
+        data.push(1);
+        data.extend_from_slice(&2_500u64.to_le_bytes());
+        assert_eq!(data.len(), 20);
+        assert!(AmmConfig::try_deserialize(&mut &data[..]).is_err());
+
+        data.resize(constants::DISCRIMINATOR + AmmConfig::INIT_SPACE, 0);
+        let mut amm_config = AmmConfig::try_deserialize(&mut &data[..]).unwrap();
+        let owner = Pubkey::new_unique();
+        amm_config.migrate(owner);
+        assert_eq!(amm_config.bump, 254);
+        assert_eq!(amm_config.index, 3);
+        assert!(amm_config.disable_create_pool);
+        assert_eq!(amm_config.trade_fee_rate, 2_500);
+        assert_eq!(amm_config.owner, owner);
+        assert_eq!(amm_config.create_pool_fee_receiver, owner);
+        assert_eq!(amm_config.update_delay, DEFAULT_CONFIG_UPDATE_DELAY);
+        assert_eq!(amm_config.lock_lp_amount, DEFAULT_LOCK_LP_AMOUNT);
+        assert_eq!(amm_config.max_creator_fee_rate, 0);
+    }
 }
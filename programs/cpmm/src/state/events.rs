@@ -0,0 +1,243 @@
+use crate::curve::TradeDirection;
+use crate::state::PoolFeeType;
+use anchor_lang::prelude::*;
+
+/// Emit an event through `emit!`, or through a self CPI with `emit_cpi!` when
+/// the `event-cpi` feature is enabled, so indexers can read it from inner
+/// instructions instead of truncatable program logs.
+#[macro_export]
+macro_rules! emit_event {
+    ($ctx:ident, $event:expr) => {{
+        let event = $event;
+        #[cfg(feature = "event-cpi")]
+        {
+            let ctx = &$ctx;
+            anchor_lang::prelude::emit_cpi!(event);
+        }
+        #[cfg(not(feature = "event-cpi"))]
+        anchor_lang::prelude::emit!(event);
+    }};
+}
+
+/// Emitted when a pool is created
+#[event]
+pub struct PoolCreated {
+    pub pool_id: Pubkey,
+    pub amm_config: Pubkey,
+    pub pool_creator: Pubkey,
+    pub token_0_mint: Pubkey,
+    pub token_1_mint: Pubkey,
+    pub lp_mint: Pubkey,
+    /// The initial amounts in the vaults
+    pub token_0_amount: u64,
+    pub token_1_amount: u64,
+    /// The initial lp supply, including the locked amount
+    pub lp_supply: u64,
+    pub open_time: u64,
+}
+
+/// Emitted when deposit and withdraw
+#[event]
+pub struct LpChanged {
+    pub pool_id: Pubkey,
+    pub lp_amount_before: u64,
+    /// pool vault amounts before the change, excluding protocol and fund fees
+    pub token_0_vault_before: u64,
+    pub token_1_vault_before: u64,
+    /// pool vault amounts after the change, excluding protocol and fund fees
+    pub token_0_vault_after: u64,
+    pub token_1_vault_after: u64,
+    /// the amounts deposited into or withdrawn from the vaults
+    pub token_0_amount: u64,
+    pub token_1_amount: u64,
+    /// the token-2022 transfer fees paid on top of the amounts above
+    pub transfer_fee_0: u64,
+    pub transfer_fee_1: u64,
+    /// the lp amount minted or burned
+    pub lp_amount: u64,
+    pub change_type: LpChangeType,
+}
+
+/// Whether liquidity is added to or removed from a pool
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LpChangeType {
+    Deposit,
+    Withdraw,
+}
+
+/// Emitted when swap
+#[event]
+pub struct SwapEvent {
+    pub pool_id: Pubkey,
+    pub trade_direction: TradeDirection,
+    /// pool vault amounts before the swap, excluding protocol, fund and creator fees
+    pub input_vault_before: u64,
+    pub output_vault_before: u64,
+    /// pool vault amounts after the swap, excluding protocol, fund and creator fees
+    pub input_vault_after: u64,
+    pub output_vault_after: u64,
+    /// The amounts received by and sent from the vaults
+    pub input_amount: u64,
+    pub output_amount: u64,
+    /// The token-2022 transfer fees charged on the input and output transfers
+    pub input_transfer_fee: u64,
+    pub output_transfer_fee: u64,
+    /// Fees charged on the input token
+    pub trade_fee: u64,
+    pub protocol_fee: u64,
+    pub fund_fee: u64,
+    /// The share of the trade fee paid to the referrer, 0 without a referrer
+    pub referral_fee: u64,
+    /// The share of the trade fee owed to the pool creator
+    pub creator_fee: u64,
+    /// true for `swap_base_input`, false for `swap_base_output` and `flash_swap`
+    pub base_input: bool,
+}
+
+/// Emitted when the protocol admin is appointed
+#[event]
+pub struct ProtocolAdminInitialized {
+    pub protocol_admin: Pubkey,
+    pub admin: Pubkey,
+}
+
+/// Emitted when an amm config is created
+#[event]
+pub struct AmmConfigCreated {
+    pub amm_config: Pubkey,
+    pub owner: Pubkey,
+    pub index: u16,
+    pub trade_fee_rate: u64,
+    pub protocol_fee_rate: u64,
+    pub fund_fee_rate: u64,
+    pub create_pool_fee: u64,
+}
+
+/// Emitted when an amm config in the layout before the owner fields is resized
+#[event]
+pub struct AmmConfigMigrated {
+    pub amm_config: Pubkey,
+    pub owner: Pubkey,
+    pub index: u16,
+}
+
+/// Emitted when a change of the amm config is queued
+#[event]
+pub struct ConfigUpdateQueued {
+    pub amm_config: Pubkey,
+    /// The queued param, see `update_amm_config`
+    pub param: u8,
+    pub value: u64,
+    /// The timestamp from which the change can be applied
+    pub effective_at: u64,
+}
+
+/// Emitted when a queued change of the amm config is cancelled
+#[event]
+pub struct ConfigUpdateCancelled {
+    pub amm_config: Pubkey,
+    pub param: u8,
+    pub value: u64,
+}
+
+/// Emitted when a new owner of an amm config or a new protocol admin is proposed
+#[event]
+pub struct AdminTransferProposed {
+    /// The amm config or protocol admin account
+    pub account: Pubkey,
+    pub admin: Pubkey,
+    pub pending_admin: Pubkey,
+}
+
+/// Emitted when the proposed owner of an amm config or protocol admin takes over
+#[event]
+pub struct AdminTransferAccepted {
+    /// The amm config or protocol admin account
+    pub account: Pubkey,
+    pub admin_before: Pubkey,
+    pub admin: Pubkey,
+}
+
+/// Emitted when a pending owner transfer of an amm config or protocol admin is cancelled
+#[event]
+pub struct AdminTransferCancelled {
+    /// The amm config or protocol admin account
+    pub account: Pubkey,
+    pub admin: Pubkey,
+    pub pending_admin: Pubkey,
+}
+
+/// Emitted when a param of the amm config is changed
+#[event]
+pub struct ConfigUpdated {
+    pub amm_config: Pubkey,
+    /// The changed param, see `update_amm_config`
+    pub param: u8,
+    pub value: u64,
+}
+
+/// Emitted when the account receiving the create pool fee is changed
+#[event]
+pub struct CreatePoolFeeReceiverUpdated {
+    pub amm_config: Pubkey,
+    pub receiver_before: Pubkey,
+    pub receiver: Pubkey,
+}
+
+/// Emitted when the status of a pool is changed
+#[event]
+pub struct PoolStatusChanged {
+    pub pool_id: Pubkey,
+    pub status_before: u8,
+    pub status: u8,
+}
+
+/// Emitted when a flash loan is repaid
+#[event]
+pub struct FlashLoanEvent {
+    pub pool_id: Pubkey,
+    pub receiver_program: Pubkey,
+    /// The amounts lent from the vaults
+    pub amount_0: u64,
+    pub amount_1: u64,
+    /// The fees paid on top of the amounts, credited to liquidity providers
+    pub fee_0: u64,
+    pub fee_1: u64,
+}
+
+/// Emitted when the reserves are synced to the vault balances
+#[event]
+pub struct ReservesSynced {
+    pub pool_id: Pubkey,
+    pub reserve_0_before: u64,
+    pub reserve_1_before: u64,
+    pub reserve_0: u64,
+    pub reserve_1: u64,
+}
+
+/// Emitted when the vault balances above the reserves are sent out
+#[event]
+pub struct Skimmed {
+    pub pool_id: Pubkey,
+    pub amount_0: u64,
+    pub amount_1: u64,
+}
+
+/// Emitted when a pool created before observations were recorded gets its
+/// observation account
+#[event]
+pub struct ObservationInitialized {
+    pub pool_id: Pubkey,
+    pub observation_state: Pubkey,
+}
+
+/// Emitted when the protocol, fund or creator fees of a pool are collected
+#[event]
+pub struct FeeCollected {
+    pub pool_id: Pubkey,
+    pub fee_type: PoolFeeType,
+    pub recipient_token_0_account: Pubkey,
+    pub recipient_token_1_account: Pubkey,
+    pub amount_0: u64,
+    pub amount_1: u64,
+}
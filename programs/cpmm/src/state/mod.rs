@@ -1,5 +1,14 @@
+pub mod protocol_admin;
+pub use protocol_admin::*;
+
 pub mod config;
 pub use config::*;
 
 pub mod pool;
 pub use pool::*;
+
+pub mod events;
+pub use events::*;
+
+pub mod oracle;
+pub use oracle::*;
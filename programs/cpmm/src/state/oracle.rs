@@ -0,0 +1,217 @@
+/// Oracle provides price data useful for a wide variety of system designs
+///
+use crate::error::ErrorCode;
+use anchor_lang::prelude::*;
+
+/// Seed to derive account address and signature
+pub const OBSERVATION_SEED: &str = "observation";
+// Number of ObservationState element
+pub const OBSERVATION_NUM: usize = 100;
+/// Minimum seconds between two observations
+pub const OBSERVATION_UPDATE_DURATION_DEFAULT: u64 = 15;
+
+/// The element of observations in ObservationState
+#[zero_copy(unsafe)]
+#[repr(C, packed)]
+#[derive(Default, Debug)]
+pub struct Observation {
+    /// The block timestamp of the observation
+    pub block_timestamp: u64,
+    /// the cumulative of token0 price during the duration time, Q32.32, the remaining 64 bit for overflow
+    pub cumulative_token_0_price_x32: u128,
+    /// the cumulative of token1 price during the duration time, Q32.32, the remaining 64 bit for overflow
+    pub cumulative_token_1_price_x32: u128,
+}
+
+#[account(zero_copy(unsafe))]
+#[repr(C, packed)]
+pub struct ObservationState {
+    /// Whether the ObservationState is initialized
+    pub initialized: bool,
+    /// the most-recently updated index of the observations array
+    pub observation_index: u16,
+    pub pool_id: Pubkey,
+    /// observation array
+    pub observations: [Observation; OBSERVATION_NUM],
+    /// padding for feature update
+    pub padding: [u64; 4],
+}
+
+impl Default for ObservationState {
+    #[inline]
+    fn default() -> ObservationState {
+        ObservationState {
+            initialized: false,
+            observation_index: 0,
+            pool_id: Pubkey::default(),
+            observations: [Observation::default(); OBSERVATION_NUM],
+            padding: [0u64; 4],
+        }
+    }
+}
+
+impl ObservationState {
+    pub const LEN: usize = 8 + 1 + 2 + 32 + (8 + 16 + 16) * OBSERVATION_NUM + 8 * 4;
+
+    // Writes an oracle observation to the account
+    ///
+    /// # Arguments
+    ///
+    /// * `block_timestamp` - The current timestamp of to update
+    /// * `token_0_price_x32` - The token_0_price_x32 price before the swap
+    /// * `token_1_price_x32` - The token_1_price_x32 price before the swap
+    ///
+    pub fn update(
+        &mut self,
+        block_timestamp: u64,
+        token_0_price_x32: u128,
+        token_1_price_x32: u128,
+    ) {
+        let observation_index = self.observation_index;
+        if !self.initialized {
+            // skip the pool init price
+            self.initialized = true;
+            self.observations[observation_index as usize].block_timestamp = block_timestamp;
+            self.observations[observation_index as usize].cumulative_token_0_price_x32 = 0;
+            self.observations[observation_index as usize].cumulative_token_1_price_x32 = 0;
+        } else {
+            let last_observation = self.observations[observation_index as usize];
+            let delta_time = block_timestamp.saturating_sub(last_observation.block_timestamp);
+            if delta_time < OBSERVATION_UPDATE_DURATION_DEFAULT {
+                return;
+            }
+            let delta_token_0_price_x32 = token_0_price_x32.checked_mul(delta_time.into()).unwrap();
+            let delta_token_1_price_x32 = token_1_price_x32.checked_mul(delta_time.into()).unwrap();
+            let next_observation_index = if observation_index as usize == OBSERVATION_NUM - 1 {
+                0
+            } else {
+                observation_index + 1
+            };
+            self.observations[next_observation_index as usize].block_timestamp = block_timestamp;
+            // cumulative_token_price_x32 only occupies the first 64 bits, and the remaining 64 bits are used to store overflow data
+            self.observations[next_observation_index as usize].cumulative_token_0_price_x32 =
+                last_observation
+                    .cumulative_token_0_price_x32
+                    .wrapping_add(delta_token_0_price_x32);
+            self.observations[next_observation_index as usize].cumulative_token_1_price_x32 =
+                last_observation
+                    .cumulative_token_1_price_x32
+                    .wrapping_add(delta_token_1_price_x32);
+            self.observation_index = next_observation_index;
+        }
+    }
+
+    /// Get the time weighted average prices of token_0 and token_1 over at least
+    /// `window` seconds before `block_timestamp`.
+    ///
+    /// # Arguments
+    ///
+    /// * `block_timestamp` - The current timestamp
+    /// * `token_0_price_x32` - The current token_0 price, extends the latest observation to now
+    /// * `token_1_price_x32` - The current token_1 price, extends the latest observation to now
+    /// * `window` - The number of seconds to average over
+    ///
+    pub fn twap(
+        &self,
+        block_timestamp: u64,
+        token_0_price_x32: u128,
+        token_1_price_x32: u128,
+        window: u64,
+    ) -> Result<(u128, u128)> {
+        require!(self.initialized && window > 0, ErrorCode::ObservationNotEnough);
+        let target_timestamp = block_timestamp
+            .checked_sub(window)
+            .ok_or(ErrorCode::ObservationNotEnough)?;
+
+        let last_observation = self.observations[self.observation_index as usize];
+        let last_block_timestamp = last_observation.block_timestamp;
+        let delta_time = block_timestamp.saturating_sub(last_block_timestamp);
+        let current_token_0_cumulative = last_observation
+            .cumulative_token_0_price_x32
+            .wrapping_add(token_0_price_x32.checked_mul(delta_time.into()).unwrap());
+        let current_token_1_cumulative = last_observation
+            .cumulative_token_1_price_x32
+            .wrapping_add(token_1_price_x32.checked_mul(delta_time.into()).unwrap());
+
+        // walk back from the latest observation to the newest one at or before the window start
+        let mut index = self.observation_index as usize;
+        for _ in 0..OBSERVATION_NUM {
+            let observation = self.observations[index];
+            let observation_timestamp = observation.block_timestamp;
+            if observation_timestamp == 0 {
+                break;
+            }
+            if observation_timestamp <= target_timestamp {
+                let elapsed = u128::from(block_timestamp - observation_timestamp);
+                return Ok((
+                    current_token_0_cumulative
+                        .wrapping_sub(observation.cumulative_token_0_price_x32)
+                        / elapsed,
+                    current_token_1_cumulative
+                        .wrapping_sub(observation.cumulative_token_1_price_x32)
+                        / elapsed,
+                ));
+            }
+            index = if index == 0 { OBSERVATION_NUM - 1 } else { index - 1 };
+        }
+        err!(ErrorCode::ObservationNotEnough)
+    }
+}
+
+#[cfg(test)]
+pub mod observation_test {
+    use super::*;
+
+    #[test]
+    fn update_skips_short_durations() {
+        let mut state = ObservationState::default();
+        state.update(1000, 1 << 32, 1 << 32);
+        state.update(1000 + OBSERVATION_UPDATE_DURATION_DEFAULT - 1, 2 << 32, 2 << 32);
+        assert_eq!({ state.observation_index }, 0);
+
+        state.update(1000 + OBSERVATION_UPDATE_DURATION_DEFAULT, 2 << 32, 2 << 32);
+        assert_eq!({ state.observation_index }, 1);
+        let observation = state.observations[1];
+        assert_eq!(
+            { observation.cumulative_token_0_price_x32 },
+            (2u128 << 32) * OBSERVATION_UPDATE_DURATION_DEFAULT as u128
+        );
+    }
+
+    #[test]
+    fn update_wraps_around_ring_buffer() {
+        let mut state = ObservationState::default();
+        let mut block_timestamp = 1000;
+        for _ in 0..=OBSERVATION_NUM {
+            state.update(block_timestamp, 1 << 32, 1 << 32);
+            block_timestamp += OBSERVATION_UPDATE_DURATION_DEFAULT;
+        }
+        assert_eq!({ state.observation_index }, 0);
+    }
+
+    #[test]
+    fn twap_over_window() {
+        let mut state = ObservationState::default();
+        // price of token_0 is 1 for 100 seconds, then 3 for 100 seconds
+        state.update(1000, 1 << 32, 1 << 32);
+        state.update(1100, 1 << 32, 1 << 32);
+        state.update(1200, 3 << 32, 3 << 32);
+
+        let (token_0_twap, _) = state.twap(1200, 3 << 32, 3 << 32, 200).unwrap();
+        assert_eq!(token_0_twap, 2 << 32);
+
+        // the current price extends the latest observation
+        let (token_0_twap, _) = state.twap(1300, 3 << 32, 3 << 32, 100).unwrap();
+        assert_eq!(token_0_twap, 3 << 32);
+    }
+
+    #[test]
+    fn twap_requires_enough_history() {
+        let mut state = ObservationState::default();
+        assert!(state.twap(1000, 1 << 32, 1 << 32, 100).is_err());
+
+        state.update(1000, 1 << 32, 1 << 32);
+        assert!(state.twap(1050, 1 << 32, 1 << 32, 100).is_err());
+        assert!(state.twap(1100, 1 << 32, 1 << 32, 100).is_ok());
+    }
+}
@@ -1,3 +1,9 @@
+use crate::curve::{
+    fees::{Fees, FEE_RATE_DENOMINATOR_VALUE},
+    CurveCalculator, CurveType, TradeDirection,
+};
+use crate::error::ErrorCode;
+use crate::state::AmmConfig;
 use anchor_lang::prelude::*;
 use anchor_spl::token_interface::Mint;
 use std::ops::{BitAnd, BitOr, BitXor};
@@ -5,15 +11,72 @@ use std::ops::{BitAnd, BitOr, BitXor};
 pub const POOL_SEED: &str = "pool";
 pub const POOL_LP_MINT_SEED: &str = "pool_lp_mint";
 pub const POOL_VAULT_SEED: &str = "pool_vault";
+pub const POOL_LP_LOCK_SEED: &str = "pool_lp_lock";
 
 pub const Q32: u128 = (u32::MAX as u128) + 1; // 2^32
 
+/// The launch fee surplus falls linearly to zero over the launch fee duration
+pub const LAUNCH_FEE_DECAY_LINEAR: u8 = 0;
+/// The launch fee surplus halves `LAUNCH_FEE_HALVINGS` times over the launch fee duration
+pub const LAUNCH_FEE_DECAY_EXPONENTIAL: u8 = 1;
+pub const LAUNCH_FEE_HALVINGS: u64 = 8;
+
+/// Optional fee schedule charged right after `open_time` to deter snipers
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LaunchFeeParams {
+    /// The trade fee rate at `open_time`, denominated in hundredths of a bip (10^-6)
+    pub fee_rate: u64,
+    /// Seconds after `open_time` until the trade fee rate is back to the config one
+    pub duration: u64,
+    /// `LAUNCH_FEE_DECAY_LINEAR` or `LAUNCH_FEE_DECAY_EXPONENTIAL`
+    pub decay: u8,
+    /// Pay the fee above the config trade fee to the creator instead of liquidity providers
+    pub surplus_to_creator: bool,
+}
+
+impl LaunchFeeParams {
+    /// Check the schedule, the fee rate and duration are capped by the config
+    /// so that a surplus paid to the creator stays bounded
+    pub fn validate(&self, amm_config: &AmmConfig) -> Result<()> {
+        require_gt!(FEE_RATE_DENOMINATOR_VALUE, self.fee_rate, ErrorCode::InvalidLaunchFee);
+        require!(
+            self.decay == LAUNCH_FEE_DECAY_LINEAR || self.decay == LAUNCH_FEE_DECAY_EXPONENTIAL,
+            ErrorCode::InvalidLaunchFee
+        );
+        require_gte!(amm_config.max_launch_fee_rate, self.fee_rate, ErrorCode::LaunchFeeTooHigh);
+        require_gte!(
+            amm_config.max_launch_fee_duration,
+            self.duration,
+            ErrorCode::LaunchFeeTooHigh
+        );
+        Ok(())
+    }
+}
+
+/// Orders a mint pair the same way `initialize` does (token_0_mint < token_1_mint),
+/// so that either trade direction resolves the same pool address.
+pub fn sorted_mints(mint_a: Pubkey, mint_b: Pubkey) -> (Pubkey, Pubkey) {
+    if mint_a < mint_b {
+        (mint_a, mint_b)
+    } else {
+        (mint_b, mint_a)
+    }
+}
+
 pub enum PoolStatusBitIndex {
     Deposit,
     Withdraw,
     Swap,
 }
 
+/// The fees accrued to a pool that are kept in the vaults until collected
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PoolFeeType {
+    Protocol,
+    Fund,
+    Creator,
+}
+
 #[derive(PartialEq, Eq)]
 pub enum PoolStatusBitFlag {
     Enable,
@@ -67,11 +130,52 @@ pub struct PoolState {
     pub open_time: u64,
     /// recent epoch
     pub recent_epoch: u64,
+
+    /// The amounts of token_0 and token_1 that are owed to the protocol
+    /// and kept in the vaults until collected.
+    pub protocol_fees_token_0: u64,
+    pub protocol_fees_token_1: u64,
+    /// The amounts of token_0 and token_1 that are owed to the fund
+    /// and kept in the vaults until collected.
+    pub fund_fees_token_0: u64,
+    pub fund_fees_token_1: u64,
+
+    /// observation account key
+    pub observation_key: Pubkey,
+
+    /// The curve of the pool, 0: constant product, 1: stable, 2: weighted
+    pub curve_type: u8,
+    /// Set while a flash loan is outstanding, swaps, deposits and withdrawals
+    /// are rejected until the loan is repaid
+    pub locked: bool,
+    pub _padding1: [u8; 6],
+    /// The params of the curve, see `CurveType`
+    pub curve_params: [u64; 2],
+    /// The token_0 and token_1 amounts owned by liquidity providers. They are
+    /// only changed by pool instructions, tokens sent to the vaults directly are
+    /// ignored until `sync` adopts them or `skim` sends them out
+    pub reserve_0: u64,
+    pub reserve_1: u64,
+    /// The creator fee, as a share of the trade fee, set at creation
+    pub creator_fee_rate: u64,
+    /// The amounts of token_0 and token_1 that are owed to the pool creator
+    /// and kept in the vaults until collected.
+    pub creator_fees_token_0: u64,
+    pub creator_fees_token_1: u64,
+    /// The trade fee rate at `open_time`, decaying to the config trade fee rate
+    /// over `launch_fee_duration` seconds, see `LaunchFeeParams`
+    pub launch_fee_rate: u64,
+    pub launch_fee_duration: u64,
+    pub launch_fee_decay: u8,
+    pub launch_fee_to_creator: bool,
+    pub _padding2: [u8; 6],
+
     /// padding for future updates
-    pub padding: [u64; 31],
+    pub padding: [u64; 12],
 }
 
 impl PoolState {
+    #[allow(clippy::too_many_arguments)]
     pub fn initialize(
         &mut self,
         auth_bump: u8,
@@ -84,6 +188,11 @@ impl PoolState {
         token_0_mint: &InterfaceAccount<Mint>,
         token_1_mint: &InterfaceAccount<Mint>,
         lp_mint: &InterfaceAccount<Mint>,
+        observation_key: Pubkey,
+        curve_type: u8,
+        curve_params: [u64; 2],
+        creator_fee_rate: u64,
+        launch_fee: LaunchFeeParams,
         bump: u8,
     ) {
         self.bump = bump;
@@ -96,7 +205,10 @@ impl PoolState {
         self.token_1_mint = token_1_mint.key();
         self.token_0_program = *token_0_mint.to_account_info().owner;
         self.token_1_program = *token_1_mint.to_account_info().owner;
-        // self.observation_key = observation_key;
+        self.observation_key = observation_key;
+        self.curve_type = curve_type;
+        self.curve_params = curve_params;
+        self.locked = false;
         self.auth_bump = auth_bump;
         self.lp_mint_decimals = lp_mint.decimals;
         self.mint_0_decimals = token_0_mint.decimals;
@@ -104,7 +216,20 @@ impl PoolState {
         self.lp_supply = lp_supply;
         self.open_time = open_time;
         self.recent_epoch = Clock::get().unwrap().epoch;
-        self.padding = [0u64; 31];
+        self.protocol_fees_token_0 = 0;
+        self.protocol_fees_token_1 = 0;
+        self.fund_fees_token_0 = 0;
+        self.fund_fees_token_1 = 0;
+        self.reserve_0 = 0;
+        self.reserve_1 = 0;
+        self.creator_fee_rate = creator_fee_rate;
+        self.creator_fees_token_0 = 0;
+        self.creator_fees_token_1 = 0;
+        self.launch_fee_rate = launch_fee.fee_rate;
+        self.launch_fee_duration = launch_fee.duration;
+        self.launch_fee_decay = launch_fee.decay;
+        self.launch_fee_to_creator = launch_fee.surplus_to_creator;
+        self.padding = [0u64; 12];
     }
 
     pub fn set_status(&mut self, status: u8) {
@@ -112,25 +237,277 @@ impl PoolState {
     }
 
     pub fn set_status_by_bit(&mut self, bit: PoolStatusBitIndex, flag: PoolStatusBitFlag) {
-        let s = u8::from(1) << (bit as u8);
+        let s = 1u8 << (bit as u8);
         if flag == PoolStatusBitFlag::Disable {
             self.status = self.status.bitor(s);
         } else {
-            let m = u8::from(255).bitxor(s);
+            let m = 255u8.bitxor(s);
             self.status = self.status.bitand(m);
         }
     }
 
     /// Get status by bit, if it is `noraml` status, return true
     pub fn get_status_by_bit(&self, bit: PoolStatusBitIndex) -> bool {
-        let status = u8::from(1) << (bit as u8);
+        let status = 1u8 << (bit as u8);
         self.status.bitand(status) == 0
     }
 
+    /// Get the token_0 and token_1 reserves the pool prices against
+    pub fn reserves(&self) -> (u64, u64) {
+        (self.reserve_0, self.reserve_1)
+    }
+
+    pub fn set_reserves(&mut self, reserve_0: u64, reserve_1: u64) {
+        self.reserve_0 = reserve_0;
+        self.reserve_1 = reserve_1;
+    }
+
+    /// Set the reserves from the input and output side of a trade
+    pub fn set_trade_reserves(
+        &mut self,
+        trade_direction: TradeDirection,
+        input_reserve: u64,
+        output_reserve: u64,
+    ) {
+        match trade_direction {
+            TradeDirection::ZeroForOne => self.set_reserves(input_reserve, output_reserve),
+            TradeDirection::OneForZero => self.set_reserves(output_reserve, input_reserve),
+        }
+    }
+
+    /// Get the vault amounts that belong to liquidity providers, excluding
+    /// the protocol, fund and creator fees that have not been collected yet.
+    pub fn vault_amount_without_fee(&self, vault_0: u64, vault_1: u64) -> Result<(u64, u64)> {
+        let fees_0 = self
+            .protocol_fees_token_0
+            .checked_add(self.fund_fees_token_0)
+            .and_then(|fees| fees.checked_add(self.creator_fees_token_0))
+            .ok_or(ErrorCode::MathOverflow)?;
+        let fees_1 = self
+            .protocol_fees_token_1
+            .checked_add(self.fund_fees_token_1)
+            .and_then(|fees| fees.checked_add(self.creator_fees_token_1))
+            .ok_or(ErrorCode::MathOverflow)?;
+        Ok((
+            vault_0
+                .checked_sub(fees_0)
+                .ok_or(ErrorCode::InsufficientVault)?,
+            vault_1
+                .checked_sub(fees_1)
+                .ok_or(ErrorCode::InsufficientVault)?,
+        ))
+    }
+
+    /// Pools created before the reserves were tracked hold liquidity with both
+    /// reserves at zero, they adopt the vault amounts on first use
+    pub fn sync_legacy_reserves(&mut self, vault_0: u64, vault_1: u64) -> Result<()> {
+        if self.reserve_0 == 0 && self.reserve_1 == 0 && self.lp_supply > 0 {
+            let (reserve_0, reserve_1) = self.vault_amount_without_fee(vault_0, vault_1)?;
+            self.set_reserves(reserve_0, reserve_1);
+        }
+        Ok(())
+    }
+
+    /// Sync the reserves of a legacy pool from the input and output vault of a trade
+    pub fn sync_legacy_trade_reserves(
+        &mut self,
+        trade_direction: TradeDirection,
+        input_vault: u64,
+        output_vault: u64,
+    ) -> Result<()> {
+        match trade_direction {
+            TradeDirection::ZeroForOne => self.sync_legacy_reserves(input_vault, output_vault),
+            TradeDirection::OneForZero => self.sync_legacy_reserves(output_vault, input_vault),
+        }
+    }
+
+    /// Get the vault amounts beyond the reserves and the uncollected fees,
+    /// a vault holding less than its reserve has nothing to skim
+    pub fn skim_amounts(&self, vault_0: u64, vault_1: u64) -> Result<(u64, u64)> {
+        let (vault_0, vault_1) = self.vault_amount_without_fee(vault_0, vault_1)?;
+        Ok((
+            vault_0.saturating_sub(self.reserve_0),
+            vault_1.saturating_sub(self.reserve_1),
+        ))
+    }
+
+    /// Unlock the pool once the vaults hold a flash loan back with its fee,
+    /// the fee belongs to liquidity providers and is credited to the reserves
+    pub fn settle_flash_loan(
+        &mut self,
+        vaults_before: (u64, u64),
+        vaults_after: (u64, u64),
+        fee_0: u64,
+        fee_1: u64,
+    ) -> Result<()> {
+        let repaid_0 = vaults_before.0.checked_add(fee_0).ok_or(ErrorCode::MathOverflow)?;
+        let repaid_1 = vaults_before.1.checked_add(fee_1).ok_or(ErrorCode::MathOverflow)?;
+        require!(
+            vaults_after.0 >= repaid_0 && vaults_after.1 >= repaid_1,
+            ErrorCode::FlashLoanNotRepaid
+        );
+        self.locked = false;
+        self.reserve_0 = self.reserve_0.checked_add(fee_0).ok_or(ErrorCode::MathOverflow)?;
+        self.reserve_1 = self.reserve_1.checked_add(fee_1).ok_or(ErrorCode::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Take up to the requested amounts out of the accrued fees of a type,
+    /// returns the amounts to send
+    pub fn take_fees(
+        &mut self,
+        fee_type: PoolFeeType,
+        amount_0_requested: u64,
+        amount_1_requested: u64,
+    ) -> (u64, u64) {
+        let (fees_0, fees_1) = match fee_type {
+            PoolFeeType::Protocol => (&mut self.protocol_fees_token_0, &mut self.protocol_fees_token_1),
+            PoolFeeType::Fund => (&mut self.fund_fees_token_0, &mut self.fund_fees_token_1),
+            PoolFeeType::Creator => (&mut self.creator_fees_token_0, &mut self.creator_fees_token_1),
+        };
+        let amount_0 = amount_0_requested.min(*fees_0);
+        let amount_1 = amount_1_requested.min(*fees_1);
+        *fees_0 -= amount_0;
+        *fees_1 -= amount_1;
+        (amount_0, amount_1)
+    }
+
+    /// Accrue the protocol and fund fees charged on the input token of a swap
+    pub fn accrue_fees(&mut self, trade_direction: TradeDirection, protocol_fee: u64, fund_fee: u64) {
+        match trade_direction {
+            TradeDirection::ZeroForOne => {
+                self.protocol_fees_token_0 = self.protocol_fees_token_0.checked_add(protocol_fee).unwrap();
+                self.fund_fees_token_0 = self.fund_fees_token_0.checked_add(fund_fee).unwrap();
+            }
+            TradeDirection::OneForZero => {
+                self.protocol_fees_token_1 = self.protocol_fees_token_1.checked_add(protocol_fee).unwrap();
+                self.fund_fees_token_1 = self.fund_fees_token_1.checked_add(fund_fee).unwrap();
+            }
+        }
+    }
+
+    /// Get the creator fee rate charged, the rate set at creation capped by the
+    /// current max creator fee rate of the config. The config keeps the max
+    /// and the other shares within the whole trade fee, so capping keeps the
+    /// shares of pools created before a config change within it too.
+    pub fn capped_creator_fee_rate(&self, amm_config: &AmmConfig) -> u64 {
+        self.creator_fee_rate.min(amm_config.max_creator_fee_rate)
+    }
+
+    /// Get the creator's share of the trade fee charged at `trade_fee_rate`:
+    /// the capped creator fee rate, plus the liquidity providers' share of the
+    /// launch fee surplus above the config trade fee when the pool pays it to
+    /// the creator. `referral_fee_rate` is the rate the swap pays, zero
+    /// without a referrer.
+    pub fn creator_share_rate(
+        &self,
+        amm_config: &AmmConfig,
+        trade_fee_rate: u64,
+        referral_fee_rate: u64,
+    ) -> Result<u64> {
+        let creator_fee_rate = self.capped_creator_fee_rate(amm_config);
+        if !self.launch_fee_to_creator || trade_fee_rate <= amm_config.trade_fee_rate {
+            return Ok(creator_fee_rate);
+        }
+        let lp_fee_rate = FEE_RATE_DENOMINATOR_VALUE
+            .checked_sub(amm_config.protocol_fee_rate)
+            .and_then(|rate| rate.checked_sub(amm_config.fund_fee_rate))
+            .and_then(|rate| rate.checked_sub(referral_fee_rate))
+            .and_then(|rate| rate.checked_sub(creator_fee_rate))
+            .ok_or(ErrorCode::MathOverflow)?;
+        let surplus_rate = u128::from(trade_fee_rate - amm_config.trade_fee_rate)
+            .checked_mul(u128::from(lp_fee_rate))
+            .ok_or(ErrorCode::MathOverflow)?
+            / u128::from(trade_fee_rate);
+        let surplus_rate = u64::try_from(surplus_rate).map_err(|_| ErrorCode::MathOverflow)?;
+        Ok(creator_fee_rate + surplus_rate)
+    }
+
+    /// Accrue the creator's share of the trade fee charged on the input token
+    /// of a swap, see `creator_share_rate`, returns the creator fee
+    pub fn accrue_creator_fee(
+        &mut self,
+        trade_direction: TradeDirection,
+        trade_fee: u128,
+        creator_share_rate: u64,
+    ) -> Result<u64> {
+        let creator_fee =
+            Fees::creator_fee(trade_fee, creator_share_rate).ok_or(ErrorCode::MathOverflow)?;
+        let creator_fee = u64::try_from(creator_fee).map_err(|_| ErrorCode::MathOverflow)?;
+        self.add_creator_fee(trade_direction, creator_fee);
+        Ok(creator_fee)
+    }
+
+    /// Accrue a creator fee already charged on the input token
+    pub fn add_creator_fee(&mut self, trade_direction: TradeDirection, creator_fee: u64) {
+        match trade_direction {
+            TradeDirection::ZeroForOne => {
+                self.creator_fees_token_0 = self.creator_fees_token_0.checked_add(creator_fee).unwrap();
+            }
+            TradeDirection::OneForZero => {
+                self.creator_fees_token_1 = self.creator_fees_token_1.checked_add(creator_fee).unwrap();
+            }
+        }
+    }
+
+    /// Get the trade fee rate charged at `block_timestamp`, the launch fee rate
+    /// decaying to the config trade fee rate over the launch fee duration
+    pub fn trade_fee_rate(&self, amm_config: &AmmConfig, block_timestamp: u64) -> Result<u64> {
+        let config_rate = amm_config.trade_fee_rate;
+        let elapsed = block_timestamp.saturating_sub(self.open_time);
+        if self.launch_fee_rate <= config_rate || elapsed >= self.launch_fee_duration {
+            return Ok(config_rate);
+        }
+        let surplus = self.launch_fee_rate - config_rate;
+        let duration = self.launch_fee_duration;
+        let remaining = if self.launch_fee_decay == LAUNCH_FEE_DECAY_EXPONENTIAL {
+            // halve every half life, falling linearly within each half life
+            let half_life = (duration / LAUNCH_FEE_HALVINGS).max(1);
+            let halved = surplus >> (elapsed / half_life).min(63);
+            let fallen = u128::from(halved / 2) * u128::from(elapsed % half_life)
+                / u128::from(half_life);
+            halved - u64::try_from(fallen).map_err(|_| ErrorCode::MathOverflow)?
+        } else {
+            u64::try_from(
+                u128::from(surplus) * u128::from(duration - elapsed) / u128::from(duration),
+            )
+            .map_err(|_| ErrorCode::MathOverflow)?
+        };
+        Ok(config_rate + remaining)
+    }
+
+    /// Pools created before observations were recorded have no observation
+    /// account, they take one once through `init_observation`
+    pub fn set_observation_key(&mut self, observation_key: Pubkey) -> Result<()> {
+        require_keys_eq!(
+            self.observation_key,
+            Pubkey::default(),
+            ErrorCode::ObservationAlreadyInitialized
+        );
+        self.observation_key = observation_key;
+        Ok(())
+    }
+
+    /// Get the trade direction of a swap from the vaults it reads and writes
+    pub fn trade_direction(&self, input_vault: Pubkey, output_vault: Pubkey) -> Result<TradeDirection> {
+        if input_vault == self.token_0_vault && output_vault == self.token_1_vault {
+            Ok(TradeDirection::ZeroForOne)
+        } else if input_vault == self.token_1_vault && output_vault == self.token_0_vault {
+            Ok(TradeDirection::OneForZero)
+        } else {
+            err!(ErrorCode::InvalidVault)
+        }
+    }
+
+    /// Get the curve of the pool
+    pub fn curve(&self) -> Result<Box<dyn CurveCalculator>> {
+        CurveType::from_u8(self.curve_type)?.calculator(self.curve_params)
+    }
+
     pub fn token_price_x32(&self, vault_0: u64, vault_1: u64) -> (u128, u128) {
         (
-            vault_1 as u128 * Q32 as u128 / vault_0 as u128,
-            vault_0 as u128 * Q32 as u128 / vault_1 as u128,
+            vault_1 as u128 * Q32 / vault_0 as u128,
+            vault_0 as u128 * Q32 / vault_1 as u128,
         )
     }
 }
@@ -139,6 +516,7 @@ impl PoolState {
 pub mod pool_test {
     use super::*;
 
+    #[allow(clippy::bool_assert_comparison)]
     mod pool_status_test {
         use super::*;
 
@@ -219,5 +597,388 @@ pub mod pool_test {
                 false
             );
         }
-    } 
+    }
+
+    mod pool_fee_test {
+        use super::*;
+
+        #[test]
+        fn accrued_fees_are_excluded_from_vault_amount() {
+            let mut pool_state = PoolState::default();
+            pool_state.accrue_fees(TradeDirection::ZeroForOne, 10, 5);
+            pool_state.accrue_fees(TradeDirection::OneForZero, 3, 1);
+            assert_eq!(pool_state.protocol_fees_token_0, 10);
+            assert_eq!(pool_state.fund_fees_token_0, 5);
+            assert_eq!(pool_state.protocol_fees_token_1, 3);
+            assert_eq!(pool_state.fund_fees_token_1, 1);
+
+            assert_eq!(pool_state.vault_amount_without_fee(100, 100).unwrap(), (85, 96));
+            assert!(pool_state.vault_amount_without_fee(14, 100).is_err());
+        }
+
+        #[test]
+        fn take_fees_is_capped_by_the_accrued_fees() {
+            let mut pool_state = PoolState::default();
+            pool_state.accrue_fees(TradeDirection::ZeroForOne, 10, 5);
+            pool_state.accrue_fees(TradeDirection::OneForZero, 3, 1);
+
+            assert_eq!(pool_state.take_fees(PoolFeeType::Protocol, 4, u64::MAX), (4, 3));
+            assert_eq!(pool_state.protocol_fees_token_0, 6);
+            assert_eq!(pool_state.protocol_fees_token_1, 0);
+            assert_eq!(pool_state.take_fees(PoolFeeType::Fund, u64::MAX, 0), (5, 0));
+            assert_eq!(pool_state.fund_fees_token_0, 0);
+            assert_eq!(pool_state.fund_fees_token_1, 1);
+            assert_eq!(pool_state.take_fees(PoolFeeType::Creator, 1, 1), (0, 0));
+        }
+
+        #[test]
+        fn flash_loan_fee_is_credited_to_the_reserves() {
+            let mut pool_state = PoolState {
+                locked: true,
+                reserve_0: 1_000,
+                reserve_1: 2_000,
+                ..Default::default()
+            };
+            pool_state
+                .settle_flash_loan((1_000, 2_000), (1_003, 2_010), 3, 6)
+                .unwrap();
+            assert!(!pool_state.locked);
+            assert_eq!(pool_state.reserves(), (1_003, 2_006));
+        }
+
+        #[test]
+        fn flash_loan_repaid_short_is_rejected() {
+            let mut pool_state = PoolState {
+                locked: true,
+                reserve_0: 1_000,
+                reserve_1: 2_000,
+                ..Default::default()
+            };
+            assert_eq!(
+                pool_state.settle_flash_loan((1_000, 2_000), (1_002, 2_006), 3, 6),
+                Err(ErrorCode::FlashLoanNotRepaid.into())
+            );
+            assert_eq!(
+                pool_state.settle_flash_loan((1_000, 2_000), (1_003, 2_005), 3, 6),
+                Err(ErrorCode::FlashLoanNotRepaid.into())
+            );
+            assert!(pool_state.locked);
+            assert_eq!(pool_state.reserves(), (1_000, 2_000));
+        }
+
+        #[test]
+        fn legacy_pool_adopts_the_vault_amounts_as_reserves() {
+            let mut pool_state = PoolState {
+                lp_supply: 1_000,
+                ..Default::default()
+            };
+            pool_state.accrue_fees(TradeDirection::ZeroForOne, 10, 5);
+            pool_state.sync_legacy_trade_reserves(TradeDirection::OneForZero, 200, 100).unwrap();
+            assert_eq!(pool_state.reserves(), (85, 200));
+
+            // tracked reserves are left alone
+            pool_state.sync_legacy_reserves(1_000, 1_000).unwrap();
+            assert_eq!(pool_state.reserves(), (85, 200));
+
+            // an empty pool has nothing to adopt
+            let mut pool_state = PoolState::default();
+            pool_state.sync_legacy_reserves(100, 100).unwrap();
+            assert_eq!(pool_state.reserves(), (0, 0));
+        }
+
+        #[test]
+        fn skim_amounts_saturate_below_the_reserves() {
+            let mut pool_state = PoolState::default();
+            pool_state.set_reserves(100, 200);
+            pool_state.accrue_fees(TradeDirection::ZeroForOne, 10, 5);
+            assert_eq!(pool_state.skim_amounts(120, 250).unwrap(), (5, 50));
+            assert_eq!(pool_state.skim_amounts(110, 150).unwrap(), (0, 0));
+        }
+
+        #[test]
+        fn creator_fee_is_a_share_of_the_trade_fee() {
+            let amm_config = AmmConfig {
+                max_creator_fee_rate: 100_000,
+                ..Default::default()
+            };
+            let mut pool_state = PoolState {
+                creator_fee_rate: 100_000,
+                ..Default::default()
+            };
+            let creator_share_rate = pool_state.creator_share_rate(&amm_config, 2_500, 0).unwrap();
+            assert_eq!(creator_share_rate, 100_000);
+            assert_eq!(
+                pool_state.accrue_creator_fee(TradeDirection::OneForZero, 1000, creator_share_rate).unwrap(),
+                100
+            );
+            assert_eq!(
+                pool_state.accrue_creator_fee(TradeDirection::OneForZero, 9, creator_share_rate).unwrap(),
+                0
+            );
+            assert_eq!(pool_state.creator_fees_token_0, 0);
+            assert_eq!(pool_state.creator_fees_token_1, 100);
+
+            assert_eq!(pool_state.vault_amount_without_fee(100, 150).unwrap(), (100, 50));
+        }
+
+        #[test]
+        fn creator_fee_is_capped_after_the_config_changes() {
+            let mut amm_config = AmmConfig {
+                trade_fee_rate: 2_500,
+                protocol_fee_rate: 120_000,
+                fund_fee_rate: 40_000,
+                max_creator_fee_rate: 100_000,
+                ..Default::default()
+            };
+            let mut pool_state = PoolState {
+                creator_fee_rate: 100_000,
+                ..Default::default()
+            };
+
+            // the max is lowered and the room it leaves is given to the protocol
+            amm_config.max_creator_fee_rate = 20_000;
+            amm_config.protocol_fee_rate = 940_000;
+            let trade_fee = 1_000u128;
+            let protocol_fee = Fees::protocol_fee(trade_fee, amm_config.protocol_fee_rate).unwrap();
+            let fund_fee = Fees::fund_fee(trade_fee, amm_config.fund_fee_rate).unwrap();
+            let creator_share_rate = pool_state.creator_share_rate(&amm_config, 2_500, 0).unwrap();
+            let creator_fee = pool_state
+                .accrue_creator_fee(TradeDirection::ZeroForOne, trade_fee, creator_share_rate)
+                .unwrap();
+            assert_eq!(creator_fee, 20);
+            assert!(protocol_fee + fund_fee + u128::from(creator_fee) <= trade_fee);
+        }
+
+        #[test]
+        fn launch_fee_decays_to_the_config_trade_fee() {
+            let amm_config = AmmConfig {
+                trade_fee_rate: 2_500,
+                ..Default::default()
+            };
+            let mut pool_state = PoolState {
+                open_time: 1_000,
+                launch_fee_rate: 502_500,
+                launch_fee_duration: 800,
+                ..Default::default()
+            };
+            assert_eq!(pool_state.trade_fee_rate(&amm_config, 1_000).unwrap(), 502_500);
+            assert_eq!(pool_state.trade_fee_rate(&amm_config, 1_400).unwrap(), 252_500);
+            assert_eq!(pool_state.trade_fee_rate(&amm_config, 1_799).unwrap(), 3_125);
+            assert_eq!(pool_state.trade_fee_rate(&amm_config, 1_800).unwrap(), 2_500);
+
+            pool_state.launch_fee_decay = LAUNCH_FEE_DECAY_EXPONENTIAL;
+            assert_eq!(pool_state.trade_fee_rate(&amm_config, 1_000).unwrap(), 502_500);
+            assert_eq!(pool_state.trade_fee_rate(&amm_config, 1_100).unwrap(), 252_500);
+            assert_eq!(pool_state.trade_fee_rate(&amm_config, 1_150).unwrap(), 190_000);
+            assert_eq!(pool_state.trade_fee_rate(&amm_config, 1_200).unwrap(), 127_500);
+            assert_eq!(pool_state.trade_fee_rate(&amm_config, 1_800).unwrap(), 2_500);
+
+            // a launch fee below the config trade fee is ignored
+            pool_state.launch_fee_rate = 1_000;
+            assert_eq!(pool_state.trade_fee_rate(&amm_config, 1_000).unwrap(), 2_500);
+        }
+
+        #[test]
+        fn launch_fee_decays_over_a_long_half_life() {
+            let amm_config = AmmConfig {
+                trade_fee_rate: 2_500,
+                ..Default::default()
+            };
+            let pool_state = PoolState {
+                launch_fee_rate: 902_500,
+                launch_fee_duration: u64::MAX,
+                launch_fee_decay: LAUNCH_FEE_DECAY_EXPONENTIAL,
+                ..Default::default()
+            };
+            // halfway through the first half life, just under a quarter of the surplus is gone
+            let half_life = u64::MAX / LAUNCH_FEE_HALVINGS;
+            assert_eq!(pool_state.trade_fee_rate(&amm_config, half_life / 2).unwrap(), 677_501);
+            assert_eq!(pool_state.trade_fee_rate(&amm_config, half_life).unwrap(), 452_500);
+        }
+
+        #[test]
+        fn launch_fee_is_capped_by_the_config() {
+            let amm_config = AmmConfig {
+                max_launch_fee_rate: 500_000,
+                max_launch_fee_duration: 3_600,
+                ..Default::default()
+            };
+            let launch_fee = LaunchFeeParams {
+                fee_rate: 500_000,
+                duration: 3_600,
+                decay: LAUNCH_FEE_DECAY_LINEAR,
+                surplus_to_creator: true,
+            };
+            launch_fee.validate(&amm_config).unwrap();
+            LaunchFeeParams::default().validate(&AmmConfig::default()).unwrap();
+
+            let too_high = LaunchFeeParams {
+                fee_rate: 500_001,
+                ..launch_fee
+            };
+            assert_eq!(
+                too_high.validate(&amm_config),
+                Err(ErrorCode::LaunchFeeTooHigh.into())
+            );
+            let too_long = LaunchFeeParams {
+                duration: 3_601,
+                ..launch_fee
+            };
+            assert_eq!(
+                too_long.validate(&amm_config),
+                Err(ErrorCode::LaunchFeeTooHigh.into())
+            );
+            let unknown_decay = LaunchFeeParams {
+                decay: 2,
+                ..launch_fee
+            };
+            assert_eq!(
+                unknown_decay.validate(&amm_config),
+                Err(ErrorCode::InvalidLaunchFee.into())
+            );
+        }
+
+        #[test]
+        fn launch_fee_surplus_is_paid_to_the_creator() {
+            let amm_config = AmmConfig {
+                trade_fee_rate: 2_500,
+                protocol_fee_rate: 120_000,
+                fund_fee_rate: 40_000,
+                max_creator_fee_rate: 40_000,
+                ..Default::default()
+            };
+            let mut pool_state = PoolState {
+                creator_fee_rate: 40_000,
+                ..Default::default()
+            };
+            assert_eq!(pool_state.creator_share_rate(&amm_config, 10_000, 0).unwrap(), 40_000);
+
+            pool_state.launch_fee_to_creator = true;
+            // 3/4 of the trade fee is surplus, 80% of it is the liquidity providers' share
+            let creator_share_rate = pool_state.creator_share_rate(&amm_config, 10_000, 0).unwrap();
+            assert_eq!(creator_share_rate, 640_000);
+            assert_eq!(
+                pool_state
+                    .accrue_creator_fee(TradeDirection::ZeroForOne, 1_000, creator_share_rate)
+                    .unwrap(),
+                640
+            );
+            assert_eq!(pool_state.creator_fees_token_0, 640);
+            assert_eq!(pool_state.creator_share_rate(&amm_config, 2_500, 0).unwrap(), 40_000);
+        }
+
+        #[test]
+        fn launch_fee_surplus_leaves_the_referral_fee_out() {
+            let amm_config = AmmConfig {
+                trade_fee_rate: 2_500,
+                protocol_fee_rate: 120_000,
+                fund_fee_rate: 40_000,
+                referral_fee_rate: 50_000,
+                max_creator_fee_rate: 40_000,
+                ..Default::default()
+            };
+            let pool_state = PoolState {
+                creator_fee_rate: 40_000,
+                launch_fee_to_creator: true,
+                ..Default::default()
+            };
+            // without a referrer the referral fee stays with the liquidity providers
+            assert_eq!(pool_state.creator_share_rate(&amm_config, 10_000, 0).unwrap(), 640_000);
+            assert_eq!(
+                pool_state
+                    .creator_share_rate(&amm_config, 10_000, amm_config.referral_fee_rate)
+                    .unwrap(),
+                602_500
+            );
+            assert_eq!(
+                pool_state.creator_share_rate(&amm_config, 10_000, 900_000),
+                Err(ErrorCode::MathOverflow.into())
+            );
+        }
+    }
+
+    mod pool_observation_test {
+        use super::*;
+
+        #[test]
+        fn legacy_pool_takes_an_observation_key_once() {
+            // pools created before observations were recorded hold a default key
+            let mut pool_state = PoolState::default();
+            assert_eq!(pool_state.observation_key, Pubkey::default());
+
+            let observation_key = Pubkey::new_unique();
+            pool_state.set_observation_key(observation_key).unwrap();
+            assert_eq!(pool_state.observation_key, observation_key);
+
+            assert_eq!(
+                pool_state.set_observation_key(Pubkey::new_unique()),
+                Err(ErrorCode::ObservationAlreadyInitialized.into())
+            );
+            assert_eq!(pool_state.observation_key, observation_key);
+        }
+    }
+
+    mod sorted_mints_test {
+        use super::*;
+
+        #[test]
+        fn sorted_mints_is_order_independent() {
+            let mint_a = Pubkey::new_unique();
+            let mint_b = Pubkey::new_unique();
+            assert_eq!(sorted_mints(mint_a, mint_b), sorted_mints(mint_b, mint_a));
+
+            let (token_0_mint, token_1_mint) = sorted_mints(mint_a, mint_b);
+            assert!(token_0_mint < token_1_mint);
+        }
+
+        #[test]
+        fn trade_direction_from_vaults() {
+            let pool_state = PoolState {
+                token_0_vault: Pubkey::new_unique(),
+                token_1_vault: Pubkey::new_unique(),
+                ..Default::default()
+            };
+
+            assert_eq!(
+                pool_state
+                    .trade_direction(pool_state.token_0_vault, pool_state.token_1_vault)
+                    .unwrap(),
+                TradeDirection::ZeroForOne
+            );
+            assert_eq!(
+                pool_state
+                    .trade_direction(pool_state.token_1_vault, pool_state.token_0_vault)
+                    .unwrap(),
+                TradeDirection::OneForZero
+            );
+            assert!(pool_state
+                .trade_direction(pool_state.token_0_vault, pool_state.token_0_vault)
+                .is_err());
+        }
+    }
+
+    mod pool_curve_test {
+        use super::*;
+
+        #[test]
+        fn curve_from_type_and_params() {
+            let mut pool_state = PoolState::default();
+            assert_eq!(pool_state.curve().unwrap().invariant(2, 3), Some(6));
+
+            pool_state.curve_params = [100, 0];
+            assert!(pool_state.curve().is_err());
+
+            pool_state.curve_type = 1;
+            assert_eq!(
+                pool_state.curve().unwrap().invariant(1_000_000, 1_000_000),
+                Some(2_000_000)
+            );
+
+            pool_state.curve_params = [0, 0];
+            assert!(pool_state.curve().is_err());
+
+            pool_state.curve_type = 2;
+            assert!(pool_state.curve().is_err());
+        }
+    }
 }
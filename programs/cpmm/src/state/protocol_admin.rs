@@ -0,0 +1,17 @@
+use anchor_lang::prelude::*;
+
+pub const PROTOCOL_ADMIN_SEED: &str = "protocol_admin";
+
+/// Holds the admin allowed to create configs
+#[account]
+#[derive(InitSpace)]
+pub struct ProtocolAdmin {
+    /// Bump to identify PDA
+    pub bump: u8,
+    /// The address that signs `create_amm_config`
+    pub admin: Pubkey,
+    /// Address proposed as the next admin, must accept to take over
+    pub pending_admin: Pubkey,
+    /// padding for future updates
+    pub padding: [u64; 8],
+}
@@ -0,0 +1,44 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+
+/// Fail if the optional unix timestamp `deadline` has already passed
+pub fn check_deadline(deadline: Option<i64>) -> Result<()> {
+    match deadline {
+        Some(_) => check_deadline_at(deadline, Clock::get()?.unix_timestamp),
+        None => Ok(()),
+    }
+}
+
+/// Fail if the optional unix timestamp `deadline` is before `unix_timestamp`
+pub fn check_deadline_at(deadline: Option<i64>, unix_timestamp: i64) -> Result<()> {
+    if let Some(deadline) = deadline {
+        require_gte!(deadline, unix_timestamp, ErrorCode::DeadlineExceeded);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expired_deadline_is_rejected() {
+        assert_eq!(
+            check_deadline_at(Some(99), 100),
+            Err(ErrorCode::DeadlineExceeded.into())
+        );
+    }
+
+    #[test]
+    fn deadline_not_yet_passed_is_accepted() {
+        assert!(check_deadline_at(Some(100), 100).is_ok());
+        assert!(check_deadline_at(Some(101), 100).is_ok());
+    }
+
+    #[test]
+    fn no_deadline_is_accepted() {
+        assert!(check_deadline_at(None, i64::MAX).is_ok());
+        assert!(check_deadline(None).is_ok());
+    }
+}
@@ -1,6 +1,9 @@
-///! 128 and 256 bit numbers
-///! U128 is more efficient that u128
-///! https://github.com/solana-labs/solana/issues/19549
+//! 128 and 256 bit numbers
+//! U128 is more efficient that u128
+//! https://github.com/solana-labs/solana/issues/19549
+#![allow(clippy::assign_op_pattern)]
+#![allow(clippy::manual_div_ceil)]
+#![allow(clippy::reversed_empty_ranges)]
 use uint::construct_uint;
 construct_uint! {
     pub struct U128(2);
@@ -22,7 +25,7 @@ impl CheckedCeilDiv for u128 {
         // fail.
         if quotient == 0 {
             // return None;
-            if self.checked_mul(2 as u128)? >= rhs {
+            if self.checked_mul(2)? >= rhs {
                 return Some((1, 0));
             } else {
                 return Some((0, 0));
@@ -45,3 +48,14 @@ impl CheckedCeilDiv for u128 {
         Some((quotient, rhs))
     }
 }
+
+/// Ceiling division of U256 numbers, None when the denominator is zero
+pub fn checked_ceil_div_u256(numerator: U256, denominator: U256) -> Option<U256> {
+    if denominator.is_zero() {
+        return None;
+    }
+    numerator
+        .checked_add(denominator)?
+        .checked_sub(U256::one())?
+        .checked_div(denominator)
+}
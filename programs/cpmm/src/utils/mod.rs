@@ -2,4 +2,7 @@ pub mod token;
 pub use token::*;
 
 pub mod math;
-pub use math::*;
\ No newline at end of file
+pub use math::*;
+
+pub mod deadline;
+pub use deadline::*;
@@ -18,7 +18,8 @@ use anchor_spl::{
     },
 };
 
-const MINT_WHITELIST: [&'static str; 4] = [
+#[allow(dead_code)]
+const MINT_WHITELIST: [&str; 4] = [
     "HVbpJAQGNpkgBaYBZQBR1t7yFdvaYVp2vCQQfKKEN4tM",
     "Crn4x1Y2HUKko7ox2EZMT6N2t2ZyH7eKtwkBGVnhEq1g",
     "FrBfWJ4qE5sCzKm3k3JaAtqZcXUh4LvJygDeketsrsH4",
@@ -52,6 +53,7 @@ pub fn transfer_from_user_to_pool_vault<'a>(
     )
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn transfer_from_pool_vault_to_user<'a>(
     authority: AccountInfo<'a>,
     from_vault: AccountInfo<'a>,
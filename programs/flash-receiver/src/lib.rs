@@ -0,0 +1,131 @@
+#![allow(unexpected_cfgs)]
+use anchor_lang::{
+    prelude::*,
+    solana_program::{instruction::Instruction, program::invoke},
+};
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+
+declare_id!("4MP51acVyVs4N17Tkdfwpe2xCuLBmb3mPssubjexGTAv");
+
+/// The receiver program the cpmm tests pass to flash_loan and flash_swap
+#[program]
+pub mod flash_receiver {
+    use super::*;
+
+    /// Pay `amount_0` and `amount_1` from the payer token accounts into the
+    /// pool vaults
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `amount_0` - the amount of token_0 to pay
+    /// * `amount_1` - the amount of token_1 to pay
+    ///
+    pub fn repay(ctx: Context<Repay>, amount_0: u64, amount_1: u64) -> Result<()> {
+        transfer(
+            &ctx.accounts.payer,
+            &ctx.accounts.payer_token_0,
+            &ctx.accounts.token_0_vault,
+            &ctx.accounts.vault_0_mint,
+            &ctx.accounts.token_0_program,
+            amount_0,
+        )?;
+        transfer(
+            &ctx.accounts.payer,
+            &ctx.accounts.payer_token_1,
+            &ctx.accounts.token_1_vault,
+            &ctx.accounts.vault_1_mint,
+            &ctx.accounts.token_1_program,
+            amount_1,
+        )
+    }
+
+    /// Invoke the program of the first remaining account with `data` and the
+    /// rest of the remaining accounts, to call back into the pool while it
+    /// is locked
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `data` - the instruction data for the invoked program
+    ///
+    pub fn reenter<'info>(ctx: Context<'_, '_, '_, 'info, Reenter>, data: Vec<u8>) -> Result<()> {
+        let (program, accounts) = ctx
+            .remaining_accounts
+            .split_first()
+            .ok_or(ErrorCode::AccountNotEnoughKeys)?;
+        let metas = accounts
+            .iter()
+            .map(|account| AccountMeta {
+                pubkey: account.key(),
+                is_signer: account.is_signer,
+                is_writable: account.is_writable,
+            })
+            .collect();
+        invoke(
+            &Instruction {
+                program_id: program.key(),
+                accounts: metas,
+                data,
+            },
+            ctx.remaining_accounts,
+        )?;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Repay<'info> {
+    /// The owner of the payer token accounts, signing the flash loan or
+    /// flash swap
+    pub payer: Signer<'info>,
+
+    #[account(mut)]
+    pub payer_token_0: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub payer_token_1: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub token_0_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub token_1_vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub vault_0_mint: InterfaceAccount<'info, Mint>,
+
+    pub vault_1_mint: InterfaceAccount<'info, Mint>,
+
+    pub token_0_program: Interface<'info, TokenInterface>,
+
+    pub token_1_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct Reenter {}
+
+fn transfer<'info>(
+    payer: &Signer<'info>,
+    from: &InterfaceAccount<'info, TokenAccount>,
+    to: &InterfaceAccount<'info, TokenAccount>,
+    mint: &InterfaceAccount<'info, Mint>,
+    token_program: &Interface<'info, TokenInterface>,
+    amount: u64,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    token_interface::transfer_checked(
+        CpiContext::new(
+            token_program.to_account_info(),
+            TransferChecked {
+                from: from.to_account_info(),
+                to: to.to_account_info(),
+                authority: payer.to_account_info(),
+                mint: mint.to_account_info(),
+            },
+        ),
+        amount,
+        mint.decimals,
+    )
+}
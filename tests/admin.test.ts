@@ -0,0 +1,281 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { Keypair, LAMPORTS_PER_SOL, PublicKey } from "@solana/web3.js";
+import { BN } from "bn.js";
+import { assert } from "chai";
+import { Cpmm } from "../target/types/cpmm";
+import { createAmmConfig, initProtocolAdmin } from "./utils";
+
+describe("admin transfer test", () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+  const owner = anchor.Wallet.local().payer;
+  const program = anchor.workspace.Cpmm as Program<Cpmm>;
+
+  const confirmOptions = {
+    skipPreflight: true,
+  };
+
+  it("propose, cancel and accept admin", async () => {
+    const configIndex = 1;
+    const configAddress = await createAmmConfig(
+      program,
+      anchor.getProvider().connection,
+      owner,
+      configIndex,
+      new BN(10),
+      new BN(1000),
+      new BN(25000),
+      new BN(0),
+      confirmOptions
+    );
+    const newOwner = Keypair.generate();
+
+    await program.methods
+      .proposeAdmin(configIndex, newOwner.publicKey)
+      .accounts({ owner: owner.publicKey })
+      .rpc(confirmOptions);
+    let ammConfig = await program.account.ammConfig.fetch(configAddress);
+    assert(ammConfig.pendingOwner.equals(newOwner.publicKey));
+
+    await program.methods
+      .cancelAdminTransfer(configIndex)
+      .accounts({ owner: owner.publicKey })
+      .rpc(confirmOptions);
+    ammConfig = await program.account.ammConfig.fetch(configAddress);
+    assert(ammConfig.pendingOwner.equals(PublicKey.default));
+
+    await program.methods
+      .proposeAdmin(configIndex, newOwner.publicKey)
+      .accounts({ owner: owner.publicKey })
+      .rpc(confirmOptions);
+    await program.methods
+      .acceptAdmin(configIndex)
+      .accounts({ pendingOwner: newOwner.publicKey })
+      .signers([newOwner])
+      .rpc(confirmOptions);
+    ammConfig = await program.account.ammConfig.fetch(configAddress);
+    assert(ammConfig.owner.equals(newOwner.publicKey));
+    assert(ammConfig.pendingOwner.equals(PublicKey.default));
+    // the create pool fee follows the owner
+    assert(ammConfig.createPoolFeeReceiver.equals(newOwner.publicKey));
+
+    const receiver = Keypair.generate();
+    try {
+      await program.methods
+        .setCreatePoolFeeReceiver(configIndex, receiver.publicKey)
+        .accounts({ owner: owner.publicKey })
+        .rpc();
+      assert.fail("only the owner can set the create pool fee receiver");
+    } catch (err) {
+      assert.include(err.toString(), "InvalidOwner");
+    }
+    await program.methods
+      .setCreatePoolFeeReceiver(configIndex, receiver.publicKey)
+      .accounts({ owner: newOwner.publicKey })
+      .signers([newOwner])
+      .rpc(confirmOptions);
+    ammConfig = await program.account.ammConfig.fetch(configAddress);
+    assert(ammConfig.createPoolFeeReceiver.equals(receiver.publicKey));
+  });
+
+  it("queue trade fee rate change", async () => {
+    const configIndex = 2;
+    const configAddress = await createAmmConfig(
+      program,
+      anchor.getProvider().connection,
+      owner,
+      configIndex,
+      new BN(10),
+      new BN(1000),
+      new BN(25000),
+      new BN(0),
+      confirmOptions
+    );
+
+    try {
+      await program.methods
+        .updateAmmConfig(0, new BN(20), configIndex)
+        .accounts({ owner: owner.publicKey })
+        .rpc();
+      assert.fail("trade fee rate must be queued");
+    } catch (err) {
+      assert.include(err.toString(), "ConfigUpdateTimelocked");
+    }
+
+    await program.methods
+      .queueConfigUpdate(configIndex, 0, new BN(20))
+      .accounts({ owner: owner.publicKey })
+      .rpc(confirmOptions);
+    const ammConfig = await program.account.ammConfig.fetch(configAddress);
+    assert.equal(ammConfig.pendingParam, 0);
+    assert(ammConfig.pendingValue.eqn(20));
+    assert(ammConfig.pendingEffectiveAt.gt(new BN(0)));
+
+    try {
+      await program.methods.applyConfigUpdate(configIndex).rpc();
+      assert.fail("queued update must wait for the delay");
+    } catch (err) {
+      assert.include(err.toString(), "ConfigUpdateNotReady");
+    }
+
+    await program.methods
+      .cancelConfigUpdate(configIndex)
+      .accounts({ owner: owner.publicKey })
+      .rpc(confirmOptions);
+    const cancelled = await program.account.ammConfig.fetch(configAddress);
+    assert(cancelled.pendingEffectiveAt.eqn(0));
+    assert(cancelled.tradeFeeRate.eqn(10));
+
+    try {
+      await program.methods
+        .cancelConfigUpdate(configIndex)
+        .accounts({ owner: owner.publicKey })
+        .rpc();
+      assert.fail("nothing is queued anymore");
+    } catch (err) {
+      assert.include(err.toString(), "ConfigUpdateNotQueued");
+    }
+
+    try {
+      await program.methods
+        .queueConfigUpdate(configIndex, 0, new BN(1_000_000))
+        .accounts({ owner: owner.publicKey })
+        .rpc();
+      assert.fail("trade fee rate must be below the denominator");
+    } catch (err) {
+      assert.include(err.toString(), "InvalidFeeRate");
+    }
+
+    try {
+      await program.methods
+        .queueConfigUpdate(configIndex, 4, new BN(60))
+        .accounts({ owner: owner.publicKey })
+        .rpc();
+      assert.fail("update delay must not go below one day");
+    } catch (err) {
+      assert.include(err.toString(), "InvalidInput");
+    }
+  });
+
+  it("update the locked lp amount within its bounds", async () => {
+    const configIndex = 3;
+    const configAddress = await createAmmConfig(
+      program,
+      anchor.getProvider().connection,
+      owner,
+      configIndex,
+      new BN(10),
+      new BN(1000),
+      new BN(25000),
+      new BN(0),
+      confirmOptions
+    );
+
+    await program.methods
+      .updateAmmConfig(7, new BN(1000000), configIndex)
+      .accounts({ owner: owner.publicKey })
+      .rpc(confirmOptions);
+    const ammConfig = await program.account.ammConfig.fetch(configAddress);
+    assert(ammConfig.lockLpAmount.eqn(1000000));
+
+    for (const lockLpAmount of [new BN(99), new BN(1000000001)]) {
+      try {
+        await program.methods
+          .updateAmmConfig(7, lockLpAmount, configIndex)
+          .accounts({ owner: owner.publicKey })
+          .rpc();
+        assert.fail("locked lp amount must be within its bounds");
+      } catch (err) {
+        assert.include(err.toString(), "InvalidInput");
+      }
+    }
+  });
+
+  it("rotate the protocol admin", async () => {
+    const connection = anchor.getProvider().connection;
+    const protocolAdminAddress = await initProtocolAdmin(
+      program,
+      connection,
+      owner,
+      confirmOptions
+    );
+    const newAdmin = Keypair.generate();
+    await connection.confirmTransaction(
+      await connection.requestAirdrop(newAdmin.publicKey, LAMPORTS_PER_SOL)
+    );
+
+    try {
+      await program.methods
+        .createAmmConfig(9, new BN(10), new BN(1000), new BN(25000), new BN(0))
+        .accounts({ owner: newAdmin.publicKey })
+        .signers([newAdmin])
+        .rpc();
+      assert.fail("only the protocol admin can create amm configs");
+    } catch (err) {
+      assert.include(err.toString(), "InvalidOwner");
+    }
+
+    try {
+      await program.methods
+        .proposeProtocolAdmin(newAdmin.publicKey)
+        .accounts({ admin: newAdmin.publicKey })
+        .signers([newAdmin])
+        .rpc();
+      assert.fail("only the protocol admin can propose a new admin");
+    } catch (err) {
+      assert.include(err.toString(), "InvalidOwner");
+    }
+
+    await program.methods
+      .proposeProtocolAdmin(newAdmin.publicKey)
+      .accounts({ admin: owner.publicKey })
+      .rpc(confirmOptions);
+    await program.methods
+      .cancelProtocolAdminTransfer()
+      .accounts({ admin: owner.publicKey })
+      .rpc(confirmOptions);
+    let protocolAdmin = await program.account.protocolAdmin.fetch(
+      protocolAdminAddress
+    );
+    assert(protocolAdmin.pendingAdmin.equals(PublicKey.default));
+
+    await program.methods
+      .proposeProtocolAdmin(newAdmin.publicKey)
+      .accounts({ admin: owner.publicKey })
+      .rpc(confirmOptions);
+    await program.methods
+      .acceptProtocolAdmin()
+      .accounts({ pendingAdmin: newAdmin.publicKey })
+      .signers([newAdmin])
+      .rpc(confirmOptions);
+    protocolAdmin = await program.account.protocolAdmin.fetch(
+      protocolAdminAddress
+    );
+    assert(protocolAdmin.admin.equals(newAdmin.publicKey));
+
+    try {
+      await program.methods
+        .createAmmConfig(9, new BN(10), new BN(1000), new BN(25000), new BN(0))
+        .accounts({ owner: owner.publicKey })
+        .rpc();
+      assert.fail("the previous admin can no longer create amm configs");
+    } catch (err) {
+      assert.include(err.toString(), "InvalidOwner");
+    }
+
+    // hand the role back so the other tests keep creating configs
+    await program.methods
+      .proposeProtocolAdmin(owner.publicKey)
+      .accounts({ admin: newAdmin.publicKey })
+      .signers([newAdmin])
+      .rpc(confirmOptions);
+    await program.methods
+      .acceptProtocolAdmin()
+      .accounts({ pendingAdmin: owner.publicKey })
+      .rpc(confirmOptions);
+    protocolAdmin = await program.account.protocolAdmin.fetch(
+      protocolAdminAddress
+    );
+    assert(protocolAdmin.admin.equals(owner.publicKey));
+  });
+});
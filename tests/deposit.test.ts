@@ -5,8 +5,11 @@ import {
     calculateFee,
     calculatePreFeeAmount,
     deposit,
+    deposit_by_token_amounts,
+    deposit_single_token,
     getUserAndPoolVaultAmount,
     setupDepositTest,
+    simulate_deposit_single_token,
   } from "./utils";
 import { BN } from "bn.js";
 import { assert } from "chai";
@@ -99,4 +102,173 @@ describe("deposit test", () => {
             input_token1_amount
         );
     });
-});
\ No newline at end of file
+
+    it("deposit single token", async () => {
+        const { poolAddress, poolState } = await setupDepositTest(
+            program,
+            anchor.getProvider().connection,
+            owner,
+            {
+            config_index: 0,
+            tradeFeeRate: new BN(10),
+            protocolFeeRate: new BN(1000),
+            fundFeeRate: new BN(25000),
+            create_fee: new BN(0),
+            },
+            { transferFeeBasisPoints: 0, MaxFee: 0 }
+        );
+
+        const {
+            onwerToken1Account: ownerToken1AccountBefore,
+            poolVault0TokenAccount: poolVault0TokenAccountBefore,
+        } = await getUserAndPoolVaultAmount(
+            owner.publicKey,
+            poolState.token0Mint,
+            poolState.token0Program,
+            poolState.token1Mint,
+            poolState.token1Program,
+            poolState.token0Vault,
+            poolState.token1Vault
+        );
+
+        const amount_in = new BN(100000000);
+        await deposit_single_token(
+          program,
+          owner,
+          0,
+          poolState.token0Mint,
+          poolState.token0Program,
+          poolState.token1Mint,
+          amount_in,
+          new BN(1),
+          confirmOptions
+        );
+        const newPoolState = await program.account.poolState.fetch(poolAddress);
+        assert(newPoolState.lpSupply.gt(poolState.lpSupply));
+
+        const {
+            onwerToken1Account: ownerToken1AccountAfter,
+            poolVault0TokenAccount: poolVault0TokenAccountAfter,
+          } = await getUserAndPoolVaultAmount(
+            owner.publicKey,
+            poolState.token0Mint,
+            poolState.token0Program,
+            poolState.token1Mint,
+            poolState.token1Program,
+            poolState.token0Vault,
+            poolState.token1Vault
+          );
+        assert.equal(
+            poolVault0TokenAccountAfter.amount - poolVault0TokenAccountBefore.amount,
+            BigInt(amount_in.toString())
+        );
+        assert.equal(ownerToken1AccountAfter.amount, ownerToken1AccountBefore.amount);
+    });
+
+    it("deposit by token amounts", async () => {
+        const { poolAddress, poolState } = await setupDepositTest(
+            program,
+            anchor.getProvider().connection,
+            owner,
+            {
+            config_index: 0,
+            tradeFeeRate: new BN(10),
+            protocolFeeRate: new BN(1000),
+            fundFeeRate: new BN(25000),
+            create_fee: new BN(0),
+            },
+            { transferFeeBasisPoints: 0, MaxFee: 0 }
+        );
+
+        const {
+            onwerToken0Account: ownerToken0AccountBefore,
+            onwerToken1Account: ownerToken1AccountBefore,
+        } = await getUserAndPoolVaultAmount(
+            owner.publicKey,
+            poolState.token0Mint,
+            poolState.token0Program,
+            poolState.token1Mint,
+            poolState.token1Program,
+            poolState.token0Vault,
+            poolState.token1Vault
+        );
+
+        const maximum_token_0_amount = new BN(100000000);
+        const maximum_token_1_amount = new BN(100000000);
+        await deposit_by_token_amounts(
+          program,
+          owner,
+          0,
+          poolState.token0Mint,
+          poolState.token0Program,
+          poolState.token1Mint,
+          poolState.token1Program,
+          maximum_token_0_amount,
+          maximum_token_1_amount,
+          new BN(1),
+          confirmOptions
+        );
+        const newPoolState = await program.account.poolState.fetch(poolAddress);
+        assert(newPoolState.lpSupply.gt(poolState.lpSupply));
+
+        const {
+            onwerToken0Account: ownerToken0AccountAfter,
+            onwerToken1Account: ownerToken1AccountAfter,
+          } = await getUserAndPoolVaultAmount(
+            owner.publicKey,
+            poolState.token0Mint,
+            poolState.token0Program,
+            poolState.token1Mint,
+            poolState.token1Program,
+            poolState.token0Vault,
+            poolState.token1Vault
+          );
+        assert(
+            ownerToken0AccountBefore.amount - ownerToken0AccountAfter.amount <=
+            BigInt(maximum_token_0_amount.toString())
+        );
+        assert(
+            ownerToken1AccountBefore.amount - ownerToken1AccountAfter.amount <=
+            BigInt(maximum_token_1_amount.toString())
+        );
+    });
+
+    it("deposit single token stays within the compute budget", async () => {
+        const curves = [
+            { curveType: 1, curveParams: [new BN(100), new BN(0)] },
+            { curveType: 2, curveParams: [new BN(800000), new BN(200000)] },
+        ];
+        for (const curve of curves) {
+            const { poolState } = await setupDepositTest(
+                program,
+                anchor.getProvider().connection,
+                owner,
+                {
+                config_index: 0,
+                tradeFeeRate: new BN(10),
+                protocolFeeRate: new BN(1000),
+                fundFeeRate: new BN(25000),
+                create_fee: new BN(0),
+                },
+                { transferFeeBasisPoints: 0, MaxFee: 0 },
+                confirmOptions,
+                undefined,
+                undefined,
+                curve
+            );
+
+            const unitsConsumed = await simulate_deposit_single_token(
+              program,
+              owner,
+              0,
+              poolState.token0Mint,
+              poolState.token0Program,
+              poolState.token1Mint,
+              new BN(30000000000),
+              new BN(1)
+            );
+            console.log("deposit single token units consumed:", curve.curveType, unitsConsumed);
+            assert(unitsConsumed < 200000);
+        }
+    });
+});
@@ -0,0 +1,415 @@
+import * as anchor from "@coral-xyz/anchor";
+import { BN, Program } from "@coral-xyz/anchor";
+import { getAccount } from "@solana/spl-token";
+import { assert } from "chai";
+import { Cpmm } from "../target/types/cpmm";
+import { FlashReceiver } from "../target/types/flash_receiver";
+import {
+  flash_loan,
+  flash_receiver_reenter,
+  flash_receiver_repay,
+  flash_swap,
+  getOrcleAccountAddress,
+  setupSwapTest,
+} from "./utils";
+
+describe("flash test", () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+  const owner = anchor.Wallet.local().payer;
+  const connection = anchor.getProvider().connection;
+
+  const program = anchor.workspace.Cpmm as Program<Cpmm>;
+  const receiverProgram = anchor.workspace
+    .FlashReceiver as Program<FlashReceiver>;
+
+  const confirmOptions = {
+    skipPreflight: true,
+  };
+
+  async function setupFlashTest() {
+    return await setupSwapTest(
+      program,
+      connection,
+      owner,
+      {
+        config_index: 0,
+        tradeFeeRate: new BN(10),
+        protocolFeeRate: new BN(1000),
+        fundFeeRate: new BN(25000),
+        create_fee: new BN(0),
+      },
+      { transferFeeBasisPoints: 0, MaxFee: 0 },
+      confirmOptions
+    );
+  }
+
+  function flashLoanFee(amount: BN, flashLoanFeeRate: BN) {
+    return amount
+      .mul(flashLoanFeeRate)
+      .add(new BN(999999))
+      .div(new BN(1000000));
+  }
+
+  it("flash loan repaid by the receiver", async () => {
+    const { configAddress, poolAddress, poolState } = await setupFlashTest();
+    const ammConfig = await program.account.ammConfig.fetch(configAddress);
+    const amount_0 = new BN(100000000);
+    const amount_1 = new BN(200000000);
+    const fee_0 = flashLoanFee(amount_0, ammConfig.flashLoanFeeRate);
+    const fee_1 = flashLoanFee(amount_1, ammConfig.flashLoanFeeRate);
+    const vault0Before = await getAccount(
+      connection,
+      poolState.token0Vault,
+      "processed",
+      poolState.token0Program
+    );
+    const vault1Before = await getAccount(
+      connection,
+      poolState.token1Vault,
+      "processed",
+      poolState.token1Program
+    );
+    const poolStateBefore = await program.account.poolState.fetch(poolAddress);
+
+    const repayIx = await flash_receiver_repay(
+      receiverProgram,
+      owner,
+      poolState.token0Vault,
+      poolState.token1Vault,
+      poolState.token0Mint,
+      poolState.token0Program,
+      poolState.token1Mint,
+      poolState.token1Program,
+      amount_0.add(fee_0),
+      amount_1.add(fee_1)
+    );
+    await flash_loan(
+      program,
+      owner,
+      0,
+      poolAddress,
+      poolState.token0Vault,
+      poolState.token1Vault,
+      poolState.token0Mint,
+      poolState.token0Program,
+      poolState.token1Mint,
+      poolState.token1Program,
+      amount_0,
+      amount_1,
+      repayIx,
+      confirmOptions
+    );
+
+    const vault0After = await getAccount(
+      connection,
+      poolState.token0Vault,
+      "processed",
+      poolState.token0Program
+    );
+    const vault1After = await getAccount(
+      connection,
+      poolState.token1Vault,
+      "processed",
+      poolState.token1Program
+    );
+    const poolStateAfter = await program.account.poolState.fetch(poolAddress);
+    assert.equal(
+      vault0After.amount - vault0Before.amount,
+      BigInt(fee_0.toString())
+    );
+    assert.equal(
+      vault1After.amount - vault1Before.amount,
+      BigInt(fee_1.toString())
+    );
+    // the fee belongs to liquidity providers
+    assert.equal(
+      poolStateAfter.reserve0.toString(),
+      poolStateBefore.reserve0.add(fee_0).toString()
+    );
+    assert.equal(
+      poolStateAfter.reserve1.toString(),
+      poolStateBefore.reserve1.add(fee_1).toString()
+    );
+    assert.isFalse(poolStateAfter.locked);
+  });
+
+  it("flash loan repaid short is rejected", async () => {
+    const { poolAddress, poolState } = await setupFlashTest();
+    const amount_0 = new BN(100000000);
+    const repayIx = await flash_receiver_repay(
+      receiverProgram,
+      owner,
+      poolState.token0Vault,
+      poolState.token1Vault,
+      poolState.token0Mint,
+      poolState.token0Program,
+      poolState.token1Mint,
+      poolState.token1Program,
+      amount_0.subn(1),
+      new BN(0)
+    );
+    try {
+      await flash_loan(
+        program,
+        owner,
+        0,
+        poolAddress,
+        poolState.token0Vault,
+        poolState.token1Vault,
+        poolState.token0Mint,
+        poolState.token0Program,
+        poolState.token1Mint,
+        poolState.token1Program,
+        amount_0,
+        new BN(0),
+        repayIx
+      );
+      assert.fail("the loan must be repaid in full");
+    } catch (err) {
+      assert.include(err.toString(), "FlashLoanNotRepaid");
+    }
+    const poolStateAfter = await program.account.poolState.fetch(poolAddress);
+    assert.isFalse(poolStateAfter.locked);
+  });
+
+  it("swap, deposit and withdraw are rejected during a flash loan", async () => {
+    const { poolAddress, poolState } = await setupFlashTest();
+    const [observationAddress] = await getOrcleAccountAddress(
+      poolAddress,
+      program.programId
+    );
+    const swapIx = await program.methods
+      .swapBaseInput(0, new BN(1000000), new BN(0))
+      .accounts({
+        payer: owner.publicKey,
+        poolState: poolAddress,
+        inputTokenProgram: poolState.token0Program,
+        outputTokenProgram: poolState.token1Program,
+        inputTokenMint: poolState.token0Mint,
+        outputTokenMint: poolState.token1Mint,
+        observationState: observationAddress,
+        referrerTokenAccount: null,
+      })
+      .instruction();
+    const depositIx = await program.methods
+      .deposit(0, new BN(1000000), new BN(100000000), new BN(100000000))
+      .accounts({
+        owner: owner.publicKey,
+        token0Mint: poolState.token0Mint,
+        token1Mint: poolState.token1Mint,
+        token0Program: poolState.token0Program,
+        token1Program: poolState.token1Program,
+      })
+      .instruction();
+    const withdrawIx = await program.methods
+      .withdraw(0, new BN(1000000), new BN(0), new BN(0))
+      .accounts({
+        vault0Mint: poolState.token0Mint,
+        token0Program: poolState.token0Program,
+        vault1Mint: poolState.token1Mint,
+        token1Program: poolState.token1Program,
+      })
+      .instruction();
+
+    for (const ix of [swapIx, depositIx, withdrawIx]) {
+      const reenterIx = await flash_receiver_reenter(receiverProgram, ix);
+      try {
+        await flash_loan(
+          program,
+          owner,
+          0,
+          poolAddress,
+          poolState.token0Vault,
+          poolState.token1Vault,
+          poolState.token0Mint,
+          poolState.token0Program,
+          poolState.token1Mint,
+          poolState.token1Program,
+          new BN(100000000),
+          new BN(0),
+          reenterIx
+        );
+        assert.fail("the pool must not be entered during a flash loan");
+      } catch (err) {
+        // the pool lock rejects the call unless the runtime already refuses
+        // to re-enter the pool program from the receiver
+        assert.match(
+          err.toString(),
+          /PoolLocked|ReentrancyNotAllowed|reentrancy not allowed/
+        );
+      }
+    }
+    const poolStateAfter = await program.account.poolState.fetch(poolAddress);
+    assert.isFalse(poolStateAfter.locked);
+  });
+
+  /// The token_0 a constant product pool needs for `amount_1_out`, before
+  /// the trade fee
+  function amount0InFor(poolState: { reserve0: BN; reserve1: BN }, amount_1_out: BN) {
+    const numerator = amount_1_out.mul(poolState.reserve0);
+    const denominator = poolState.reserve1.sub(amount_1_out);
+    return numerator.add(denominator).subn(1).div(denominator);
+  }
+
+  it("flash swap measures the input from the vault", async () => {
+    const { poolAddress, poolState } = await setupFlashTest();
+    const poolStateBefore = await program.account.poolState.fetch(poolAddress);
+    const amount_out = new BN(100000000);
+    // pay twice what the curve needs, the whole payment is the input
+    const amount_in = amount0InFor(poolStateBefore, amount_out).muln(2);
+    const vault0Before = await getAccount(
+      connection,
+      poolState.token0Vault,
+      "processed",
+      poolState.token0Program
+    );
+
+    let swapEvent = null;
+    const listener = program.addEventListener("swapEvent", (event) => {
+      swapEvent = event;
+    });
+    const repayIx = await flash_receiver_repay(
+      receiverProgram,
+      owner,
+      poolState.token0Vault,
+      poolState.token1Vault,
+      poolState.token0Mint,
+      poolState.token0Program,
+      poolState.token1Mint,
+      poolState.token1Program,
+      amount_in,
+      new BN(0)
+    );
+    await flash_swap(
+      program,
+      owner,
+      0,
+      poolState.token0Mint,
+      poolState.token1Mint,
+      poolState.token1Program,
+      amount_out,
+      amount_in,
+      repayIx,
+      confirmOptions
+    );
+    await sleep(1000);
+    await program.removeEventListener(listener);
+
+    const vault0After = await getAccount(
+      connection,
+      poolState.token0Vault,
+      "processed",
+      poolState.token0Program
+    );
+    const poolStateAfter = await program.account.poolState.fetch(poolAddress);
+    assert.equal(
+      vault0After.amount - vault0Before.amount,
+      BigInt(amount_in.toString())
+    );
+    assert.isNotNull(swapEvent);
+    assert.equal(swapEvent.inputAmount.toString(), amount_in.toString());
+    assert.equal(swapEvent.outputAmount.toString(), amount_out.toString());
+    assert.equal(
+      poolStateAfter.reserve1.toString(),
+      poolStateBefore.reserve1.sub(amount_out).toString()
+    );
+    // the input beyond the protocol, fund and creator fees joins the reserves
+    assert.equal(
+      poolStateAfter.reserve0.toString(),
+      poolStateBefore.reserve0
+        .add(amount_in)
+        .sub(swapEvent.protocolFee)
+        .sub(swapEvent.fundFee)
+        .sub(swapEvent.creatorFee)
+        .toString()
+    );
+    assert.isFalse(poolStateAfter.locked);
+  });
+
+  it("flash swap paying more than max_amount_in is rejected", async () => {
+    const { poolAddress, poolState } = await setupFlashTest();
+    const poolStateBefore = await program.account.poolState.fetch(poolAddress);
+    const amount_out = new BN(100000000);
+    const max_amount_in = amount0InFor(poolStateBefore, amount_out).muln(2);
+    const repayIx = await flash_receiver_repay(
+      receiverProgram,
+      owner,
+      poolState.token0Vault,
+      poolState.token1Vault,
+      poolState.token0Mint,
+      poolState.token0Program,
+      poolState.token1Mint,
+      poolState.token1Program,
+      max_amount_in.addn(1),
+      new BN(0)
+    );
+    try {
+      await flash_swap(
+        program,
+        owner,
+        0,
+        poolState.token0Mint,
+        poolState.token1Mint,
+        poolState.token1Program,
+        amount_out,
+        max_amount_in,
+        repayIx
+      );
+      assert.fail("the input vault must not receive more than max_amount_in");
+    } catch (err) {
+      assert.include(err.toString(), "ExceededSlippage");
+    }
+    const poolStateAfter = await program.account.poolState.fetch(poolAddress);
+    assert.isFalse(poolStateAfter.locked);
+  });
+
+  it("flash swap breaking the invariant is rejected", async () => {
+    const { poolAddress, poolState } = await setupFlashTest();
+    const poolStateBefore = await program.account.poolState.fetch(poolAddress);
+    const amount_out = new BN(100000000);
+    // the curve amount without the trade fee is not enough
+    const amount_in = amount0InFor(poolStateBefore, amount_out);
+    const repayIx = await flash_receiver_repay(
+      receiverProgram,
+      owner,
+      poolState.token0Vault,
+      poolState.token1Vault,
+      poolState.token0Mint,
+      poolState.token0Program,
+      poolState.token1Mint,
+      poolState.token1Program,
+      amount_in,
+      new BN(0)
+    );
+    try {
+      await flash_swap(
+        program,
+        owner,
+        0,
+        poolState.token0Mint,
+        poolState.token1Mint,
+        poolState.token1Program,
+        amount_out,
+        amount_in,
+        repayIx
+      );
+      assert.fail("the invariant must not decrease");
+    } catch (err) {
+      assert.include(err.toString(), "RequireGteViolated");
+    }
+    const poolStateAfter = await program.account.poolState.fetch(poolAddress);
+    assert.equal(
+      poolStateAfter.reserve0.toString(),
+      poolStateBefore.reserve0.toString()
+    );
+    assert.equal(
+      poolStateAfter.reserve1.toString(),
+      poolStateBefore.reserve1.toString()
+    );
+    assert.isFalse(poolStateAfter.locked);
+  });
+});
+
+function sleep(ms: number): Promise<void> {
+  return new Promise((resolve) => setTimeout(resolve, ms));
+}
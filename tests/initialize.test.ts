@@ -1,6 +1,7 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
-import { getAccount } from "@solana/spl-token";
+import { getAccount, getMint, TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { Keypair } from "@solana/web3.js";
 import { BN } from "bn.js";
 import { assert } from "chai";
 import { Cpmm } from "../target/types/cpmm";
@@ -65,5 +66,167 @@ describe("initialize", () => {
       poolState.token1Program
     );
     assert.equal(vault1.amount.toString(), initAmount1.toString());
+
+    // the locked lp is minted too, so the lp mint supply matches the pool
+    const lpMint = await getMint(
+      anchor.getProvider().connection,
+      poolState.lpMint,
+      "processed",
+      TOKEN_PROGRAM_ID
+    );
+    assert.equal(lpMint.supply.toString(), poolState.lpSupply.toString());
+  });
+
+  it("create stable pool", async () => {
+    const { token0, token0Program, token1, token1Program } =
+      await setupInitializeTest(
+        program,
+        anchor.getProvider().connection,
+        owner,
+        {
+          config_index: 0,
+          tradeFeeRate: new BN(10),
+          protocolFeeRate: new BN(1000),
+          fundFeeRate: new BN(25000),
+          create_fee: new BN(0),
+        },
+        { transferFeeBasisPoints: 0, MaxFee: 0 },
+        confirmOptions
+      );
+
+    const initAmount0 = new BN(10000000000);
+    const initAmount1 = new BN(10000000000);
+    const { poolState } = await initialize(
+      program,
+      owner,
+      0,
+      token0,
+      token0Program,
+      token1,
+      token1Program,
+      confirmOptions,
+      { initAmount0, initAmount1 },
+      { curveType: 1, curveParams: [new BN(100), new BN(0)] }
+    );
+
+    assert.equal(poolState.curveType, 1);
+    assert.equal(poolState.curveParams[0].toString(), "100");
+  });
+
+  it("create pool charges the create pool fee", async () => {
+    const connection = anchor.getProvider().connection;
+    const configIndex = 4;
+    const createPoolFee = new BN(100000000);
+    const { token0, token0Program, token1, token1Program } =
+      await setupInitializeTest(
+        program,
+        connection,
+        owner,
+        {
+          config_index: configIndex,
+          tradeFeeRate: new BN(10),
+          protocolFeeRate: new BN(1000),
+          fundFeeRate: new BN(25000),
+          create_fee: createPoolFee,
+        },
+        { transferFeeBasisPoints: 0, MaxFee: 0 },
+        confirmOptions
+      );
+    const receiver = Keypair.generate();
+    await program.methods
+      .setCreatePoolFeeReceiver(configIndex, receiver.publicKey)
+      .accounts({ owner: owner.publicKey })
+      .rpc(confirmOptions);
+
+    try {
+      await program.methods
+        .initialize(
+          configIndex,
+          new BN(10000000000),
+          new BN(10000000000),
+          new BN(0),
+          0,
+          [new BN(0), new BN(0)],
+          new BN(0),
+          null
+        )
+        .accounts({
+          creator: owner.publicKey,
+          createPoolFeeReceiver: owner.publicKey,
+          token0Mint: token0,
+          token1Mint: token1,
+          token0Program: token0Program,
+          token1Program: token1Program,
+        })
+        .rpc();
+      assert.fail("the fee must go to the receiver of the config");
+    } catch (err) {
+      assert.include(err.toString(), "InvalidInput");
+    }
+
+    await initialize(
+      program,
+      owner,
+      configIndex,
+      token0,
+      token0Program,
+      token1,
+      token1Program,
+      confirmOptions
+    );
+    const receiverBalance = await connection.getBalance(
+      receiver.publicKey,
+      "processed"
+    );
+    assert.equal(receiverBalance, createPoolFee.toNumber());
+  });
+
+  it("create pool with a launch fee above the config limit is rejected", async () => {
+    const { configAddress, token0, token0Program, token1, token1Program } =
+      await setupInitializeTest(
+        program,
+        anchor.getProvider().connection,
+        owner,
+        {
+          config_index: 0,
+          tradeFeeRate: new BN(10),
+          protocolFeeRate: new BN(1000),
+          fundFeeRate: new BN(25000),
+          create_fee: new BN(0),
+        },
+        { transferFeeBasisPoints: 0, MaxFee: 0 },
+        confirmOptions
+      );
+    const ammConfig = await program.account.ammConfig.fetch(configAddress);
+    try {
+      await program.methods
+        .initialize(
+          0,
+          new BN(10000000000),
+          new BN(10000000000),
+          new BN(0),
+          0,
+          [new BN(0), new BN(0)],
+          new BN(0),
+          {
+            feeRate: ammConfig.maxLaunchFeeRate.addn(1),
+            duration: ammConfig.maxLaunchFeeDuration,
+            decay: 0,
+            surplusToCreator: true,
+          }
+        )
+        .accounts({
+          creator: owner.publicKey,
+          createPoolFeeReceiver: ammConfig.createPoolFeeReceiver,
+          token0Mint: token0,
+          token1Mint: token1,
+          token0Program: token0Program,
+          token1Program: token1Program,
+        })
+        .rpc();
+      assert.fail("the launch fee must be within the config limits");
+    } catch (err) {
+      assert.include(err.toString(), "LaunchFeeTooHigh");
+    }
   });
 });
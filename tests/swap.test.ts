@@ -1,9 +1,22 @@
 import * as anchor from "@coral-xyz/anchor";
 import { BN, Program } from "@coral-xyz/anchor";
-import { getAccount, getAssociatedTokenAddressSync } from "@solana/spl-token";
+import {
+  getAccount,
+  getAssociatedTokenAddressSync,
+  getOrCreateAssociatedTokenAccount,
+  transferChecked,
+} from "@solana/spl-token";
+import { Keypair, PublicKey } from "@solana/web3.js";
 import { assert } from "chai";
 import { Cpmm } from "../target/types/cpmm";
-import { setupSwapTest, swap_base_input, swap_base_output } from "./utils";
+import {
+  setupSwapTest,
+  skim,
+  swap_base_input,
+  swap_base_input_to_recipient,
+  swap_base_output,
+  swap_route,
+} from "./utils";
 
 
 describe("swap test", () => {
@@ -166,6 +179,491 @@ describe("swap test", () => {
         console.log("input token change",inputTokenAccountBefore.amount - inputTokenAccountAfter.amount);
   });
 
+  it("swap base input one for zero", async () => {
+    const { poolState } = await setupSwapTest(
+      program,
+      anchor.getProvider().connection,
+      owner,
+      {
+        config_index: 0,
+        tradeFeeRate: new BN(10),
+        protocolFeeRate: new BN(1000),
+        fundFeeRate: new BN(25000),
+        create_fee: new BN(0),
+      },
+      { transferFeeBasisPoints: 0, MaxFee: 0 }
+    );
+    const inputToken = poolState.token1Mint;
+    const inputTokenProgram = poolState.token1Program;
+    const outputToken = poolState.token0Mint;
+    const outputTokenProgram = poolState.token0Program;
+    const inputTokenAccountAddr = getAssociatedTokenAddressSync(
+      inputToken,
+      owner.publicKey,
+      false,
+      inputTokenProgram
+    );
+    const outputTokenAccountAddr = getAssociatedTokenAddressSync(
+      outputToken,
+      owner.publicKey,
+      false,
+      outputTokenProgram
+    );
+    const inputTokenAccountBefore = await getAccount(
+      anchor.getProvider().connection,
+      inputTokenAccountAddr,
+      "processed",
+      inputTokenProgram
+    );
+    const outputTokenAccountBefore = await getAccount(
+      anchor.getProvider().connection,
+      outputTokenAccountAddr,
+      "processed",
+      outputTokenProgram
+    );
+    await sleep(1000);
+    let amount_in = new BN(100000000);
+    await swap_base_input(
+      program,
+      owner,
+      0,
+      inputToken,
+      inputTokenProgram,
+      outputToken,
+      outputTokenProgram,
+      amount_in,
+      new BN(0),
+      confirmOptions
+    );
+    const inputTokenAccountAfter = await getAccount(
+      anchor.getProvider().connection,
+      inputTokenAccountAddr,
+      "processed",
+      inputTokenProgram
+    );
+    const outputTokenAccountAfter = await getAccount(
+      anchor.getProvider().connection,
+      outputTokenAccountAddr,
+      "processed",
+      outputTokenProgram
+    );
+    assert.equal(
+      inputTokenAccountBefore.amount - inputTokenAccountAfter.amount,
+      BigInt(amount_in.toString())
+    );
+    assert(outputTokenAccountAfter.amount > outputTokenAccountBefore.amount);
+  });
+
+  it("swap route through one pool", async () => {
+    const { poolState } = await setupSwapTest(
+      program,
+      anchor.getProvider().connection,
+      owner,
+      {
+        config_index: 0,
+        tradeFeeRate: new BN(10),
+        protocolFeeRate: new BN(1000),
+        fundFeeRate: new BN(25000),
+        create_fee: new BN(0),
+      },
+      { transferFeeBasisPoints: 0, MaxFee: 0 }
+    );
+    const inputToken = poolState.token0Mint;
+    const inputTokenProgram = poolState.token0Program;
+    const outputToken = poolState.token1Mint;
+    const outputTokenProgram = poolState.token1Program;
+    const inputTokenAccountAddr = getAssociatedTokenAddressSync(
+      inputToken,
+      owner.publicKey,
+      false,
+      inputTokenProgram
+    );
+    const outputTokenAccountAddr = getAssociatedTokenAddressSync(
+      outputToken,
+      owner.publicKey,
+      false,
+      outputTokenProgram
+    );
+    const inputTokenAccountBefore = await getAccount(
+      anchor.getProvider().connection,
+      inputTokenAccountAddr,
+      "processed",
+      inputTokenProgram
+    );
+    const outputTokenAccountBefore = await getAccount(
+      anchor.getProvider().connection,
+      outputTokenAccountAddr,
+      "processed",
+      outputTokenProgram
+    );
+    await sleep(1000);
+    let amount_in = new BN(100000000);
+    await swap_route(
+      program,
+      owner,
+      inputToken,
+      inputTokenProgram,
+      [{ config_index: 0, outputToken, outputTokenProgram }],
+      amount_in,
+      new BN(1),
+      confirmOptions
+    );
+    const inputTokenAccountAfter = await getAccount(
+      anchor.getProvider().connection,
+      inputTokenAccountAddr,
+      "processed",
+      inputTokenProgram
+    );
+    const outputTokenAccountAfter = await getAccount(
+      anchor.getProvider().connection,
+      outputTokenAccountAddr,
+      "processed",
+      outputTokenProgram
+    );
+    assert.equal(
+      inputTokenAccountBefore.amount - inputTokenAccountAfter.amount,
+      BigInt(amount_in.toString())
+    );
+    assert(outputTokenAccountAfter.amount > outputTokenAccountBefore.amount);
+  });
+
+  it("swap base input to the accounts of other owners", async () => {
+    const { poolState } = await setupSwapTest(
+      program,
+      anchor.getProvider().connection,
+      owner,
+      {
+        config_index: 0,
+        tradeFeeRate: new BN(10),
+        protocolFeeRate: new BN(1000),
+        fundFeeRate: new BN(25000),
+        create_fee: new BN(0),
+      },
+      { transferFeeBasisPoints: 0, MaxFee: 0 }
+    );
+    const connection = anchor.getProvider().connection;
+    const thirdParty = Keypair.generate().publicKey;
+    const [pda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("recipient")],
+      program.programId
+    );
+    await sleep(1000);
+    for (const recipient of [thirdParty, pda]) {
+      const outputTokenAccount = await getOrCreateAssociatedTokenAccount(
+        connection,
+        owner,
+        poolState.token1Mint,
+        recipient,
+        true,
+        "processed",
+        confirmOptions,
+        poolState.token1Program
+      );
+      const amount_in = new BN(100000000);
+      await swap_base_input_to_recipient(
+        program,
+        owner,
+        0,
+        poolState.token0Mint,
+        poolState.token0Program,
+        poolState.token1Mint,
+        poolState.token1Program,
+        recipient,
+        outputTokenAccount.address,
+        amount_in,
+        new BN(1),
+        confirmOptions
+      );
+      const outputTokenAccountAfter = await getAccount(
+        connection,
+        outputTokenAccount.address,
+        "processed",
+        poolState.token1Program
+      );
+      assert(outputTokenAccountAfter.amount > outputTokenAccount.amount);
+    }
+  });
+
+  it("swap base input to an account of the wrong mint is rejected", async () => {
+    const { poolState } = await setupSwapTest(
+      program,
+      anchor.getProvider().connection,
+      owner,
+      {
+        config_index: 0,
+        tradeFeeRate: new BN(10),
+        protocolFeeRate: new BN(1000),
+        fundFeeRate: new BN(25000),
+        create_fee: new BN(0),
+      },
+      { transferFeeBasisPoints: 0, MaxFee: 0 }
+    );
+    const recipient = Keypair.generate().publicKey;
+    // an account of the input mint instead of the output mint
+    const wrongMintAccount = await getOrCreateAssociatedTokenAccount(
+      anchor.getProvider().connection,
+      owner,
+      poolState.token0Mint,
+      recipient,
+      false,
+      "processed",
+      confirmOptions,
+      poolState.token0Program
+    );
+    try {
+      await swap_base_input_to_recipient(
+        program,
+        owner,
+        0,
+        poolState.token0Mint,
+        poolState.token0Program,
+        poolState.token1Mint,
+        poolState.token1Program,
+        recipient,
+        wrongMintAccount.address,
+        new BN(100000000),
+        new BN(1)
+      );
+      assert.fail("the output account must be of the output mint");
+    } catch (err) {
+      assert.include(err.toString(), "ConstraintTokenMint");
+    }
+  });
+
+  it("swap base input pays the referral fee to any referrer", async () => {
+    const { poolState } = await setupSwapTest(
+      program,
+      anchor.getProvider().connection,
+      owner,
+      {
+        config_index: 0,
+        tradeFeeRate: new BN(10),
+        protocolFeeRate: new BN(1000),
+        fundFeeRate: new BN(25000),
+        create_fee: new BN(0),
+      },
+      { transferFeeBasisPoints: 0, MaxFee: 0 }
+    );
+    const connection = anchor.getProvider().connection;
+    await sleep(1000);
+
+    // the payer may refer their own swap, taking the referral fee as a rebate
+    const ownReferrerAccount = getAssociatedTokenAddressSync(
+      poolState.token0Mint,
+      owner.publicKey,
+      false,
+      poolState.token0Program
+    );
+    await swap_base_input(
+      program,
+      owner,
+      0,
+      poolState.token0Mint,
+      poolState.token0Program,
+      poolState.token1Mint,
+      poolState.token1Program,
+      new BN(100000000),
+      new BN(1),
+      confirmOptions,
+      ownReferrerAccount
+    );
+
+    const referrerAccount = await getOrCreateAssociatedTokenAccount(
+      connection,
+      owner,
+      poolState.token0Mint,
+      Keypair.generate().publicKey,
+      false,
+      "processed",
+      confirmOptions,
+      poolState.token0Program
+    );
+    await swap_base_input(
+      program,
+      owner,
+      0,
+      poolState.token0Mint,
+      poolState.token0Program,
+      poolState.token1Mint,
+      poolState.token1Program,
+      new BN(100000000),
+      new BN(1),
+      confirmOptions,
+      referrerAccount.address
+    );
+  });
+
+  it("swap base input after its deadline is rejected", async () => {
+    const { poolAddress, poolState } = await setupSwapTest(
+      program,
+      anchor.getProvider().connection,
+      owner,
+      {
+        config_index: 0,
+        tradeFeeRate: new BN(10),
+        protocolFeeRate: new BN(1000),
+        fundFeeRate: new BN(25000),
+        create_fee: new BN(0),
+      },
+      { transferFeeBasisPoints: 0, MaxFee: 0 }
+    );
+    await sleep(1000);
+    const deadline = new BN(Math.floor(Date.now() / 1000) - 60);
+    try {
+      await program.methods
+        .swapBaseInputWithDeadline(0, new BN(100000000), new BN(1), deadline)
+        .accounts({
+          payer: owner.publicKey,
+          poolState: poolAddress,
+          inputTokenProgram: poolState.token0Program,
+          outputTokenProgram: poolState.token1Program,
+          inputTokenMint: poolState.token0Mint,
+          outputTokenMint: poolState.token1Mint,
+          observationState: poolState.observationKey,
+          referrerTokenAccount: null,
+        })
+        .rpc();
+      assert.fail("the deadline has passed");
+    } catch (err) {
+      assert.include(err.toString(), "DeadlineExceeded");
+    }
+  });
+
+  it("swap base output with transfer fee", async () => {
+    const { poolState } = await setupSwapTest(
+      program,
+      anchor.getProvider().connection,
+      owner,
+      {
+        config_index: 0,
+        tradeFeeRate: new BN(10),
+        protocolFeeRate: new BN(1000),
+        fundFeeRate: new BN(25000),
+        create_fee: new BN(0),
+      },
+      { transferFeeBasisPoints: 100, MaxFee: 100000000 }
+    );
+    const inputToken = poolState.token0Mint;
+    const inputTokenProgram = poolState.token0Program;
+    const outputToken = poolState.token1Mint;
+    const outputTokenProgram = poolState.token1Program;
+    const outputTokenAccountAddr = getAssociatedTokenAddressSync(
+      outputToken,
+      owner.publicKey,
+      false,
+      outputTokenProgram
+    );
+    const outputTokenAccountBefore = await getAccount(
+      anchor.getProvider().connection,
+      outputTokenAccountAddr,
+      "processed",
+      outputTokenProgram
+    );
+    await sleep(1000);
+    let amount_out = new BN(100000000);
+    await swap_base_output(
+      program,
+      owner,
+      0,
+      inputToken,
+      inputTokenProgram,
+      outputToken,
+      outputTokenProgram,
+      amount_out,
+      new BN(10000000000000),
+      confirmOptions
+    );
+    const outputTokenAccountAfter = await getAccount(
+      anchor.getProvider().connection,
+      outputTokenAccountAddr,
+      "processed",
+      outputTokenProgram
+    );
+    // the transfer fee of the output token is paid by the pool on top of amount_out
+    assert.equal(
+      outputTokenAccountAfter.amount - outputTokenAccountBefore.amount,
+      BigInt(amount_out.toString())
+    );
+  });
+
+  it("skim tokens sent to the vault directly", async () => {
+    const { poolAddress, poolState } = await setupSwapTest(
+      program,
+      anchor.getProvider().connection,
+      owner,
+      {
+        config_index: 0,
+        tradeFeeRate: new BN(10),
+        protocolFeeRate: new BN(1000),
+        fundFeeRate: new BN(25000),
+        create_fee: new BN(0),
+      },
+      { transferFeeBasisPoints: 0, MaxFee: 0 }
+    );
+    const token0AccountAddr = getAssociatedTokenAddressSync(
+      poolState.token0Mint,
+      owner.publicKey,
+      false,
+      poolState.token0Program
+    );
+    const token1AccountAddr = getAssociatedTokenAddressSync(
+      poolState.token1Mint,
+      owner.publicKey,
+      false,
+      poolState.token1Program
+    );
+    const donation = 1000000;
+    await transferChecked(
+      anchor.getProvider().connection,
+      owner,
+      token0AccountAddr,
+      poolState.token0Mint,
+      poolState.token0Vault,
+      owner,
+      donation,
+      9,
+      [],
+      confirmOptions,
+      poolState.token0Program
+    );
+    const poolStateBefore = await program.account.poolState.fetch(poolAddress);
+    const token0AccountBefore = await getAccount(
+      anchor.getProvider().connection,
+      token0AccountAddr,
+      "processed",
+      poolState.token0Program
+    );
+
+    await skim(
+      program,
+      poolAddress,
+      poolState.token0Vault,
+      poolState.token1Vault,
+      poolState.token0Mint,
+      poolState.token0Program,
+      poolState.token1Mint,
+      poolState.token1Program,
+      token0AccountAddr,
+      token1AccountAddr,
+      confirmOptions
+    );
+    const poolStateAfter = await program.account.poolState.fetch(poolAddress);
+    const token0AccountAfter = await getAccount(
+      anchor.getProvider().connection,
+      token0AccountAddr,
+      "processed",
+      poolState.token0Program
+    );
+    // the donation is not part of the reserves and is sent back out
+    assert.equal(
+      poolStateAfter.reserve0.toString(),
+      poolStateBefore.reserve0.toString()
+    );
+    assert.equal(
+      token0AccountAfter.amount - token0AccountBefore.amount,
+      BigInt(donation)
+    );
+  });
 });
 
 function sleep(ms: number): Promise<void> {
@@ -1,17 +1,25 @@
-import { BN, Program } from "@coral-xyz/anchor";
+import { AnchorProvider, BN, Program } from "@coral-xyz/anchor";
+import { getAssociatedTokenAddressSync } from "@solana/spl-token";
 import {
   ConfirmOptions,
   Connection,
   Keypair,
   PublicKey,
   Signer,
+  TransactionInstruction,
 } from "@solana/web3.js";
 import { Cpmm } from "../../target/types/cpmm";
+import { FlashReceiver } from "../../target/types/flash_receiver";
 import {
   accountExist,
   createTokenMintAndAssociatedTokenAccount,
   getAmmConfigAddress,
+  getOrcleAccountAddress,
   getPoolAddress,
+  getPoolAddressBySortedMints,
+  getPoolVaultAddress,
+  getProgramDataAddress,
+  getProtocolAdminAddress,
   sendTransaction,
 } from "./index";
 
@@ -45,6 +53,9 @@ export async function setupInitializeTest(
     owner,
     config.config_index,
     config.tradeFeeRate,
+    config.protocolFeeRate,
+    config.fundFeeRate,
+    config.create_fee,
     confirmOptions
   );
   return {
@@ -56,12 +67,41 @@ export async function setupInitializeTest(
   };
 }
 
+// the local wallet deploys the program, so it is also the upgrade authority
+export async function initProtocolAdmin(
+  program: Program<Cpmm>,
+  connection: Connection,
+  owner: Signer,
+  confirmOptions?: ConfirmOptions
+): Promise<PublicKey> {
+  const [address, _] = await getProtocolAdminAddress(program.programId);
+  if (await accountExist(connection, address)) {
+    return address;
+  }
+  const [programData] = await getProgramDataAddress(program.programId);
+
+  const ix = await program.methods
+    .initProtocolAdmin(owner.publicKey)
+    .accounts({
+      payer: owner.publicKey,
+      programData,
+    })
+    .instruction();
+
+  const tx = await sendTransaction(connection, [ix], [owner], confirmOptions);
+  console.log("init protocol admin tx: ", tx);
+  return address;
+}
+
 export async function createAmmConfig(
   program: Program<Cpmm>,
   connection: Connection,
   owner: Signer,
   config_index: number,
   tradeFeeRate: BN,
+  protocolFeeRate: BN,
+  fundFeeRate: BN,
+  createPoolFee: BN,
   confirmOptions?: ConfirmOptions
 ): Promise<PublicKey> {
   const [address, _] = await getAmmConfigAddress(
@@ -71,9 +111,16 @@ export async function createAmmConfig(
   if (await accountExist(connection, address)) {
     return address;
   }
+  await initProtocolAdmin(program, connection, owner, confirmOptions);
 
   const ix = await program.methods
-    .createAmmConfig(config_index, tradeFeeRate)
+    .createAmmConfig(
+      config_index,
+      tradeFeeRate,
+      protocolFeeRate,
+      fundFeeRate,
+      createPoolFee
+    )
     .accounts({
       owner: owner.publicKey,
       // ammConfig: address,
@@ -98,6 +145,10 @@ export async function initialize(
   initAmount: { initAmount0: BN; initAmount1: BN } = {
     initAmount0: new BN(10000000000),
     initAmount1: new BN(20000000000),
+  },
+  curve: { curveType: number; curveParams: BN[] } = {
+    curveType: 0,
+    curveParams: [new BN(0), new BN(0)],
   }
 ) {
   const [ammConfigAddress] = await getAmmConfigAddress(
@@ -113,6 +164,7 @@ export async function initialize(
     program.programId
   );
   console.log("getPoolAddress:", poolAddress);
+  const ammConfig = await program.account.ammConfig.fetch(ammConfigAddress);
 
   /* console.log(
     "initialize1:",
@@ -133,10 +185,15 @@ export async function initialize(
         config_index,
         initAmount.initAmount0,
         initAmount.initAmount1,
-        new BN(0)
+        new BN(0),
+        curve.curveType,
+        curve.curveParams,
+        new BN(0),
+        null
       )
       .accounts({
         creator: creator.publicKey,
+        createPoolFeeReceiver: ammConfig.createPoolFeeReceiver,
         token0Mint: token0,
         token1Mint: token1,
         token0Program: token0Program,
@@ -175,6 +232,10 @@ export async function setupDepositTest(
   tokenProgramRequired?: {
     token0Program: PublicKey;
     token1Program: PublicKey;
+  },
+  curve: { curveType: number; curveParams: BN[] } = {
+    curveType: 0,
+    curveParams: [new BN(0), new BN(0)],
   }
 ) {
   const configAddress = await createAmmConfig(
@@ -183,6 +244,9 @@ export async function setupDepositTest(
     owner,
     config.config_index,
     config.tradeFeeRate,
+    config.protocolFeeRate,
+    config.fundFeeRate,
+    config.create_fee,
     confirmOptions
   );
 
@@ -209,7 +273,8 @@ export async function setupDepositTest(
           token1,
           token1Program,
           confirmOptions,
-          initAmount
+          initAmount,
+          curve
         );
       }
     } else {
@@ -222,7 +287,8 @@ export async function setupDepositTest(
         token1,
         token1Program,
         confirmOptions,
-        initAmount
+        initAmount,
+        curve
       );
     }
   }
@@ -261,6 +327,122 @@ export async function deposit(
   return tx;
 }
 
+export async function deposit_by_token_amounts(
+  program: Program<Cpmm>,
+  owner: Signer,
+  config_index: number,
+  token0Mint: PublicKey,
+  token0Program: PublicKey,
+  token1Mint: PublicKey,
+  token1Program: PublicKey,
+  maximum_token_0_amount: BN,
+  maximum_token_1_amount: BN,
+  minimum_lp_out: BN,
+  confirmOptions?: ConfirmOptions
+) {
+  const tx = await program.methods
+    .depositByTokenAmounts(
+      config_index,
+      maximum_token_0_amount,
+      maximum_token_1_amount,
+      minimum_lp_out,
+      null
+    )
+    .accounts({
+      owner: owner.publicKey,
+      token0Mint: token0Mint,
+      token1Mint: token1Mint,
+      token0Program: token0Program,
+      token1Program: token1Program,
+    })
+    .rpc(confirmOptions);
+
+  console.log("deposit by token amounts tx:", tx);
+  return tx;
+}
+
+export async function deposit_single_token(
+  program: Program<Cpmm>,
+  owner: Signer,
+  config_index: number,
+  inputToken: PublicKey,
+  inputTokenProgram: PublicKey,
+  otherToken: PublicKey,
+  amount_in: BN,
+  minimum_lp_out: BN,
+  confirmOptions?: ConfirmOptions
+) {
+  const [ammConfigAddress] = await getAmmConfigAddress(
+    config_index,
+    program.programId
+  );
+  const [poolAddress] = await getPoolAddressBySortedMints(
+    ammConfigAddress,
+    inputToken,
+    otherToken,
+    program.programId
+  );
+  const [observationAddress] = await getOrcleAccountAddress(
+    poolAddress,
+    program.programId
+  );
+  const tx = await program.methods
+    .depositSingleToken(config_index, amount_in, minimum_lp_out, null)
+    .accounts({
+      owner: owner.publicKey,
+      poolState: poolAddress,
+      inputTokenProgram: inputTokenProgram,
+      inputTokenMint: inputToken,
+      outputTokenMint: otherToken,
+      observationState: observationAddress,
+    })
+    .rpc(confirmOptions);
+
+  console.log("deposit single token tx:", tx);
+  return tx;
+}
+
+export async function simulate_deposit_single_token(
+  program: Program<Cpmm>,
+  owner: Signer,
+  config_index: number,
+  inputToken: PublicKey,
+  inputTokenProgram: PublicKey,
+  otherToken: PublicKey,
+  amount_in: BN,
+  minimum_lp_out: BN
+) {
+  const [ammConfigAddress] = await getAmmConfigAddress(
+    config_index,
+    program.programId
+  );
+  const [poolAddress] = await getPoolAddressBySortedMints(
+    ammConfigAddress,
+    inputToken,
+    otherToken,
+    program.programId
+  );
+  const [observationAddress] = await getOrcleAccountAddress(
+    poolAddress,
+    program.programId
+  );
+  const tx = await program.methods
+    .depositSingleToken(config_index, amount_in, minimum_lp_out, null)
+    .accounts({
+      owner: owner.publicKey,
+      poolState: poolAddress,
+      inputTokenProgram: inputTokenProgram,
+      inputTokenMint: inputToken,
+      outputTokenMint: otherToken,
+      observationState: observationAddress,
+    })
+    .transaction();
+
+  const provider = program.provider as AnchorProvider;
+  const simulation = await provider.simulate(tx, [owner]);
+  return simulation.unitsConsumed;
+}
+
 export async function withdraw(
   program: Program<Cpmm>,
   config_index: number,
@@ -295,6 +477,52 @@ export async function withdraw(
   return tx;
 }
 
+export async function withdraw_single_token(
+  program: Program<Cpmm>,
+  owner: Signer,
+  config_index: number,
+  outputToken: PublicKey,
+  outputTokenProgram: PublicKey,
+  otherToken: PublicKey,
+  lp_token_amount: BN,
+  minimum_amount_out: BN,
+  confirmOptions?: ConfirmOptions
+) {
+  const [ammConfigAddress] = await getAmmConfigAddress(
+    config_index,
+    program.programId
+  );
+  const [poolAddress] = await getPoolAddressBySortedMints(
+    ammConfigAddress,
+    otherToken,
+    outputToken,
+    program.programId
+  );
+  const [observationAddress] = await getOrcleAccountAddress(
+    poolAddress,
+    program.programId
+  );
+  const tx = await program.methods
+    .withdrawSingleToken(
+      config_index,
+      lp_token_amount,
+      minimum_amount_out,
+      null
+    )
+    .accounts({
+      owner: owner.publicKey,
+      poolState: poolAddress,
+      inputTokenMint: otherToken,
+      outputTokenMint: outputToken,
+      outputTokenProgram: outputTokenProgram,
+      observationState: observationAddress,
+    })
+    .rpc(confirmOptions);
+
+  console.log("withdraw single token tx:", tx);
+  return tx;
+}
+
 export async function setupSwapTest(
   program: Program<Cpmm>,
   connection: Connection,
@@ -318,6 +546,9 @@ export async function setupSwapTest(
     owner,
     config.config_index,
     config.tradeFeeRate,
+    config.protocolFeeRate,
+    config.fundFeeRate,
+    config.create_fee,
     confirmOptions
   );
 
@@ -366,15 +597,30 @@ export async function swap_base_input(
   outputTokenProgram: PublicKey,
   amount_in: BN,
   minimum_amount_out: BN,
-  confirmOptions?: ConfirmOptions
+  confirmOptions?: ConfirmOptions,
+  referrerTokenAccount: PublicKey | null = null
 ) {
+  const [ammConfigAddress] = await getAmmConfigAddress(
+    config_index,
+    program.programId
+  );
+  const [poolAddress] = await getPoolAddressBySortedMints(
+    ammConfigAddress,
+    inputToken,
+    outputToken,
+    program.programId
+  );
+  const [observationAddress] = await getOrcleAccountAddress(
+    poolAddress,
+    program.programId
+  );
   const tx = await program.methods
     .swapBaseInput(config_index, amount_in, minimum_amount_out)
     .accounts({
       payer: owner.publicKey,
       // authority: auth,
       // ammConfig: configAddress,
-      // poolState: poolAddress,
+      poolState: poolAddress,
       // inputTokenAccount,
       // outputTokenAccount,
       // inputVault,
@@ -383,7 +629,8 @@ export async function swap_base_input(
       outputTokenProgram: outputTokenProgram,
       inputTokenMint: inputToken,
       outputTokenMint: outputToken,
-      // observationState: observationAddress,
+      observationState: observationAddress,
+      referrerTokenAccount,
     })
     .rpc(confirmOptions);
 
@@ -402,16 +649,361 @@ export async function swap_base_output(
   max_amount_in: BN,
   confirmOptions?: ConfirmOptions
 ) {
+  const [ammConfigAddress] = await getAmmConfigAddress(
+    config_index,
+    program.programId
+  );
+  const [poolAddress] = await getPoolAddressBySortedMints(
+    ammConfigAddress,
+    inputToken,
+    outputToken,
+    program.programId
+  );
+  const [observationAddress] = await getOrcleAccountAddress(
+    poolAddress,
+    program.programId
+  );
   const tx = await program.methods
     .swapBaseOutput(config_index, amount_out_less_fee, max_amount_in)
     .accounts({
       payer: owner.publicKey,
+      poolState: poolAddress,
+      inputTokenProgram: inputTokenProgram,
+      outputTokenProgram: outputTokenProgram,
+      inputTokenMint: inputToken,
+      outputTokenMint: outputToken,
+      observationState: observationAddress,
+      referrerTokenAccount: null,
+    })
+    .rpc(confirmOptions);
+
+  return tx;
+}
+
+export async function swap_base_input_to_recipient(
+  program: Program<Cpmm>,
+  owner: Signer,
+  config_index: number,
+  inputToken: PublicKey,
+  inputTokenProgram: PublicKey,
+  outputToken: PublicKey,
+  outputTokenProgram: PublicKey,
+  recipient: PublicKey,
+  outputTokenAccount: PublicKey,
+  amount_in: BN,
+  minimum_amount_out: BN,
+  confirmOptions?: ConfirmOptions
+) {
+  const [ammConfigAddress] = await getAmmConfigAddress(
+    config_index,
+    program.programId
+  );
+  const [poolAddress] = await getPoolAddressBySortedMints(
+    ammConfigAddress,
+    inputToken,
+    outputToken,
+    program.programId
+  );
+  const [observationAddress] = await getOrcleAccountAddress(
+    poolAddress,
+    program.programId
+  );
+  const inputTokenAccount = getAssociatedTokenAddressSync(
+    inputToken,
+    owner.publicKey,
+    false,
+    inputTokenProgram
+  );
+  const tx = await program.methods
+    .swapBaseInputToRecipient(
+      config_index,
+      amount_in,
+      minimum_amount_out,
+      null
+    )
+    .accounts({
+      payer: owner.publicKey,
+      recipient,
+      poolState: poolAddress,
+      inputTokenAccount,
+      outputTokenAccount,
       inputTokenProgram: inputTokenProgram,
       outputTokenProgram: outputTokenProgram,
       inputTokenMint: inputToken,
       outputTokenMint: outputToken,
+      observationState: observationAddress,
+      referrerTokenAccount: null,
+    })
+    .rpc(confirmOptions);
+
+  return tx;
+}
+
+export async function swap_route(
+  program: Program<Cpmm>,
+  owner: Signer,
+  inputToken: PublicKey,
+  inputTokenProgram: PublicKey,
+  hops: {
+    config_index: number;
+    outputToken: PublicKey;
+    outputTokenProgram: PublicKey;
+  }[],
+  amount_in: BN,
+  minimum_amount_out: BN,
+  confirmOptions?: ConfirmOptions
+) {
+  const remainingAccounts = [];
+  let hopInputToken = inputToken;
+  for (const hop of hops) {
+    const [ammConfigAddress] = await getAmmConfigAddress(
+      hop.config_index,
+      program.programId
+    );
+    const [poolAddress] = await getPoolAddressBySortedMints(
+      ammConfigAddress,
+      hopInputToken,
+      hop.outputToken,
+      program.programId
+    );
+    const [inputVault] = await getPoolVaultAddress(
+      poolAddress,
+      hopInputToken,
+      program.programId
+    );
+    const [outputVault] = await getPoolVaultAddress(
+      poolAddress,
+      hop.outputToken,
+      program.programId
+    );
+    const [observationAddress] = await getOrcleAccountAddress(
+      poolAddress,
+      program.programId
+    );
+    remainingAccounts.push(
+      { pubkey: ammConfigAddress, isSigner: false, isWritable: false },
+      { pubkey: poolAddress, isSigner: false, isWritable: true },
+      { pubkey: inputVault, isSigner: false, isWritable: true },
+      { pubkey: outputVault, isSigner: false, isWritable: true },
+      { pubkey: hop.outputToken, isSigner: false, isWritable: false },
+      { pubkey: hop.outputTokenProgram, isSigner: false, isWritable: false },
+      { pubkey: observationAddress, isSigner: false, isWritable: true }
+    );
+    hopInputToken = hop.outputToken;
+  }
+  const lastHop = hops[hops.length - 1];
+  const tx = await program.methods
+    .swapRoute(amount_in, minimum_amount_out, null)
+    .accounts({
+      payer: owner.publicKey,
+      inputTokenProgram: inputTokenProgram,
+      outputTokenProgram: lastHop.outputTokenProgram,
+      inputTokenMint: inputToken,
+      outputTokenMint: lastHop.outputToken,
+    })
+    .remainingAccounts(remainingAccounts)
+    .rpc(confirmOptions);
+
+  return tx;
+}
+
+export async function sync(
+  program: Program<Cpmm>,
+  poolAddress: PublicKey,
+  token0Vault: PublicKey,
+  token1Vault: PublicKey,
+  confirmOptions?: ConfirmOptions
+) {
+  const tx = await program.methods
+    .sync()
+    .accounts({
+      poolState: poolAddress,
+      token0Vault,
+      token1Vault,
+    })
+    .rpc(confirmOptions);
+
+  return tx;
+}
+
+export async function skim(
+  program: Program<Cpmm>,
+  poolAddress: PublicKey,
+  token0Vault: PublicKey,
+  token1Vault: PublicKey,
+  token0: PublicKey,
+  token0Program: PublicKey,
+  token1: PublicKey,
+  token1Program: PublicKey,
+  recipientToken0Account: PublicKey,
+  recipientToken1Account: PublicKey,
+  confirmOptions?: ConfirmOptions
+) {
+  const tx = await program.methods
+    .skim()
+    .accounts({
+      poolState: poolAddress,
+      token0Vault,
+      token1Vault,
+      vault0Mint: token0,
+      vault1Mint: token1,
+      recipientToken0Account,
+      recipientToken1Account,
+      token0Program,
+      token1Program,
+    })
+    .rpc(confirmOptions);
+
+  return tx;
+}
+
+export async function flash_loan(
+  program: Program<Cpmm>,
+  owner: Signer,
+  config_index: number,
+  poolAddress: PublicKey,
+  token0Vault: PublicKey,
+  token1Vault: PublicKey,
+  token0: PublicKey,
+  token0Program: PublicKey,
+  token1: PublicKey,
+  token1Program: PublicKey,
+  amount_0: BN,
+  amount_1: BN,
+  receiverIx: TransactionInstruction,
+  confirmOptions?: ConfirmOptions
+) {
+  const tx = await program.methods
+    .flashLoan(config_index, amount_0, amount_1, receiverIx.data)
+    .accounts({
+      borrower: owner.publicKey,
+      poolState: poolAddress,
+      token0Vault,
+      token1Vault,
+      receiverToken0: getAssociatedTokenAddressSync(
+        token0,
+        owner.publicKey,
+        false,
+        token0Program
+      ),
+      receiverToken1: getAssociatedTokenAddressSync(
+        token1,
+        owner.publicKey,
+        false,
+        token1Program
+      ),
+      vault0Mint: token0,
+      vault1Mint: token1,
+      token0Program,
+      token1Program,
+      receiverProgram: receiverIx.programId,
+    })
+    .remainingAccounts(receiverIx.keys)
+    .rpc(confirmOptions);
+
+  console.log("flash loan tx:", tx);
+  return tx;
+}
+
+export async function flash_swap(
+  program: Program<Cpmm>,
+  owner: Signer,
+  config_index: number,
+  inputToken: PublicKey,
+  outputToken: PublicKey,
+  outputTokenProgram: PublicKey,
+  amount_out: BN,
+  max_amount_in: BN,
+  receiverIx: TransactionInstruction,
+  confirmOptions?: ConfirmOptions
+) {
+  const [ammConfigAddress] = await getAmmConfigAddress(
+    config_index,
+    program.programId
+  );
+  const [poolAddress] = await getPoolAddressBySortedMints(
+    ammConfigAddress,
+    inputToken,
+    outputToken,
+    program.programId
+  );
+  const [observationAddress] = await getOrcleAccountAddress(
+    poolAddress,
+    program.programId
+  );
+  const tx = await program.methods
+    .flashSwap(config_index, amount_out, max_amount_in, null, receiverIx.data)
+    .accounts({
+      payer: owner.publicKey,
+      poolState: poolAddress,
+      outputTokenAccount: getAssociatedTokenAddressSync(
+        outputToken,
+        owner.publicKey,
+        false,
+        outputTokenProgram
+      ),
+      outputTokenProgram,
+      inputTokenMint: inputToken,
+      outputTokenMint: outputToken,
+      observationState: observationAddress,
+      receiverProgram: receiverIx.programId,
     })
+    .remainingAccounts(receiverIx.keys)
     .rpc(confirmOptions);
 
+  console.log("flash swap tx:", tx);
   return tx;
 }
+
+/// The receiver instruction paying the owner's tokens into the pool vaults
+export async function flash_receiver_repay(
+  receiverProgram: Program<FlashReceiver>,
+  owner: Signer,
+  token0Vault: PublicKey,
+  token1Vault: PublicKey,
+  token0: PublicKey,
+  token0Program: PublicKey,
+  token1: PublicKey,
+  token1Program: PublicKey,
+  amount_0: BN,
+  amount_1: BN
+) {
+  return await receiverProgram.methods
+    .repay(amount_0, amount_1)
+    .accounts({
+      payer: owner.publicKey,
+      payerToken0: getAssociatedTokenAddressSync(
+        token0,
+        owner.publicKey,
+        false,
+        token0Program
+      ),
+      payerToken1: getAssociatedTokenAddressSync(
+        token1,
+        owner.publicKey,
+        false,
+        token1Program
+      ),
+      token0Vault,
+      token1Vault,
+      vault0Mint: token0,
+      vault1Mint: token1,
+      token0Program,
+      token1Program,
+    })
+    .instruction();
+}
+
+/// The receiver instruction invoking `ix` from inside the receiver
+export async function flash_receiver_reenter(
+  receiverProgram: Program<FlashReceiver>,
+  ix: TransactionInstruction
+) {
+  return await receiverProgram.methods
+    .reenter(ix.data)
+    .remainingAccounts([
+      { pubkey: ix.programId, isSigner: false, isWritable: false },
+      ...ix.keys,
+    ])
+    .instruction();
+}
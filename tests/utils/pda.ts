@@ -1,5 +1,11 @@
 import * as anchor from "@coral-xyz/anchor";
 import { PublicKey } from "@solana/web3.js";
+export const PROTOCOL_ADMIN_SEED = Buffer.from(
+  anchor.utils.bytes.utf8.encode("protocol_admin")
+);
+export const BPF_LOADER_UPGRADEABLE_PROGRAM_ID = new PublicKey(
+  "BPFLoaderUpgradeab1e11111111111111111111111"
+);
 export const AMM_CONFIG_SEED = Buffer.from(
   anchor.utils.bytes.utf8.encode("amm_config")
 );
@@ -53,6 +59,26 @@ export function i32ToBytes(num: number) {
   return new Uint8Array(arr);
 }
 
+export async function getProtocolAdminAddress(
+  programId: PublicKey
+): Promise<[PublicKey, number]> {
+  const [address, bump] = await PublicKey.findProgramAddress(
+    [PROTOCOL_ADMIN_SEED],
+    programId
+  );
+  return [address, bump];
+}
+
+export async function getProgramDataAddress(
+  programId: PublicKey
+): Promise<[PublicKey, number]> {
+  const [address, bump] = await PublicKey.findProgramAddress(
+    [programId.toBuffer()],
+    BPF_LOADER_UPGRADEABLE_PROGRAM_ID
+  );
+  return [address, bump];
+}
+
 export async function getAmmConfigAddress(
   index: number,
   programId: PublicKey
@@ -92,6 +118,19 @@ export async function getPoolAddress(
   return [address, bump];
 }
 
+// pools are always derived from the sorted mint pair, whatever the trade direction
+export async function getPoolAddressBySortedMints(
+  ammConfig: PublicKey,
+  tokenMintA: PublicKey,
+  tokenMintB: PublicKey,
+  programId: PublicKey
+): Promise<[PublicKey, number]> {
+  if (Buffer.compare(tokenMintA.toBuffer(), tokenMintB.toBuffer()) < 0) {
+    return getPoolAddress(ammConfig, tokenMintA, tokenMintB, programId);
+  }
+  return getPoolAddress(ammConfig, tokenMintB, tokenMintA, programId);
+}
+
 export async function getPoolVaultAddress(
   pool: PublicKey,
   vaultTokenMint: PublicKey,
@@ -7,6 +7,7 @@ import {
   isEqual,
   setupDepositTest,
   withdraw,
+  withdraw_single_token,
 } from "./utils";
 import { assert } from "chai";
 
@@ -159,4 +160,63 @@ describe("withdraw test",() => {
           )
         );
       });
+
+    it("withdraw lp into token 1 only", async () => {
+        const { poolAddress, poolState } = await setupDepositTest(
+            program,
+            anchor.getProvider().connection,
+            owner,
+            {
+              config_index: 0,
+              tradeFeeRate: new BN(10),
+              protocolFeeRate: new BN(1000),
+              fundFeeRate: new BN(25000),
+              create_fee: new BN(0),
+            },
+            { transferFeeBasisPoints: 0, MaxFee: 0 }
+        );
+
+        const {
+            onwerToken0Account: ownerToken0AccountBefore,
+            onwerToken1Account: ownerToken1AccountBefore,
+        } = await getUserAndPoolVaultAmount(
+            owner.publicKey,
+            poolState.token0Mint,
+            poolState.token0Program,
+            poolState.token1Mint,
+            poolState.token1Program,
+            poolState.token0Vault,
+            poolState.token1Vault
+        );
+
+        const lp_token_amount = new BN(10000000);
+        await withdraw_single_token(
+            program,
+            owner,
+            0,
+            poolState.token1Mint,
+            poolState.token1Program,
+            poolState.token0Mint,
+            lp_token_amount,
+            new BN(1),
+            confirmOptions
+        );
+        const newPoolState = await program.account.poolState.fetch(poolAddress);
+        assert(newPoolState.lpSupply.eq(poolState.lpSupply.sub(lp_token_amount)));
+
+        const {
+            onwerToken0Account: ownerToken0AccountAfter,
+            onwerToken1Account: ownerToken1AccountAfter,
+        } = await getUserAndPoolVaultAmount(
+            owner.publicKey,
+            poolState.token0Mint,
+            poolState.token0Program,
+            poolState.token1Mint,
+            poolState.token1Program,
+            poolState.token0Vault,
+            poolState.token1Vault
+        );
+        assert.equal(ownerToken0AccountAfter.amount, ownerToken0AccountBefore.amount);
+        assert(ownerToken1AccountAfter.amount > ownerToken1AccountBefore.amount);
+    });
 })